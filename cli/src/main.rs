@@ -8,8 +8,10 @@ use rand::rngs::OsRng;
 use zcash_protocol::consensus::Network;
 
 mod db;
+mod lightwalletd;
 mod rpc;
 mod scanner;
+mod server;
 
 #[derive(Parser)]
 #[command(name = "zcash-wallet")]
@@ -32,9 +34,19 @@ enum Commands {
         /// Account index (BIP32 level 3, ZIP32 account). Default: 0
         #[arg(long, default_value = "0")]
         account: u32,
-        /// Address index (diversifier index for shielded addresses). Default: 0
+        /// Address index (ZIP32 diversifier index, 0..2^88). Default: 0
         #[arg(long, default_value = "0")]
-        address_index: u32,
+        address_index: u128,
+        /// Optional BIP39 passphrase ("25th word"). Produces an entirely
+        /// different wallet from the same seed phrase.
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Current chain tip height, recorded as the account's birthday in
+        /// the wallet file. The `sync` command does not read it; it starts
+        /// from `--start-height` or the last scanned height. Defaults to no
+        /// birthday.
+        #[arg(long)]
+        birthday_height: Option<u32>,
     },
     /// Restore wallet from seed phrase
     Restore {
@@ -50,9 +62,94 @@ enum Commands {
         /// Account index (BIP32 level 3, ZIP32 account). Default: 0
         #[arg(long, default_value = "0")]
         account: u32,
-        /// Address index (diversifier index for shielded addresses). Default: 0
+        /// Address index (ZIP32 diversifier index, 0..2^88). Default: 0
+        #[arg(long, default_value = "0")]
+        address_index: u128,
+        /// Optional BIP39 passphrase ("25th word"). Must match the
+        /// passphrase used when the wallet was created.
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Height before which the account is known to have no activity,
+        /// recorded as its birthday in the wallet file. The `sync` command
+        /// does not read it. Defaults to no birthday - the safe choice for
+        /// a phrase of unknown age.
+        #[arg(long)]
+        birthday_height: Option<u32>,
+    },
+    /// Inspect a seed phrase without deriving any addresses
+    Inspect {
+        /// The seed phrase to inspect
+        #[arg(short, long)]
+        seed: String,
+        /// Use mainnet instead of testnet
+        #[arg(long)]
+        mainnet: bool,
+        /// Account index (BIP32 level 3, ZIP32 account). Default: 0
         #[arg(long, default_value = "0")]
-        address_index: u32,
+        account: u32,
+        /// Optional BIP39 passphrase ("25th word").
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+    /// Auto-detect and decode an arbitrary pasted datum: a raw transaction,
+    /// an address, or a viewing key
+    Identify {
+        /// The datum to inspect
+        #[arg(short, long)]
+        input: String,
+        /// Use mainnet instead of testnet
+        #[arg(long)]
+        mainnet: bool,
+    },
+    /// Encrypt a wallet file with a passphrase for at-rest storage
+    Encrypt {
+        /// Wallet file to encrypt (as produced by `generate`/`restore`)
+        #[arg(short, long)]
+        wallet: String,
+        /// Output file for the encrypted wallet blob
+        #[arg(short, long)]
+        output: String,
+        /// Passphrase to encrypt the wallet under
+        #[arg(long)]
+        passphrase: String,
+    },
+    /// Decrypt a wallet file previously produced by `encrypt`
+    Decrypt {
+        /// Encrypted wallet blob to decrypt
+        #[arg(short, long)]
+        wallet: String,
+        /// Output file for the decrypted wallet JSON
+        #[arg(short, long)]
+        output: String,
+        /// Passphrase the wallet was encrypted under
+        #[arg(long)]
+        passphrase: String,
+    },
+    /// Build a ZIP 321 payment request URI
+    Request {
+        /// Recipient address (unified, Sapling, or transparent). Repeat for
+        /// a multi-recipient request.
+        #[arg(long = "to")]
+        to: Vec<String>,
+        /// Requested amount in zatoshis, one per `--to` in the same order.
+        #[arg(long)]
+        amount: Vec<u64>,
+        /// Memo text for the recipient at the same position. Use an empty
+        /// string to skip a memo for a given recipient in a multi-recipient
+        /// request.
+        #[arg(long)]
+        memo: Vec<String>,
+        /// Human-readable label for the recipient at the same position.
+        #[arg(long)]
+        label: Vec<String>,
+        /// Human-readable message for the recipient at the same position.
+        #[arg(long)]
+        message: Vec<String>,
+    },
+    /// Decode a ZIP 321 payment request URI
+    Pay {
+        /// The `zcash:` payment request URI to decode
+        uri: String,
     },
     /// Show faucet information
     Faucet,
@@ -88,6 +185,10 @@ enum Commands {
         /// Database file path
         #[arg(long, default_value = "notes.db")]
         db: String,
+        /// Break the total down into spendable external funds vs. internal
+        /// change, instead of reporting a single combined total
+        #[arg(long)]
+        split: bool,
     },
     /// List all tracked notes
     Notes {
@@ -97,6 +198,93 @@ enum Commands {
         /// Show all notes including spent
         #[arg(long)]
         all: bool,
+        /// Show the commitment-tree anchor of each unspent note's witness,
+        /// as maintained by `sync`. A witness is needed before a note can
+        /// be spent.
+        #[arg(long)]
+        witness: bool,
+    },
+    /// Follow the chain by scanning compact blocks from a lightwalletd server
+    Sync {
+        /// Wallet file containing viewing key
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// lightwalletd gRPC server URL (e.g. https://mainnet.lightwalletd.com:9067)
+        #[arg(long)]
+        server: String,
+        /// Height to start scanning from. Defaults to the height after the
+        /// last scanned height recorded in the database, or 0 if never synced.
+        #[arg(long)]
+        start_height: Option<u32>,
+    },
+    /// Query the RPC for the wallet's transparent UTXOs and track them
+    Utxos {
+        /// Wallet file containing the transparent address
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+    },
+    /// Shield tracked transparent UTXOs into the wallet's own Orchard address
+    Shield {
+        /// Wallet file containing the seed phrase and account
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// Height the transaction targets for inclusion. Defaults to the
+        /// last scanned height recorded in the database.
+        #[arg(long)]
+        target_height: Option<u32>,
+    },
+    /// Spend tracked Orchard notes to a recipient (a `z_sendmany` equivalent)
+    Send {
+        /// Wallet file containing the seed phrase and account
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// Recipient address - transparent or Orchard-capable unified
+        #[arg(long)]
+        to: String,
+        /// Amount to send, in zatoshis
+        #[arg(long)]
+        amount: u64,
+        /// An optional ZIP 302 plaintext memo. Only valid for a shielded recipient.
+        #[arg(long)]
+        memo: Option<String>,
+        /// Height the transaction targets for inclusion. Defaults to the
+        /// last scanned height recorded in the database.
+        #[arg(long)]
+        target_height: Option<u32>,
+    },
+    /// Recover the diversifier index that produced one of this wallet's
+    /// own shielded addresses
+    WhichIndex {
+        /// Wallet file containing the seed phrase and account
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// One of this wallet's own unified or Sapling addresses
+        #[arg(long)]
+        address: String,
+    },
+    /// Start a local HTTP server exposing read-only wallet state as JSON
+    Serve {
+        /// Wallet file containing the unified/transparent addresses
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// Address to bind the HTTP server to
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
     },
 }
 
@@ -109,14 +297,58 @@ fn main() -> Result<()> {
             mainnet,
             account,
             address_index,
-        } => generate_wallet(&output, mainnet, account, address_index),
+            passphrase,
+            birthday_height,
+        } => generate_wallet(
+            &output,
+            mainnet,
+            account,
+            address_index,
+            passphrase.as_deref(),
+            birthday_height,
+        ),
         Commands::Restore {
             seed,
             output,
             mainnet,
             account,
             address_index,
-        } => restore_wallet(&seed, output.as_deref(), mainnet, account, address_index),
+            passphrase,
+            birthday_height,
+        } => restore_wallet(
+            &seed,
+            output.as_deref(),
+            mainnet,
+            account,
+            address_index,
+            passphrase.as_deref(),
+            birthday_height,
+        ),
+        Commands::Inspect {
+            seed,
+            mainnet,
+            account,
+            passphrase,
+        } => inspect_mnemonic(&seed, mainnet, account, passphrase.as_deref()),
+        Commands::Identify { input, mainnet } => identify_data(&input, mainnet),
+        Commands::Encrypt {
+            wallet,
+            output,
+            passphrase,
+        } => encrypt_wallet(&wallet, &output, &passphrase),
+        Commands::Decrypt {
+            wallet,
+            output,
+            passphrase,
+        } => decrypt_wallet(&wallet, &output, &passphrase),
+        Commands::Request {
+            to,
+            amount,
+            memo,
+            label,
+            message,
+        } => build_payment_request(&to, &amount, &memo, &label, &message),
+        Commands::Pay { uri } => decode_payment_request(&uri),
         Commands::Faucet => show_faucet_info(),
         Commands::Config { rpc_url, db } => configure(&db, rpc_url),
         Commands::Scan {
@@ -126,8 +358,30 @@ fn main() -> Result<()> {
             db,
             height,
         } => scan_transaction(&db, &wallet, txid, raw, height),
-        Commands::Balance { db } => show_balance(&db),
-        Commands::Notes { db, all } => list_notes(&db, all),
+        Commands::Balance { db, split } => show_balance(&db, split),
+        Commands::Notes { db, all, witness } => list_notes(&db, all, witness),
+        Commands::Sync {
+            wallet,
+            db,
+            server,
+            start_height,
+        } => sync_wallet(&db, &wallet, &server, start_height),
+        Commands::Utxos { wallet, db } => fetch_transparent_utxos(&db, &wallet),
+        Commands::Shield {
+            wallet,
+            db,
+            target_height,
+        } => shield_transparent_funds(&db, &wallet, target_height),
+        Commands::Send {
+            wallet,
+            db,
+            to,
+            amount,
+            memo,
+            target_height,
+        } => send_funds(&db, &wallet, &to, amount, memo.as_deref(), target_height),
+        Commands::WhichIndex { wallet, address } => which_index(&wallet, &address),
+        Commands::Serve { wallet, db, bind } => server::serve(&db, &wallet, &bind),
     }
 }
 
@@ -135,7 +389,9 @@ fn generate_wallet(
     output_path: &str,
     mainnet: bool,
     account: u32,
-    address_index: u32,
+    address_index: u128,
+    passphrase: Option<&str>,
+    birthday_height: Option<u32>,
 ) -> Result<()> {
     // Check if output file already exists
     let path = Path::new(output_path);
@@ -158,8 +414,16 @@ fn generate_wallet(
     OsRng.fill_bytes(&mut entropy);
 
     // Use core library for wallet derivation
-    let wallet = zcash_wallet_core::generate_wallet(&entropy, network, account, address_index)
-        .map_err(|e| anyhow::anyhow!("Failed to generate wallet: {}", e))?;
+    let wallet = zcash_wallet_core::generate_wallet(
+        &entropy,
+        network,
+        account,
+        address_index,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase,
+        birthday_height,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to generate wallet: {}", e))?;
 
     // Create JSON wallet data
     let wallet_json = serde_json::json!({
@@ -170,6 +434,7 @@ fn generate_wallet(
         "unified_address": wallet.unified_address,
         "unified_full_viewing_key": wallet.unified_full_viewing_key,
         "transparent_address": wallet.transparent_address,
+        "birthday": wallet.birthday,
     });
 
     // Write wallet to file
@@ -233,7 +498,9 @@ fn restore_wallet(
     output_path: Option<&str>,
     mainnet: bool,
     account: u32,
-    address_index: u32,
+    address_index: u128,
+    passphrase: Option<&str>,
+    birthday_height: Option<u32>,
 ) -> Result<()> {
     let network = if mainnet {
         Network::MainNetwork
@@ -243,8 +510,16 @@ fn restore_wallet(
     let network_name = if mainnet { "MAINNET" } else { "TESTNET" };
 
     // Use core library for wallet restoration
-    let wallet = zcash_wallet_core::restore_wallet(seed_phrase, network, account, address_index)
-        .map_err(|e| anyhow::anyhow!("Failed to restore wallet: {}", e))?;
+    let wallet = zcash_wallet_core::restore_wallet(
+        seed_phrase,
+        network,
+        account,
+        address_index,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase,
+        birthday_height,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to restore wallet: {}", e))?;
 
     // Save to file if output path is provided
     if let Some(path_str) = output_path {
@@ -264,6 +539,7 @@ fn restore_wallet(
             "unified_address": wallet.unified_address,
             "unified_full_viewing_key": wallet.unified_full_viewing_key,
             "transparent_address": wallet.transparent_address,
+            "birthday": wallet.birthday,
         });
 
         let json_string = serde_json::to_string_pretty(&wallet_json)?;
@@ -308,6 +584,240 @@ fn restore_wallet(
     Ok(())
 }
 
+fn inspect_mnemonic(
+    seed_phrase: &str,
+    mainnet: bool,
+    account: u32,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    let network = if mainnet {
+        Network::MainNetwork
+    } else {
+        Network::TestNetwork
+    };
+    let network_name = if mainnet { "MAINNET" } else { "TESTNET" };
+
+    let info = zcash_wallet_core::inspect_mnemonic(seed_phrase, network, account, passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to inspect mnemonic: {}", e))?;
+
+    println!("============================================================");
+    println!("           {} MNEMONIC INSPECTION", network_name);
+    println!("============================================================");
+    println!();
+    println!("Word Count: {}", info.word_count);
+    println!("Language: {}", info.language);
+    println!("Entropy: {}", info.entropy);
+    println!();
+    println!("------------------------------------------------------------");
+    println!("FINGERPRINTS");
+    println!("------------------------------------------------------------");
+    println!();
+    println!("Seed Fingerprint: {}", info.seed_fingerprint);
+    println!(
+        "UFVK Fingerprint (account {}): {}",
+        account, info.ufvk_fingerprint
+    );
+    println!();
+
+    Ok(())
+}
+
+fn identify_data(input: &str, mainnet: bool) -> Result<()> {
+    let network = if mainnet {
+        zcash_wallet_core::NetworkKind::Mainnet
+    } else {
+        zcash_wallet_core::NetworkKind::Testnet
+    };
+
+    let result = zcash_wallet_core::inspect(input, network);
+
+    println!("============================================================");
+    println!("           IDENTIFY");
+    println!("============================================================");
+    println!();
+    println!("Detected: {}", result.kind);
+    println!();
+
+    match result.kind {
+        zcash_wallet_core::DataKind::Transaction => {
+            println!("{:#?}", result.transaction.expect("set when kind is Transaction"));
+        }
+        zcash_wallet_core::DataKind::Address => {
+            println!("{:#?}", result.address.expect("set when kind is Address"));
+        }
+        zcash_wallet_core::DataKind::ViewingKey => {
+            println!("{:#?}", result.viewing_key.expect("set when kind is ViewingKey"));
+        }
+        zcash_wallet_core::DataKind::Unrecognized => {
+            println!("{}", result.error.unwrap_or_default());
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Build a `WalletInfo` from a wallet JSON file as written by `generate`/`restore`.
+///
+/// Those files omit the `receivers` field (the CLI always derives with
+/// `ReceiverSelection::default()`), so it's filled back in here.
+pub(crate) fn load_wallet_info(wallet_path: &str) -> Result<zcash_wallet_core::WalletInfo> {
+    let wallet_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read wallet file: {}", wallet_path))?;
+    let wallet_json: serde_json::Value =
+        serde_json::from_str(&wallet_content).context("Failed to parse wallet JSON")?;
+
+    let network = match wallet_json["network"].as_str().unwrap_or("testnet") {
+        "mainnet" => zcash_wallet_core::NetworkKind::Mainnet,
+        _ => zcash_wallet_core::NetworkKind::Testnet,
+    };
+
+    Ok(zcash_wallet_core::WalletInfo {
+        seed_phrase: wallet_json["seed_phrase"]
+            .as_str()
+            .context("Wallet missing seed_phrase")?
+            .to_string(),
+        network,
+        account_index: wallet_json["account_index"].as_u64().unwrap_or(0) as u32,
+        address_index: wallet_json["address_index"].as_u64().unwrap_or(0) as u128,
+        unified_address: wallet_json["unified_address"]
+            .as_str()
+            .context("Wallet missing unified_address")?
+            .to_string(),
+        receivers: zcash_wallet_core::ReceiverSelection::default(),
+        transparent_address: wallet_json["transparent_address"]
+            .as_str()
+            .map(|s| s.to_string()),
+        unified_full_viewing_key: wallet_json["unified_full_viewing_key"]
+            .as_str()
+            .context("Wallet missing unified_full_viewing_key")?
+            .to_string(),
+    })
+}
+
+fn encrypt_wallet(wallet_path: &str, output_path: &str, passphrase: &str) -> Result<()> {
+    let path = Path::new(output_path);
+    if path.exists() {
+        bail!(
+            "Output file '{}' already exists. Choose a different filename or remove the existing file.",
+            output_path
+        );
+    }
+
+    let wallet = load_wallet_info(wallet_path)?;
+    let encrypted = zcash_wallet_core::encrypt_wallet(&wallet, passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt wallet: {}", e))?;
+
+    let json_string = serde_json::to_string_pretty(&encrypted)?;
+    fs::write(path, &json_string).context("Failed to write encrypted wallet file")?;
+
+    println!("Encrypted wallet written to: {}", output_path);
+    Ok(())
+}
+
+fn decrypt_wallet(wallet_path: &str, output_path: &str, passphrase: &str) -> Result<()> {
+    let path = Path::new(output_path);
+    if path.exists() {
+        bail!(
+            "Output file '{}' already exists. Choose a different filename or remove the existing file.",
+            output_path
+        );
+    }
+
+    let encrypted_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read encrypted wallet file: {}", wallet_path))?;
+    let encrypted: zcash_wallet_core::EncryptedWallet = serde_json::from_str(&encrypted_content)
+        .context("Failed to parse encrypted wallet JSON")?;
+
+    let wallet = zcash_wallet_core::decrypt_wallet(&encrypted, passphrase)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt wallet: {}", e))?;
+
+    let wallet_json = serde_json::json!({
+        "seed_phrase": wallet.seed_phrase,
+        "network": wallet.network,
+        "account_index": wallet.account_index,
+        "address_index": wallet.address_index,
+        "unified_address": wallet.unified_address,
+        "unified_full_viewing_key": wallet.unified_full_viewing_key,
+        "transparent_address": wallet.transparent_address,
+    });
+    let json_string = serde_json::to_string_pretty(&wallet_json)?;
+    fs::write(path, &json_string).context("Failed to write decrypted wallet file")?;
+
+    println!("Decrypted wallet written to: {}", output_path);
+    Ok(())
+}
+
+/// Build a ZIP 321 `zcash:` payment request URI from per-recipient CLI args.
+///
+/// `amount` must hold exactly one entry per `to`. `memo`, `label`, and
+/// `message` are matched to `to` by position; any that are shorter than
+/// `to`, or hold an empty string at a position, leave the corresponding
+/// recipient without that field.
+fn build_payment_request(
+    to: &[String],
+    amount: &[u64],
+    memo: &[String],
+    label: &[String],
+    message: &[String],
+) -> Result<()> {
+    if to.is_empty() {
+        bail!("At least one --to address is required");
+    }
+    if amount.len() != to.len() {
+        bail!("Expected one --amount per --to (got {} addresses and {} amounts)", to.len(), amount.len());
+    }
+
+    let recipients: Vec<zcash_wallet_core::PaymentRecipient> = to
+        .iter()
+        .enumerate()
+        .map(|(i, address)| zcash_wallet_core::PaymentRecipient {
+            address: address.clone(),
+            amount_zatoshis: amount[i],
+            memo: memo.get(i).filter(|m| !m.is_empty()).cloned(),
+            label: label.get(i).filter(|l| !l.is_empty()).cloned(),
+            message: message.get(i).filter(|m| !m.is_empty()).cloned(),
+        })
+        .collect();
+
+    let uri = zcash_wallet_core::build_payment_uri(&recipients)
+        .map_err(|e| anyhow::anyhow!("Failed to build payment request: {}", e))?;
+
+    println!("{}", uri);
+    Ok(())
+}
+
+/// Decode a ZIP 321 `zcash:` payment request URI and print its payments.
+fn decode_payment_request(uri: &str) -> Result<()> {
+    let payments = zcash_wallet_core::parse_payment_uri(uri)
+        .map_err(|e| anyhow::anyhow!("Failed to parse payment request: {}", e))?;
+
+    println!("============================================================");
+    println!("           PAYMENT REQUEST");
+    println!("============================================================");
+    println!();
+
+    for (i, payment) in payments.iter().enumerate() {
+        println!("Payment {}:", i + 1);
+        println!("  Recipient: {}", payment.recipient_address);
+        if let Some(amount) = payment.amount {
+            println!("  Amount: {} zatoshis", amount);
+        }
+        if let Some(ref memo) = payment.memo {
+            println!("  Memo: {}", memo);
+        }
+        if let Some(ref label) = payment.label {
+            println!("  Label: {}", label);
+        }
+        if let Some(ref message) = payment.message {
+            println!("  Message: {}", message);
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
 fn show_faucet_info() -> Result<()> {
     println!("============================================================");
     println!("           ZCASH TESTNET FAUCET");
@@ -387,9 +897,12 @@ fn scan_transaction(
         bail!("Must provide either --txid or --raw");
     };
 
-    // Parse and scan transaction
+    // Parse and scan transaction. The tree size before a single transaction
+    // isn't known here (only `sync` tracks the commitment trees), so found
+    // notes aren't assigned a position and the Sapling nullifier can't be
+    // computed.
     let tx = scanner::parse_transaction(&tx_hex, network)?;
-    let result = scanner::scan_transaction(&tx, viewing_key, network, height)?;
+    let result = scanner::scan_transaction(&tx, viewing_key, network, height, None, None)?;
 
     // Open database
     let db = db::Database::open(db_path)?;
@@ -406,6 +919,7 @@ fn scan_transaction(
             note.nullifier.as_deref(),
             note.memo.as_deref(),
             note.address.as_deref(),
+            note.transfer_type.as_ref().map(|t| t.as_str()),
             height.map(|h| h as i64),
         )?;
         if inserted {
@@ -476,7 +990,252 @@ fn scan_transaction(
     Ok(())
 }
 
-fn show_balance(db_path: &str) -> Result<()> {
+/// Load the persisted commitment-tree/witness state, or start fresh trees if
+/// this is the first sync.
+fn load_note_commitment_trees(db: &db::Database) -> Result<zcash_wallet_core::NoteCommitmentTrees> {
+    let Some((sapling_bytes, orchard_bytes)) = db.get_tree_state()? else {
+        return Ok(zcash_wallet_core::NoteCommitmentTrees::new());
+    };
+
+    let sapling_tree = zcash_wallet_core::deserialize_sapling_tree(&sapling_bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to load Sapling tree state: {}", e))?;
+    let orchard_tree = zcash_wallet_core::deserialize_orchard_tree(&orchard_bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to load Orchard tree state: {}", e))?;
+
+    let sapling_witnesses = db
+        .get_witnesses("sapling")?
+        .into_iter()
+        .map(|(note_id, bytes)| {
+            zcash_wallet_core::deserialize_sapling_witness(&bytes)
+                .map(|witness| (note_id, witness))
+                .map_err(|e| anyhow::anyhow!("Failed to load Sapling witness: {}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let orchard_witnesses = db
+        .get_witnesses("orchard")?
+        .into_iter()
+        .map(|(note_id, bytes)| {
+            zcash_wallet_core::deserialize_orchard_witness(&bytes)
+                .map(|witness| (note_id, witness))
+                .map_err(|e| anyhow::anyhow!("Failed to load Orchard witness: {}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(zcash_wallet_core::NoteCommitmentTrees::from_parts(
+        sapling_tree,
+        orchard_tree,
+        sapling_witnesses,
+        orchard_witnesses,
+    ))
+}
+
+/// Persist the commitment-tree frontier so the next `sync` run can resume
+/// without replaying every previously-scanned block.
+fn save_note_commitment_trees(
+    db: &db::Database,
+    trees: &zcash_wallet_core::NoteCommitmentTrees,
+) -> Result<()> {
+    let sapling_bytes = zcash_wallet_core::serialize_sapling_tree(trees.sapling_tree())
+        .map_err(|e| anyhow::anyhow!("Failed to serialize Sapling tree state: {}", e))?;
+    let orchard_bytes = zcash_wallet_core::serialize_orchard_tree(trees.orchard_tree())
+        .map_err(|e| anyhow::anyhow!("Failed to serialize Orchard tree state: {}", e))?;
+    db.set_tree_state(&sapling_bytes, &orchard_bytes)
+}
+
+/// Insert a scanned note into the database, returning its assigned id if it
+/// was newly inserted (`None` if it was already known, e.g. re-scanning a
+/// block after a rewind).
+fn insert_scanned_note(
+    db: &db::Database,
+    txid: &str,
+    note: &zcash_wallet_core::ScannedNote,
+    height: u32,
+) -> Result<Option<i64>> {
+    let inserted = db.insert_note(
+        txid,
+        note.output_index as i64,
+        &note.pool,
+        note.value as i64,
+        Some(note.commitment.as_str()),
+        note.nullifier.as_deref(),
+        note.memo.as_deref(),
+        note.address.as_deref(),
+        note.transfer_type.as_ref().map(|t| t.as_str()),
+        Some(height as i64),
+    )?;
+
+    if inserted {
+        Ok(Some(db.note_id(txid, note.output_index as i64, &note.pool)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Scan a range of compact blocks from a lightwalletd server, resuming from
+/// the last synced height and rewinding past any detected reorg.
+///
+/// Unlike `scan_transaction`, which requires the caller to already know a
+/// txid, this follows the chain: it fetches every block since the last
+/// checkpoint, trial-decrypts every compact output against the wallet's
+/// viewing key, and records a checkpoint per block so the next run can
+/// resume (or detect a reorg and rewind) instead of rescanning from genesis.
+fn sync_wallet(
+    db_path: &str,
+    wallet_path: &str,
+    server: &str,
+    start_height: Option<u32>,
+) -> Result<()> {
+    let wallet_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read wallet file: {}", wallet_path))?;
+    let wallet_json: serde_json::Value =
+        serde_json::from_str(&wallet_content).context("Failed to parse wallet JSON")?;
+    let viewing_key = wallet_json["unified_full_viewing_key"]
+        .as_str()
+        .context("Wallet missing unified_full_viewing_key")?;
+
+    // Get network from wallet file
+    let network_str = wallet_json["network"].as_str().unwrap_or("testnet");
+    let network = match network_str {
+        "mainnet" => Network::MainNetwork,
+        _ => Network::TestNetwork,
+    };
+
+    let db = db::Database::open(db_path)?;
+
+    let resume_height = match start_height {
+        Some(h) => h,
+        None => match db.get_config("last_scanned_height")? {
+            Some(h) => h.parse::<u32>().context("Invalid last_scanned_height in database")? + 1,
+            None => 0,
+        },
+    };
+
+    println!("Connecting to lightwalletd at {}...", server);
+    let tip_height = lightwalletd::fetch_latest_height(server)? as u32;
+
+    if resume_height > tip_height {
+        println!("Already synced to the chain tip (height {}).", tip_height);
+        return Ok(());
+    }
+
+    println!(
+        "Syncing blocks {} to {} ({} blocks)...",
+        resume_height,
+        tip_height,
+        tip_height - resume_height + 1
+    );
+    let blocks = lightwalletd::fetch_block_range(server, resume_height as u64, tip_height as u64)?;
+
+    let mut trees = load_note_commitment_trees(&db)?;
+    let mut notes_added = 0;
+    let mut notes_spent = 0;
+    let mut blocks_synced = 0;
+
+    for block in &blocks {
+        let height = block.height as u32;
+        let prev_hash = hex::encode(&block.prev_hash);
+
+        if let Some((checkpoint_height, checkpoint_hash)) = db.latest_checkpoint()? {
+            if height == checkpoint_height as u32 + 1 && prev_hash != checkpoint_hash {
+                let fork_height = checkpoint_height - 1;
+                db.rewind_to_height(fork_height)?;
+                db.set_config("last_scanned_height", &fork_height.to_string())?;
+                bail!(
+                    "Reorg detected at height {}: expected prev_hash {} but block has {}. \
+                     Rewound to height {}; re-run sync to continue.",
+                    height,
+                    checkpoint_hash,
+                    prev_hash,
+                    fork_height
+                );
+            }
+        }
+
+        let results = scanner::scan_compact_block(
+            block,
+            viewing_key,
+            network,
+            Some(trees.sapling_size()),
+            Some(trees.orchard_size()),
+        )?;
+        for (tx, result) in block.vtx.iter().zip(results.iter()) {
+            for (out_idx, output) in tx.outputs.iter().enumerate() {
+                let cmu = zcash_wallet_core::sapling_commitment_from_hex(&hex::encode(&output.cmu))
+                    .map_err(|e| anyhow::anyhow!("Invalid Sapling commitment: {}", e))?;
+                trees.append_sapling_commitment(cmu)?;
+
+                if let Some(note) = result
+                    .notes
+                    .iter()
+                    .find(|n| n.pool == zcash_wallet_core::Pool::Sapling && n.output_index == out_idx)
+                {
+                    if let Some(note_id) = insert_scanned_note(&db, &result.txid, note, height)? {
+                        notes_added += 1;
+                        trees.track_sapling_note(note_id)?;
+                        let witness = trees.sapling_witness(note_id).expect("just tracked");
+                        let witness_bytes = zcash_wallet_core::serialize_sapling_witness(witness)
+                            .map_err(|e| anyhow::anyhow!("Failed to serialize witness: {}", e))?;
+                        db.set_witness(note_id, "sapling", &witness_bytes)?;
+                    }
+                }
+            }
+
+            for (out_idx, action) in tx.actions.iter().enumerate() {
+                let cmx = zcash_wallet_core::orchard_commitment_from_hex(&hex::encode(&action.cmx))
+                    .map_err(|e| anyhow::anyhow!("Invalid Orchard commitment: {}", e))?;
+                trees.append_orchard_commitment(cmx)?;
+
+                if let Some(note) = result
+                    .notes
+                    .iter()
+                    .find(|n| n.pool == zcash_wallet_core::Pool::Orchard && n.output_index == out_idx)
+                {
+                    if let Some(note_id) = insert_scanned_note(&db, &result.txid, note, height)? {
+                        notes_added += 1;
+                        trees.track_orchard_note(note_id)?;
+                        let witness = trees.orchard_witness(note_id).expect("just tracked");
+                        let witness_bytes = zcash_wallet_core::serialize_orchard_witness(witness)
+                            .map_err(|e| anyhow::anyhow!("Failed to serialize witness: {}", e))?;
+                        db.set_witness(note_id, "orchard", &witness_bytes)?;
+                    }
+                }
+            }
+
+            let nullifier_strings: Vec<String> = result
+                .spent_nullifiers
+                .iter()
+                .map(|n| n.nullifier.clone())
+                .collect();
+            notes_spent += db.mark_spent_by_nullifiers(&nullifier_strings, &result.txid)?;
+        }
+
+        let block_hash = hex::encode(&block.hash);
+        db.insert_checkpoint(height as i64, &block_hash)?;
+        db.set_config("last_scanned_height", &height.to_string())?;
+        save_note_commitment_trees(&db, &trees)?;
+        blocks_synced += 1;
+    }
+
+    println!();
+    println!("============================================================");
+    println!("           SYNC COMPLETE");
+    println!("============================================================");
+    println!();
+    println!("Blocks synced: {}", blocks_synced);
+    println!("New notes added to database: {}", notes_added);
+    println!("Notes marked as spent: {}", notes_spent);
+    println!();
+
+    let balance = db.get_balance()?;
+    println!("============================================================");
+    println!("Current balance: {} ZEC", format_zatoshi(balance as u64));
+    println!("============================================================");
+    println!();
+
+    Ok(())
+}
+
+fn show_balance(db_path: &str, split: bool) -> Result<()> {
     let db = db::Database::open(db_path)?;
 
     let total_balance = db.get_balance()?;
@@ -498,10 +1257,243 @@ fn show_balance(db_path: &str) -> Result<()> {
         println!();
     }
 
+    if split {
+        // Outgoing notes (payments we sent to someone else) are deliberately
+        // excluded here - we have no spend authority over them, so they were
+        // never part of this wallet's balance to begin with.
+        let balances_by_transfer_type = db.get_balance_by_transfer_type()?;
+        if !balances_by_transfer_type.is_empty() {
+            println!("By transfer type:");
+            for (transfer_type, balance) in balances_by_transfer_type {
+                let label = match transfer_type.as_str() {
+                    "incoming" => "External (spendable)",
+                    "wallet_internal" => "Internal (change)",
+                    other => other,
+                };
+                println!("  {}: {} ZEC", label, format_zatoshi(balance as u64));
+            }
+            println!();
+        }
+    }
+
     Ok(())
 }
 
-fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
+/// Query the RPC for the wallet's transparent address's UTXOs and persist
+/// any newly-seen ones, then report the transparent balance.
+fn fetch_transparent_utxos(db_path: &str, wallet_path: &str) -> Result<()> {
+    let wallet_info = load_wallet_info(wallet_path)?;
+    let address = wallet_info
+        .transparent_address
+        .context("Wallet has no transparent address")?;
+
+    let db = db::Database::open(db_path)?;
+    let rpc_url = db
+        .get_config("rpc_url")?
+        .context("RPC URL not configured. Run: zcash-wallet config --rpc-url <url>")?;
+    let client = rpc::RpcClient::new(&rpc_url);
+
+    println!("Fetching UTXOs for {} from RPC...", address);
+    let utxos = client.get_address_utxos(&address)?;
+
+    let mut utxos_added = 0;
+    for utxo in &utxos {
+        let inserted = db.insert_utxo(
+            &utxo.txid,
+            utxo.vout as i64,
+            &utxo.script_pubkey,
+            utxo.value as i64,
+            utxo.height.map(|h| h as i64),
+        )?;
+        if inserted {
+            utxos_added += 1;
+        }
+    }
+
+    let balance = db.get_transparent_balance()?;
+
+    println!();
+    println!("============================================================");
+    println!("           TRANSPARENT UTXOS");
+    println!("============================================================");
+    println!();
+    println!("UTXOs found: {}", utxos.len());
+    println!("  New UTXOs added to database: {}", utxos_added);
+    println!();
+    println!("Transparent balance: {} ZEC", format_zatoshi(balance as u64));
+    println!();
+
+    Ok(())
+}
+
+/// Build a transaction shielding all tracked, unspent transparent UTXOs into
+/// the wallet's own Orchard address, and print the raw hex for broadcast.
+fn shield_transparent_funds(
+    db_path: &str,
+    wallet_path: &str,
+    target_height: Option<u32>,
+) -> Result<()> {
+    let wallet_info = load_wallet_info(wallet_path)?;
+    let network = wallet_info.network.to_network();
+
+    let db = db::Database::open(db_path)?;
+    let utxos = db.get_unspent_utxos()?;
+    if utxos.is_empty() {
+        println!("No tracked transparent UTXOs to shield.");
+        return Ok(());
+    }
+
+    let target_height = match target_height {
+        Some(height) => height,
+        None => match db.get_config("last_scanned_height")? {
+            Some(h) => h.parse::<u32>().context("Invalid last_scanned_height in database")?,
+            None => bail!("No target height available; pass --target-height or run `sync` first"),
+        },
+    };
+
+    let tx_hex = zcash_wallet_core::build_shielding_transaction(
+        &wallet_info.seed_phrase,
+        network,
+        wallet_info.account_index,
+        None,
+        &utxos,
+        target_height,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to build shielding transaction: {}", e))?;
+
+    let total_value: u64 = utxos.iter().map(|u| u.value).sum();
+
+    println!();
+    println!("============================================================");
+    println!("           SHIELDING TRANSACTION BUILT");
+    println!("============================================================");
+    println!();
+    println!("Inputs: {}", utxos.len());
+    println!("Total value: {} ZEC", format_zatoshi(total_value));
+    println!();
+    println!("Raw transaction hex (broadcast with your RPC's sendrawtransaction):");
+    println!();
+    println!("{}", tx_hex);
+    println!();
+
+    Ok(())
+}
+
+fn send_funds(
+    db_path: &str,
+    wallet_path: &str,
+    to: &str,
+    amount: u64,
+    memo: Option<&str>,
+    target_height: Option<u32>,
+) -> Result<()> {
+    let wallet_info = load_wallet_info(wallet_path)?;
+    let network = wallet_info.network.to_network();
+
+    let db = db::Database::open(db_path)?;
+    let unspent: Vec<_> = db
+        .get_unspent_notes()?
+        .into_iter()
+        .filter(|note| note.pool == "orchard")
+        .collect();
+    if unspent.is_empty() {
+        bail!("No tracked, unspent Orchard notes to spend.");
+    }
+
+    let mut inputs = Vec::with_capacity(unspent.len());
+    for note in &unspent {
+        let witness_bytes = db
+            .get_witness(note.id)?
+            .with_context(|| format!("Note #{} has no witness yet; run `sync` first", note.id))?;
+        inputs.push(zcash_wallet_core::WitnessedNote {
+            note: zcash_wallet_core::ScannedNote {
+                output_index: note.output_index as usize,
+                pool: zcash_wallet_core::Pool::Orchard,
+                value: note.value as u64,
+                commitment: note.commitment.clone().unwrap_or_default(),
+                nullifier: note.nullifier.clone(),
+                memo: None,
+                payment_request: None,
+                address: note.address.clone(),
+                transfer_type: None,
+                // `notes.db` doesn't yet persist a note's `rho`/`rseed`
+                // alongside its commitment, so a previously-synced note
+                // can't actually be spent until that's tracked - the same
+                // kind of gap as the commitment-tree position noted in
+                // `scan_transaction`. `build_transaction` below reports
+                // this clearly per note rather than silently moving on.
+                rho: None,
+                rseed: None,
+                position: None,
+            },
+            note_id: note.id,
+            witness: witness_bytes,
+        });
+    }
+
+    let target_height = match target_height {
+        Some(height) => height,
+        None => match db.get_config("last_scanned_height")? {
+            Some(h) => h.parse::<u32>().context("Invalid last_scanned_height in database")?,
+            None => bail!("No target height available; pass --target-height or run `sync` first"),
+        },
+    };
+
+    let outputs = vec![zcash_wallet_core::SendOutput {
+        address: to.to_string(),
+        amount,
+        memo: memo.map(str::to_string),
+    }];
+
+    let tx_hex = zcash_wallet_core::build_transaction(
+        &wallet_info.seed_phrase,
+        network,
+        wallet_info.account_index,
+        None,
+        &inputs,
+        &outputs,
+        target_height,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to build transaction: {}", e))?;
+
+    println!();
+    println!("============================================================");
+    println!("           SEND TRANSACTION BUILT");
+    println!("============================================================");
+    println!();
+    println!("Inputs: {}", inputs.len());
+    println!("To: {}", to);
+    println!("Amount: {} ZEC", format_zatoshi(amount));
+    println!();
+    println!("Raw transaction hex (broadcast with your RPC's sendrawtransaction):");
+    println!();
+    println!("{}", tx_hex);
+    println!();
+
+    Ok(())
+}
+
+fn which_index(wallet_path: &str, address: &str) -> Result<()> {
+    let wallet_info = load_wallet_info(wallet_path)?;
+    let network = wallet_info.network.to_network();
+
+    let diversifier_index = zcash_wallet_core::find_diversifier_index(
+        &wallet_info.seed_phrase,
+        network,
+        wallet_info.account_index,
+        address,
+        None,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to find diversifier index: {}", e))?;
+
+    println!();
+    println!("Diversifier index: {}", diversifier_index);
+    println!();
+
+    Ok(())
+}
+
+fn list_notes(db_path: &str, show_all: bool, show_witness: bool) -> Result<()> {
     let db = db::Database::open(db_path)?;
 
     let notes = if show_all {
@@ -551,6 +1543,27 @@ fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
         if let Some(ref spent_txid) = note.spent_txid {
             println!("  Spent in: {}", spent_txid);
         }
+        if let Some(ref transfer_type) = note.transfer_type {
+            println!("  Transfer: {}", transfer_type);
+        }
+        if show_witness && note.spent_txid.is_none() {
+            match db.get_witness(note.id)? {
+                Some(witness_bytes) => match note.pool.as_str() {
+                    "sapling" => {
+                        let witness = zcash_wallet_core::deserialize_sapling_witness(&witness_bytes)
+                            .map_err(|e| anyhow::anyhow!("Failed to decode witness: {}", e))?;
+                        println!("  Anchor: {}", zcash_wallet_core::sapling_anchor_hex(&witness));
+                    }
+                    "orchard" => {
+                        let witness = zcash_wallet_core::deserialize_orchard_witness(&witness_bytes)
+                            .map_err(|e| anyhow::anyhow!("Failed to decode witness: {}", e))?;
+                        println!("  Anchor: {}", zcash_wallet_core::orchard_anchor_hex(&witness));
+                    }
+                    _ => println!("  Witness: (not applicable to this pool)"),
+                },
+                None => println!("  Witness: not yet available (run `sync` to build it)"),
+            }
+        }
         println!();
     }
 
@@ -0,0 +1,122 @@
+//! A minimal read-only HTTP server exposing wallet state as JSON.
+//!
+//! This lets a browser-based viewer or external scripts poll the wallet's
+//! tracked notes and balance without re-invoking the CLI per query. It's
+//! deliberately read-only and reuses the same `db::Database` queries and
+//! `rpc::RpcClient` the other commands use, rather than duplicating them.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{db, load_wallet_info, rpc};
+
+/// Start the HTTP server bound to `bind` and serve requests forever.
+pub fn serve(db_path: &str, wallet_path: &str, bind: &str) -> Result<()> {
+    let server =
+        Server::http(bind).map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", bind, e))?;
+    println!("Serving wallet state on http://{} (Ctrl+C to stop)", bind);
+
+    for request in server.incoming_requests() {
+        let (path, query) = split_path_and_query(request.url());
+        let response = match (request.method(), path.as_str()) {
+            (Method::Get, "/balance") => respond(query_balance(db_path)),
+            (Method::Get, "/notes") => respond(query_notes(db_path, &query)),
+            (Method::Get, "/latest_height") => respond(query_latest_height(db_path)),
+            (Method::Get, "/unified_address") => respond(query_unified_address(wallet_path)),
+            _ => json_response(404, &serde_json::json!({"error": "not found"})),
+        };
+        // A client that disconnects mid-response shouldn't take the server down.
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+fn respond(result: Result<serde_json::Value>) -> Response<Cursor<Vec<u8>>> {
+    match result {
+        Ok(body) => json_response(200, &body),
+        Err(e) => json_response(500, &serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+fn json_response(status: u16, body: &serde_json::Value) -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    Response::from_data(serde_json::to_vec(body).unwrap_or_default())
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn query_balance(db_path: &str) -> Result<serde_json::Value> {
+    let db = db::Database::open(db_path)?;
+    let total = db.get_balance()?;
+    let by_pool: HashMap<String, i64> = db.get_balance_by_pool()?.into_iter().collect();
+    Ok(serde_json::json!({ "total": total, "by_pool": by_pool }))
+}
+
+fn query_notes(db_path: &str, query: &str) -> Result<serde_json::Value> {
+    let show_all = query_param(query, "all") == Some("true");
+
+    let db = db::Database::open(db_path)?;
+    let notes = if show_all {
+        db.get_all_notes()?
+    } else {
+        db.get_unspent_notes()?
+    };
+
+    let notes_json: Vec<serde_json::Value> = notes
+        .iter()
+        .map(|note| {
+            serde_json::json!({
+                "id": note.id,
+                "txid": note.txid,
+                "output_index": note.output_index,
+                "pool": note.pool,
+                "value": note.value,
+                "commitment": note.commitment,
+                "nullifier": note.nullifier,
+                "spent_txid": note.spent_txid,
+                "transfer_type": note.transfer_type,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::Value::Array(notes_json))
+}
+
+fn query_latest_height(db_path: &str) -> Result<serde_json::Value> {
+    let db = db::Database::open(db_path)?;
+    let rpc_url = db
+        .get_config("rpc_url")?
+        .context("RPC URL not configured. Run: zcash-wallet config --rpc-url <url>")?;
+    let client = rpc::RpcClient::new(&rpc_url);
+    let height = client.get_latest_height()?;
+    Ok(serde_json::json!({ "height": height }))
+}
+
+fn query_unified_address(wallet_path: &str) -> Result<serde_json::Value> {
+    let wallet_info = load_wallet_info(wallet_path)?;
+    Ok(serde_json::json!({
+        "unified_address": wallet_info.unified_address,
+        "transparent_address": wallet_info.transparent_address,
+    }))
+}
+
+/// Split a request URL into its path and raw query string (without the `?`).
+fn split_path_and_query(url: &str) -> (String, String) {
+    match url.split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (url.to_string(), String::new()),
+    }
+}
+
+/// Look up a single key in a `key=value&key=value` query string.
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=')?;
+        (k == key).then_some(v)
+    })
+}
@@ -0,0 +1,140 @@
+//! Recipient address inspection.
+//!
+//! Before sending funds, a wallet UI needs to know what an arbitrary,
+//! user-pasted address string actually is - a unified address, a legacy
+//! Sapling address, or a transparent P2PKH/P2SH address - and which pools
+//! it can receive into, so it can warn when, e.g., the user is about to
+//! send shielded funds to a transparent-only address. [`parse_address`]
+//! answers that, mirroring how `parse_viewing_key` classifies a viewing
+//! key into a [`crate::types::ViewingKeyInfo`].
+
+use zcash_keys::address::Address;
+
+use crate::types::{AddressInfo, NetworkKind, Pool};
+
+/// Parse a unified or legacy address string and report what it is.
+///
+/// `network` is the network the address is expected to be valid for; an
+/// address encoded for a different network is reported as invalid, same as
+/// one that isn't a recognized Zcash address at all.
+pub fn parse_address(address: &str, network: NetworkKind) -> AddressInfo {
+    let params = network.to_network();
+
+    match Address::decode(&params, address.trim()) {
+        Some(Address::Unified(ua)) => {
+            let mut receivers = Vec::new();
+            if ua.has_transparent() {
+                receivers.push(Pool::Transparent);
+            }
+            if ua.has_sapling() {
+                receivers.push(Pool::Sapling);
+            }
+            if ua.has_orchard() {
+                receivers.push(Pool::Orchard);
+            }
+            AddressInfo {
+                valid: true,
+                kind: "unified".to_string(),
+                receivers,
+                network: Some(network),
+                error: None,
+            }
+        }
+        Some(Address::Sapling(_)) => AddressInfo {
+            valid: true,
+            kind: "sapling".to_string(),
+            receivers: vec![Pool::Sapling],
+            network: Some(network),
+            error: None,
+        },
+        Some(Address::Transparent(addr)) => {
+            let kind = match addr {
+                zcash_transparent::address::TransparentAddress::PublicKeyHash(_) => {
+                    "transparent-p2pkh"
+                }
+                zcash_transparent::address::TransparentAddress::ScriptHash(_) => {
+                    "transparent-p2sh"
+                }
+            };
+            AddressInfo {
+                valid: true,
+                kind: kind.to_string(),
+                receivers: vec![Pool::Transparent],
+                network: Some(network),
+                error: None,
+            }
+        }
+        Some(Address::Tex(_)) => AddressInfo {
+            valid: true,
+            kind: "transparent-p2pkh".to_string(),
+            receivers: vec![Pool::Transparent],
+            network: Some(network),
+            error: None,
+        },
+        None => AddressInfo {
+            valid: false,
+            kind: String::new(),
+            receivers: Vec::new(),
+            network: None,
+            error: Some(format!(
+                "Unrecognized address, or not valid for {}",
+                network
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transparent_p2pkh_address_has_only_transparent_receiver() {
+        let info = parse_address("t1HxutHFt2Sejz7fs92wFVAbsFM7NDjsBG6", NetworkKind::Mainnet);
+
+        assert!(info.valid);
+        assert_eq!(info.kind, "transparent-p2pkh");
+        assert!(info.has_receiver_of_type(Pool::Transparent));
+        assert!(!info.has_receiver_of_type(Pool::Sapling));
+        assert_eq!(info.network, Some(NetworkKind::Mainnet));
+    }
+
+    #[test]
+    fn test_sapling_address_has_only_sapling_receiver() {
+        let info = parse_address(
+            "zs1z7rejlpsa98s2rrrfkwmaxu53e4ue0ulcrw0h4x5g8jl04tak0d3mm47vdtahatqrlkngh9slya",
+            NetworkKind::Mainnet,
+        );
+
+        assert!(info.valid);
+        assert_eq!(info.kind, "sapling");
+        assert!(info.has_receiver_of_type(Pool::Sapling));
+        assert!(!info.has_receiver_of_type(Pool::Orchard));
+    }
+
+    #[test]
+    fn test_transparent_p2sh_address_has_only_transparent_receiver() {
+        let info = parse_address("t3JkEgmb4dTqGTkzGTjiyG9TkPZKN67J6kX", NetworkKind::Mainnet);
+
+        assert!(info.valid);
+        assert_eq!(info.kind, "transparent-p2sh");
+        assert!(info.has_receiver_of_type(Pool::Transparent));
+    }
+
+    #[test]
+    fn test_address_for_wrong_network_is_invalid() {
+        let info = parse_address("t1HxutHFt2Sejz7fs92wFVAbsFM7NDjsBG6", NetworkKind::Testnet);
+
+        assert!(!info.valid);
+        assert!(info.network.is_none());
+        assert!(info.error.is_some());
+    }
+
+    #[test]
+    fn test_garbage_input_is_invalid() {
+        let info = parse_address("not a zcash address", NetworkKind::Mainnet);
+
+        assert!(!info.valid);
+        assert!(info.receivers.is_empty());
+    }
+}
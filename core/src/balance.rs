@@ -0,0 +1,345 @@
+//! Wallet balance aggregation over scan results.
+//!
+//! [`ScanResult`] is transaction-scoped: it reports the notes and spends
+//! found in one transaction, with no memory of notes discovered in earlier
+//! transactions. Computing a wallet's balance means folding a whole
+//! sequence of these together, matching each spend against the note it
+//! consumes - which may have been received many transactions earlier - and
+//! subtracting its value. [`AccountBalance`] does that folding incrementally,
+//! so a caller syncing block-by-block can feed it one `ScanResult` at a time
+//! without re-scanning history to get an up-to-date balance.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Pool, ScanResult, ScannedNote, TransferType};
+
+/// A pool's balance, broken down by spendability.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolBalance {
+    /// Value of unspent notes with a known commitment-tree position,
+    /// received from someone else - ready to spend.
+    pub spendable: u64,
+    /// Value of unspent notes that are our own change (`TransferType::WalletInternal`).
+    pub pending_change: u64,
+    /// Value of unspent notes received from someone else but not yet
+    /// assigned a commitment-tree position (no confirmed block has
+    /// incorporated them yet, as far as this balance knows).
+    pub pending_spendable: u64,
+}
+
+impl PoolBalance {
+    /// The pool's total value across all three buckets.
+    pub fn total(&self) -> u64 {
+        self.spendable + self.pending_change + self.pending_spendable
+    }
+
+    fn bucket_mut(&mut self, bucket: Bucket) -> &mut u64 {
+        match bucket {
+            Bucket::Spendable => &mut self.spendable,
+            Bucket::PendingChange => &mut self.pending_change,
+            Bucket::PendingSpendable => &mut self.pending_spendable,
+        }
+    }
+}
+
+/// Which [`PoolBalance`] bucket a note's value has been counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bucket {
+    Spendable,
+    PendingChange,
+    PendingSpendable,
+}
+
+/// Identifies an outstanding note so a later spend can find and subtract it.
+///
+/// Shielded notes are keyed by nullifier (only known once decrypted with a
+/// spend-capable key and assigned a commitment position); transparent
+/// outputs are keyed by the outpoint they'll be spent by reference to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SpendKey {
+    Nullifier(String),
+    Outpoint(String, u32),
+}
+
+/// Classify a scanned note into the pool/bucket its value should be counted
+/// in, or `None` if it shouldn't count toward our balance at all.
+///
+/// Notes recovered via the outgoing viewing key (`TransferType::Outgoing`)
+/// are payments we sent to someone else - we hold no spend authority over
+/// them, so they never contribute to our balance. A transparent output has
+/// no `transfer_type` (it isn't scoped by a shielded key) and no commitment
+/// position to fall back on, so it's treated as spendable as soon as it's
+/// seen; an undecryptable shielded note (also `transfer_type: None`) isn't
+/// known to be ours and is ignored.
+fn classify_note(note: &ScannedNote) -> Option<(Pool, Bucket)> {
+    match note.transfer_type {
+        Some(TransferType::Outgoing) => None,
+        Some(TransferType::WalletInternal) => Some((note.pool, Bucket::PendingChange)),
+        Some(TransferType::Incoming) => {
+            let bucket = if note.position.is_some() {
+                Bucket::Spendable
+            } else {
+                Bucket::PendingSpendable
+            };
+            Some((note.pool, bucket))
+        }
+        None if note.pool == Pool::Transparent => Some((Pool::Transparent, Bucket::Spendable)),
+        None => None,
+    }
+}
+
+/// A wallet's balance across all three pools, folded from a sequence of
+/// [`ScanResult`]s via [`Self::apply_scan_result`].
+#[derive(Debug, Clone, Default)]
+pub struct AccountBalance {
+    pub transparent: PoolBalance,
+    pub sapling: PoolBalance,
+    pub orchard: PoolBalance,
+    /// Unspent notes counted so far, keyed for lookup when a later scan
+    /// result reports the spend that consumes them.
+    outstanding: HashMap<SpendKey, (Pool, Bucket, u64)>,
+}
+
+impl AccountBalance {
+    /// An empty balance, as at the start of a sync.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// This pool's balance.
+    pub fn pool(&self, pool: Pool) -> &PoolBalance {
+        match pool {
+            Pool::Transparent => &self.transparent,
+            Pool::Sapling => &self.sapling,
+            Pool::Orchard => &self.orchard,
+        }
+    }
+
+    /// Mutable access to a pool's balance, for callers that need to adjust
+    /// it directly (e.g. applying a manual correction).
+    pub fn pool_mut(&mut self, pool: Pool) -> &mut PoolBalance {
+        match pool {
+            Pool::Transparent => &mut self.transparent,
+            Pool::Sapling => &mut self.sapling,
+            Pool::Orchard => &mut self.orchard,
+        }
+    }
+
+    /// Total value across all three pools and every bucket.
+    pub fn total(&self) -> u64 {
+        self.transparent.total() + self.sapling.total() + self.orchard.total()
+    }
+
+    /// Fold one more transaction's scan result into this balance.
+    ///
+    /// Safe to call incrementally as new transactions are scanned - each
+    /// call only adds the notes and spends in `result`, so the caller never
+    /// needs to re-supply earlier `ScanResult`s.
+    pub fn apply_scan_result(&mut self, result: &ScanResult) {
+        for note in &result.notes {
+            let Some((pool, bucket)) = classify_note(note) else {
+                continue;
+            };
+            *self.pool_mut(pool).bucket_mut(bucket) += note.value;
+
+            let key = match pool {
+                Pool::Transparent => {
+                    Some(SpendKey::Outpoint(result.txid.clone(), note.output_index as u32))
+                }
+                Pool::Sapling | Pool::Orchard => note.nullifier.clone().map(SpendKey::Nullifier),
+            };
+            if let Some(key) = key {
+                self.outstanding.insert(key, (pool, bucket, note.value));
+            }
+        }
+
+        for spent in &result.spent_nullifiers {
+            self.subtract(SpendKey::Nullifier(spent.nullifier.clone()));
+        }
+        for spent in &result.transparent_spends {
+            self.subtract(SpendKey::Outpoint(
+                spent.prevout_txid.clone(),
+                spent.prevout_index,
+            ));
+        }
+    }
+
+    fn subtract(&mut self, key: SpendKey) {
+        if let Some((pool, bucket, value)) = self.outstanding.remove(&key) {
+            let field = self.pool_mut(pool).bucket_mut(bucket);
+            *field = field.saturating_sub(value);
+        }
+    }
+}
+
+/// Fold a sequence of `ScanResult`s into a single `AccountBalance`.
+///
+/// Equivalent to calling [`AccountBalance::apply_scan_result`] once per
+/// result in order; provided for the common one-shot case of computing a
+/// balance from a wallet's full scan history.
+pub fn compute_balance(results: &[ScanResult]) -> AccountBalance {
+    let mut balance = AccountBalance::new();
+    for result in results {
+        balance.apply_scan_result(result);
+    }
+    balance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SpentNullifier, TransparentSpend};
+
+    fn received_note(
+        pool: Pool,
+        value: u64,
+        transfer_type: Option<TransferType>,
+        position: Option<u64>,
+        nullifier: Option<&str>,
+    ) -> ScannedNote {
+        ScannedNote {
+            output_index: 0,
+            pool,
+            value,
+            commitment: String::new(),
+            nullifier: nullifier.map(|s| s.to_string()),
+            memo: None,
+            payment_request: None,
+            address: None,
+            transfer_type,
+            position,
+            rho: None,
+            rseed: None,
+        }
+    }
+
+    fn scan_result(txid: &str, notes: Vec<ScannedNote>) -> ScanResult {
+        ScanResult {
+            txid: txid.to_string(),
+            notes,
+            spent_nullifiers: Vec::new(),
+            transparent_spends: Vec::new(),
+            transparent_received: 0,
+            transparent_outputs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_incoming_confirmed_note_is_spendable() {
+        let note = received_note(Pool::Sapling, 1000, Some(TransferType::Incoming), Some(5), Some("nf1"));
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+
+        assert_eq!(balance.sapling.spendable, 1000);
+        assert_eq!(balance.total(), 1000);
+    }
+
+    #[test]
+    fn test_incoming_unconfirmed_note_is_pending_spendable() {
+        let note = received_note(Pool::Orchard, 500, Some(TransferType::Incoming), None, None);
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+
+        assert_eq!(balance.orchard.pending_spendable, 500);
+        assert_eq!(balance.orchard.spendable, 0);
+    }
+
+    #[test]
+    fn test_wallet_internal_note_is_pending_change() {
+        let note = received_note(Pool::Sapling, 200, Some(TransferType::WalletInternal), Some(1), Some("nf1"));
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+
+        assert_eq!(balance.sapling.pending_change, 200);
+    }
+
+    #[test]
+    fn test_outgoing_note_does_not_count_toward_balance() {
+        let note = received_note(Pool::Orchard, 999, Some(TransferType::Outgoing), None, None);
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+
+        assert_eq!(balance.total(), 0);
+    }
+
+    #[test]
+    fn test_transparent_output_is_spendable() {
+        let note = received_note(Pool::Transparent, 4000, None, None, None);
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+
+        assert_eq!(balance.transparent.spendable, 4000);
+    }
+
+    #[test]
+    fn test_spending_a_shielded_note_subtracts_its_value() {
+        let note = received_note(Pool::Sapling, 1000, Some(TransferType::Incoming), Some(5), Some("nf1"));
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+        assert_eq!(balance.sapling.spendable, 1000);
+
+        let mut spend_result = scan_result("tx2", Vec::new());
+        spend_result.spent_nullifiers.push(SpentNullifier {
+            pool: Pool::Sapling,
+            nullifier: "nf1".to_string(),
+        });
+        balance.apply_scan_result(&spend_result);
+
+        assert_eq!(balance.sapling.spendable, 0);
+        assert_eq!(balance.total(), 0);
+    }
+
+    #[test]
+    fn test_spending_a_transparent_output_subtracts_its_value() {
+        let note = received_note(Pool::Transparent, 2500, None, None, None);
+        let mut balance = AccountBalance::new();
+        balance.apply_scan_result(&scan_result("tx1", vec![note]));
+        assert_eq!(balance.transparent.spendable, 2500);
+
+        let mut spend_result = scan_result("tx2", Vec::new());
+        spend_result.transparent_spends.push(TransparentSpend {
+            prevout_txid: "tx1".to_string(),
+            prevout_index: 0,
+        });
+        balance.apply_scan_result(&spend_result);
+
+        assert_eq!(balance.transparent.spendable, 0);
+    }
+
+    #[test]
+    fn test_compute_balance_folds_a_sequence_of_results() {
+        let received = scan_result(
+            "tx1",
+            vec![received_note(
+                Pool::Orchard,
+                700,
+                Some(TransferType::Incoming),
+                Some(0),
+                Some("nf-orchard"),
+            )],
+        );
+        let mut spent = scan_result("tx2", Vec::new());
+        spent.spent_nullifiers.push(SpentNullifier {
+            pool: Pool::Orchard,
+            nullifier: "nf-orchard".to_string(),
+        });
+
+        let balance = compute_balance(&[received, spent]);
+        assert_eq!(balance.orchard.total(), 0);
+    }
+
+    #[test]
+    fn test_unrecognized_spend_is_a_no_op() {
+        let mut balance = AccountBalance::new();
+        let mut spend_result = scan_result("tx1", Vec::new());
+        spend_result.spent_nullifiers.push(SpentNullifier {
+            pool: Pool::Sapling,
+            nullifier: "unknown-nf".to_string(),
+        });
+        balance.apply_scan_result(&spend_result);
+
+        assert_eq!(balance.total(), 0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Generic append-style encoder and read-cursor decoder for binary formats.
+//!
+//! These primitives back the memo wire format (see [`crate::memo`]) so that
+//! offset arithmetic for header fields lives in one place instead of being
+//! hand-indexed (`memo[2..6]`-style slicing) at every call site. They are
+//! deliberately unaware of memo semantics - `scanner` can reuse the same
+//! cursor abstraction to parse other transaction bytes.
+
+/// Errors produced while encoding or decoding with [`Encoder`]/[`Decoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// A read ran past the end of the buffer.
+    UnexpectedEnd {
+        /// Number of bytes the read needed.
+        needed: usize,
+        /// Number of bytes actually remaining.
+        remaining: usize,
+    },
+    /// An encoded value does not fit in the requested width.
+    ValueTooWide {
+        /// The value that didn't fit.
+        value: u64,
+        /// The width, in bytes, it was asked to fit in.
+        n_bytes: usize,
+    },
+    /// The encoded buffer does not fit within a fixed-size output.
+    Overflow {
+        /// Number of bytes that were encoded.
+        len: usize,
+        /// Maximum number of bytes allowed.
+        max: usize,
+    },
+    /// A decoded variable-length integer used a wider prefix than the
+    /// minimal encoding for its value (e.g. 253 followed by a value <= 252).
+    NonMinimalVarint,
+}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedEnd { needed, remaining } => write!(
+                f,
+                "unexpected end of buffer: needed {} bytes, {} remaining",
+                needed, remaining
+            ),
+            Self::ValueTooWide { value, n_bytes } => {
+                write!(f, "value {} does not fit in {} bytes", value, n_bytes)
+            }
+            Self::Overflow { len, max } => {
+                write!(f, "encoded {} bytes, which exceeds the {}-byte limit", len, max)
+            }
+            Self::NonMinimalVarint => write!(f, "varint used a non-minimal encoding"),
+        }
+    }
+}
+
+impl core::error::Error for CodecError {}
+
+/// An append-only, builder-style byte encoder.
+#[derive(Debug, Clone, Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    /// Create an empty encoder.
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// Append a single byte.
+    pub fn encode_byte(&mut self, value: u8) -> &mut Self {
+        self.buf.push(value);
+        self
+    }
+
+    /// Append `n_bytes` of `value` in big-endian order.
+    ///
+    /// `n_bytes` may be 0-8 (a `u64` holds at most 8 bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::ValueTooWide`] if `value` does not fit in
+    /// `n_bytes`.
+    pub fn encode_uint(&mut self, n_bytes: usize, value: u64) -> Result<&mut Self, CodecError> {
+        if n_bytes < 8 && value >= (1u64 << (n_bytes * 8)) {
+            return Err(CodecError::ValueTooWide { value, n_bytes });
+        }
+        let be = value.to_be_bytes();
+        self.buf.extend_from_slice(&be[8 - n_bytes..]);
+        Ok(self)
+    }
+
+    /// Append raw bytes verbatim.
+    pub fn encode_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    /// Append a minimal-length variable-size integer:
+    ///
+    /// - `0..=252` as a single byte
+    /// - `253..=0xFFFF` as `253` followed by a big-endian `u16`
+    /// - `0x10000..=0xFFFFFFFF` as `254` followed by a big-endian `u32`
+    /// - everything else as `255` followed by a big-endian `u64`
+    pub fn encode_varint(&mut self, value: u64) -> &mut Self {
+        if value <= 252 {
+            self.buf.push(value as u8);
+        } else if value <= u16::MAX as u64 {
+            self.buf.push(253);
+            self.buf.extend_from_slice(&(value as u16).to_be_bytes());
+        } else if value <= u32::MAX as u64 {
+            self.buf.push(254);
+            self.buf.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            self.buf.push(255);
+            self.buf.extend_from_slice(&value.to_be_bytes());
+        }
+        self
+    }
+
+    /// Consume the encoder, returning the accumulated bytes.
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Consume the encoder, null-padding (or erroring) to exactly `size` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::Overflow`] if more than `size` bytes were encoded.
+    pub fn finish_padded(self, size: usize) -> Result<Vec<u8>, CodecError> {
+        if self.buf.len() > size {
+            return Err(CodecError::Overflow {
+                len: self.buf.len(),
+                max: size,
+            });
+        }
+        let mut out = vec![0u8; size];
+        out[..self.buf.len()].copy_from_slice(&self.buf);
+        Ok(out)
+    }
+}
+
+/// A read cursor over a borrowed byte slice.
+///
+/// Every read is bounds-checked and returns a `Result` instead of panicking
+/// on underrun.
+#[derive(Debug, Clone, Copy)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Wrap a byte slice for cursor-style reading, starting at offset 0.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    /// Current read offset into the underlying buffer.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CodecError> {
+        if self.remaining() < n {
+            return Err(CodecError::UnexpectedEnd {
+                needed: n,
+                remaining: self.remaining(),
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    /// Read a single byte.
+    pub fn decode_byte(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read `n` big-endian bytes as a `u64`.
+    ///
+    /// `n` may be 0-8.
+    pub fn decode_uint(&mut self, n: usize) -> Result<u64, CodecError> {
+        let slice = self.take(n)?;
+        let mut buf = [0u8; 8];
+        buf[8 - n..].copy_from_slice(slice);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    /// Read exactly `n` bytes.
+    pub fn decode_bytes(&mut self, n: usize) -> Result<&'a [u8], CodecError> {
+        self.take(n)
+    }
+
+    /// Read every remaining byte.
+    pub fn decode_remainder(&mut self) -> &'a [u8] {
+        let slice = &self.bytes[self.pos..];
+        self.pos = self.bytes.len();
+        slice
+    }
+
+    /// Read a minimal-length variable-size integer (see
+    /// [`Encoder::encode_varint`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::NonMinimalVarint`] if the value was encoded with
+    /// a wider prefix than necessary.
+    pub fn decode_varint(&mut self) -> Result<u64, CodecError> {
+        let prefix = self.decode_byte()?;
+        match prefix {
+            0..=252 => Ok(prefix as u64),
+            253 => {
+                let value = self.decode_uint(2)?;
+                if value <= 252 {
+                    return Err(CodecError::NonMinimalVarint);
+                }
+                Ok(value)
+            }
+            254 => {
+                let value = self.decode_uint(4)?;
+                if value <= u16::MAX as u64 {
+                    return Err(CodecError::NonMinimalVarint);
+                }
+                Ok(value)
+            }
+            _ => {
+                let value = self.decode_uint(8)?;
+                if value <= u32::MAX as u64 {
+                    return Err(CodecError::NonMinimalVarint);
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_mixed_fields() {
+        let mut enc = Encoder::new();
+        enc.encode_byte(0xAB);
+        enc.encode_uint(4, 1_700_000_000).unwrap();
+        enc.encode_uint(2, 42).unwrap();
+        enc.encode_bytes(b"hello");
+        let bytes = enc.finish();
+
+        let mut dec = Decoder::new(&bytes);
+        assert_eq!(dec.decode_byte().unwrap(), 0xAB);
+        assert_eq!(dec.decode_uint(4).unwrap(), 1_700_000_000);
+        assert_eq!(dec.decode_uint(2).unwrap(), 42);
+        assert_eq!(dec.decode_bytes(5).unwrap(), b"hello");
+        assert_eq!(dec.remaining(), 0);
+    }
+
+    #[test]
+    fn test_decode_underrun_is_an_error_not_a_panic() {
+        let bytes = [0u8; 2];
+        let mut dec = Decoder::new(&bytes);
+        let err = dec.decode_uint(4).unwrap_err();
+        assert_eq!(
+            err,
+            CodecError::UnexpectedEnd {
+                needed: 4,
+                remaining: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_encode_uint_rejects_overflow() {
+        let mut enc = Encoder::new();
+        let err = enc.encode_uint(1, 256).unwrap_err();
+        assert_eq!(
+            err,
+            CodecError::ValueTooWide {
+                value: 256,
+                n_bytes: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_finish_padded() {
+        let mut enc = Encoder::new();
+        enc.encode_bytes(b"ab");
+        let padded = enc.finish_padded(5).unwrap();
+        assert_eq!(padded, vec![b'a', b'b', 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_finish_padded_rejects_overflow() {
+        let mut enc = Encoder::new();
+        enc.encode_bytes(b"abcdef");
+        let err = enc.finish_padded(3).unwrap_err();
+        assert_eq!(err, CodecError::Overflow { len: 6, max: 3 });
+    }
+
+    #[test]
+    fn test_varint_roundtrip_across_widths() {
+        for value in [0u64, 1, 252, 253, 65535, 65536, u32::MAX as u64, 1 << 40] {
+            let mut enc = Encoder::new();
+            enc.encode_varint(value);
+            let bytes = enc.finish();
+            let mut dec = Decoder::new(&bytes);
+            assert_eq!(dec.decode_varint().unwrap(), value, "value {}", value);
+            assert_eq!(dec.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn test_varint_minimal_width() {
+        let mut enc = Encoder::new();
+        enc.encode_varint(252);
+        assert_eq!(enc.finish(), vec![252]);
+
+        let mut enc = Encoder::new();
+        enc.encode_varint(253);
+        assert_eq!(enc.finish(), vec![253, 0, 253]);
+    }
+
+    #[test]
+    fn test_varint_rejects_non_minimal_encoding() {
+        // 253 prefix encoding a value that fit in a single byte.
+        let bytes = [253u8, 0, 10];
+        let mut dec = Decoder::new(&bytes);
+        assert_eq!(dec.decode_varint().unwrap_err(), CodecError::NonMinimalVarint);
+
+        // 254 prefix encoding a value that fit in the u16 form.
+        let bytes = [254u8, 0, 0, 0, 10];
+        let mut dec = Decoder::new(&bytes);
+        assert_eq!(dec.decode_varint().unwrap_err(), CodecError::NonMinimalVarint);
+    }
+
+    #[test]
+    fn test_decode_remainder() {
+        let bytes = b"abcdef";
+        let mut dec = Decoder::new(bytes);
+        dec.decode_bytes(2).unwrap();
+        assert_eq!(dec.decode_remainder(), b"cdef");
+        assert_eq!(dec.remaining(), 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! General-purpose Zcash datum inspection.
+//!
+//! A user can paste almost anything into a "transaction viewer" - a raw
+//! transaction, a unified/Sapling/transparent address, or a viewing key -
+//! without saying which. [`inspect`] auto-detects which one it is and
+//! structurally decodes it, by trying each of this crate's existing
+//! type-specific inspectors in turn: [`inspect_transaction`], [`parse_address`],
+//! and [`parse_viewing_key`].
+
+use crate::address::parse_address;
+use crate::scanner::{inspect_transaction, parse_viewing_key};
+use crate::types::{DataInspection, DataKind, NetworkKind};
+
+/// Auto-detect what `input` is and structurally decode it.
+///
+/// Tries, in order: a raw transaction (hex-encoded), an address, then a
+/// viewing key. `network` is used to validate a transaction or address
+/// against the expected network; a viewing key instead reports whichever
+/// network it was itself encoded for.
+pub fn inspect(input: &str, network: NetworkKind) -> DataInspection {
+    let input = input.trim();
+
+    if let Ok(transaction) = inspect_transaction(input, network.to_network()) {
+        return DataInspection {
+            kind: DataKind::Transaction,
+            transaction: Some(transaction),
+            address: None,
+            viewing_key: None,
+            error: None,
+        };
+    }
+
+    let address = parse_address(input, network);
+    if address.valid {
+        return DataInspection {
+            kind: DataKind::Address,
+            transaction: None,
+            address: Some(address),
+            viewing_key: None,
+            error: None,
+        };
+    }
+
+    let viewing_key = parse_viewing_key(input);
+    if viewing_key.valid {
+        return DataInspection {
+            kind: DataKind::ViewingKey,
+            transaction: None,
+            address: None,
+            viewing_key: Some(viewing_key),
+            error: None,
+        };
+    }
+
+    DataInspection {
+        kind: DataKind::Unrecognized,
+        transaction: None,
+        address: None,
+        viewing_key: None,
+        error: Some("Unrecognized input - not a transaction, address, or viewing key".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_transparent_address() {
+        let result = inspect("t1HxutHFt2Sejz7fs92wFVAbsFM7NDjsBG6", NetworkKind::Mainnet);
+
+        assert_eq!(result.kind, DataKind::Address);
+        assert!(result.address.is_some());
+        assert!(result.transaction.is_none());
+        assert!(result.viewing_key.is_none());
+    }
+
+    #[test]
+    fn test_detects_sapling_address() {
+        let result = inspect(
+            "zs1z7rejlpsa98s2rrrfkwmaxu53e4ue0ulcrw0h4x5g8jl04tak0d3mm47vdtahatqrlkngh9slya",
+            NetworkKind::Mainnet,
+        );
+
+        assert_eq!(result.kind, DataKind::Address);
+    }
+
+    #[test]
+    fn test_garbage_input_is_unrecognized() {
+        let result = inspect("not a zcash datum", NetworkKind::Mainnet);
+
+        assert_eq!(result.kind, DataKind::Unrecognized);
+        assert!(result.error.is_some());
+    }
+}
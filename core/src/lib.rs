@@ -1,18 +1,67 @@
+pub mod address;
+pub mod balance;
+pub mod block_scanner;
+pub mod codec;
+pub mod inspect;
+pub mod memo;
+pub mod proposal;
 pub mod scanner;
+pub mod send;
+pub mod shield;
+pub mod storage;
+pub mod tree;
 pub mod types;
 pub mod wallet;
+pub mod zip321;
 
+pub use address::parse_address;
+pub use balance::{AccountBalance, PoolBalance, compute_balance};
+pub use block_scanner::{
+    AccountBirthday, BlockScanError, BlockScanResult, BlockScanner, NullifierQuery, WitnessedNote,
+    parse_compact_blocks, scan_compact_blocks, scan_compact_blocks_bytes,
+};
+pub use codec::{CodecError, Decoder, Encoder};
+pub use inspect::inspect;
+pub use memo::{
+    AckInfo, ConversationTracker, EXTENSION_TYPE_CONTENT_TYPE, EXTENSION_TYPE_REPLY_TO,
+    FragmentInfo, Memo, MemoBytes, MemoError, MemoType, Message, ReplyTo, decode_content_type,
+    decode_message_memo, decode_reply_to, encode_ack_memo, encode_content_type,
+    encode_message_fragments, encode_message_memo, encode_message_memo_with_extensions,
+    encode_reply_to, reassemble_fragments,
+};
+pub use proposal::{PROPOSAL_SER_V1, Proposal, ProposalError, ProposalInput};
 pub use scanner::{
-    ScannerError, extract_nullifiers, parse_transaction, parse_viewing_key_capabilities,
-    scan_transaction, scan_transaction_hex,
+    ScannerError, extract_nullifiers, inspect_transaction, parse_transaction, parse_viewing_key,
+    parse_viewing_key_capabilities, scan_compact_block, scan_transaction, scan_transaction_hex,
+    scan_transactions_batch,
+};
+pub use send::{SendError, SendOutput, build_transaction};
+pub use shield::{ShieldError, build_shielding_transaction};
+pub use storage::{
+    DEFAULT_DUST_THRESHOLD_ZAT, DetailedBalanceResult, MAX_REORG, NoteCollection,
+    NoteConsolidationResult, NoteSelectionResult, RollbackResult, StorageResult, StoredNote,
+    StoredWallet, WalletCollection, calculate_balance_detailed, plan_note_consolidation,
+    rollback_notes_to_height, select_spendable_notes,
+};
+pub use tree::{
+    NoteCommitmentTrees, OrchardCommitmentTree, OrchardWitness, TreeError,
+    deserialize_orchard_tree, deserialize_orchard_witness, deserialize_sapling_tree,
+    deserialize_sapling_witness, orchard_anchor_hex, orchard_commitment_from_hex,
+    sapling_anchor_hex, sapling_commitment_from_hex, serialize_orchard_tree,
+    serialize_orchard_witness, serialize_sapling_tree, serialize_sapling_witness,
 };
 pub use types::{
-    DecryptedOrchardAction, DecryptedSaplingOutput, DecryptedTransaction, DecryptionResult,
-    NetworkKind, Pool, ScanResult, ScanTransactionResult, ScannedNote, ScannedTransparentOutput,
-    SpentNullifier, TransparentInput, TransparentOutput, TransparentSpend, ViewingKeyInfo,
-    WalletResult,
+    AddressInfo, DataInspection, DataKind, DecryptedOrchardAction, DecryptedSaplingOutput,
+    DecryptedSentOutput, DecryptedTransaction, DecryptionResult, EncryptedWalletResult,
+    InspectionResult, NetworkKind, Pool, ScanCompactBlocksResult, ScanResult,
+    ScanTransactionResult, ScannedNote, ScannedTransparentOutput, SpentNullifier, TransferType,
+    TransparentInput, TransparentOutput, TransparentSpend, TransparentUtxo, TxInspection,
+    ViewingKeyInfo, WalletResult,
 };
 pub use wallet::{
-    WalletInfo, derive_transparent_addresses, derive_unified_addresses, derive_wallet,
-    generate_wallet, restore_wallet,
+    EncryptedWallet, KdfParams, MnemonicInspection, ReceiverSelection, WalletInfo,
+    decrypt_wallet, derive_transparent_addresses, derive_unified_addresses, derive_wallet,
+    discover_unified_addresses, encrypt_wallet, find_diversifier_index, generate_wallet,
+    inspect_mnemonic, restore_wallet,
 };
+pub use zip321::{Payment, PaymentRecipient, PaymentRequestError, build_payment_uri, parse_payment_uri};
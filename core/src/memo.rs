@@ -1,41 +1,311 @@
 //! Memo encoding and decoding for encrypted messaging.
 //!
-//! This module implements a protocol for encoding messages in Zcash transaction memos.
-//! Memos are 512 bytes and encrypted along with shielded notes, providing end-to-end
-//! encrypted messaging.
+//! This module implements [ZIP 302](https://zips.z.cash/zip-0302) memo field
+//! interpretation, plus a proprietary messaging protocol layered on top of it
+//! so that this wallet's structured messages stay interoperable with other
+//! Zcash wallets.
 //!
-//! # Memo Format
+//! # ZIP 302 interpretation
+//!
+//! Every memo is exactly 512 null-padded bytes (a [`MemoBytes`]). Byte 0
+//! determines how the remaining bytes are interpreted:
+//!
+//! ```text
+//! 0x00-0xF4  UTF-8 text memo (trailing NUL bytes trimmed)      -> Memo::Text
+//! 0xF5       "do not display" arbitrary binary data            -> Memo::Arbitrary
+//! 0xF6       empty memo (all remaining bytes must be zero)     -> Memo::Empty
+//! 0xF7-0xFF  reserved for future use, preserved verbatim       -> Memo::Future
+//! ```
+//!
+//! # Our messaging protocol
+//!
+//! To stay interoperable with standards-compliant wallets (which will show
+//! "arbitrary memo" rather than choke on unrecognized bytes), our
+//! version/type/timestamp/nonce/fragment header lives entirely inside the
+//! ZIP 302 "arbitrary" namespace (byte 0 == 0xF5):
 //!
 //! ```text
-//! [0]      version (0x01)
-//! [1]      type (0x00=text, 0x01=ack, 0x02=fragment)
-//! [2-5]    timestamp (u32, unix epoch, big-endian)
-//! [6-9]    nonce (u32, for dedup/ordering, big-endian)
-//! [10-13]  fragment info (if type=0x02): total_fragments(u16) + index(u16), big-endian
-//! [14-511] payload (UTF-8 text, null-padded)
+//! [0]      ZIP 302 arbitrary marker (0xF5)
+//! [1]      our protocol version (0x01)
+//! [2]      our message type (0x00=text, 0x01=ack, 0x02=fragment,
+//!          0x03=compressed text, 0x04=compressed fragment)
+//! [3-6]    timestamp (u32, unix epoch, big-endian)
+//! [7-10]   nonce (u32, for dedup/ordering, big-endian)
+//! [11-14]  fragment info (if type=0x02/0x04): total_fragments(u16) + index(u16), big-endian
+//! [15-*]   extensions: varint length, then that many bytes of TLV records
+//! [*-511]  payload (UTF-8 text, null-padded, or see "Compression" below)
 //! ```
 //!
+//! # Extensions
+//!
+//! The extensions block holds a sequence of `type:varint, length:varint,
+//! value:[length bytes]` records, sorted ascending by type with no duplicate
+//! types. This lets new fields (see [`EXTENSION_TYPE_REPLY_TO`],
+//! [`EXTENSION_TYPE_CONTENT_TYPE`]) ship without a breaking version bump:
+//! unknown *even* types are a hard error, unknown *odd* types are skipped
+//! ("it's okay to be odd").
+//!
+//! # Compression
+//!
+//! Every fragment is a separate, fee-costing shielded output, so
+//! [`encode_message_memo`] and [`encode_message_fragments`] try DEFLATE
+//! compression of the message text and use it - recording
+//! [`MemoType::CompressedText`]/[`MemoType::CompressedFragment`] instead of
+//! `Text`/`Fragment` - whenever that's strictly smaller than the raw bytes.
+//! The compressed payload is `varint(original_len) ++ deflate_bytes`;
+//! `original_len` both bounds the inflate (rejecting decompression bombs)
+//! and lets the decoder verify the round trip.
+//!
+//! # Acknowledgments
+//!
+//! [`encode_ack_memo`] builds a `MemoType::Ack` memo referencing the
+//! acknowledged message by the same timestamp+nonce pair its own header
+//! carries - mirroring how those fields already identify a message for
+//! dedup/ordering, rather than introducing a separate reference field. Its
+//! payload is `total_fragments(u16) ++ bitmap`, one bit per received
+//! fragment index, so the sender learns exactly which fragments to
+//! retransmit. [`ConversationTracker`] ingests decoded acks to track, per
+//! sent message, which fragments remain outstanding.
+//!
 //! # Message Fragmentation
 //!
-//! Messages longer than 498 bytes (512 - 14 byte header) are split across multiple
-//! memos with type=0x02 (fragment). Fragments share the same timestamp and nonce
-//! to enable reassembly.
+//! Messages whose (possibly compressed) payload is longer than
+//! [`MAX_PAYLOAD_SIZE`] bytes are split across multiple memos with
+//! type=0x02/0x04 (fragment/compressed fragment). Fragments share the same
+//! timestamp and nonce to enable reassembly.
 
 use serde::{Deserialize, Serialize};
 
+use crate::codec::{CodecError, Decoder, Encoder};
+
+impl From<CodecError> for MemoError {
+    fn from(err: CodecError) -> Self {
+        match err {
+            CodecError::UnexpectedEnd { remaining, .. } => MemoError::TooShort(remaining),
+            CodecError::ValueTooWide { value, .. } => MemoError::MessageTooLong(value as usize),
+            CodecError::Overflow { len, .. } => MemoError::MessageTooLong(len),
+            CodecError::NonMinimalVarint => {
+                MemoError::InvalidExtension("non-minimal varint encoding".to_string())
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for MemoError {
+    fn from(err: std::io::Error) -> Self {
+        MemoError::InvalidCompression(err.to_string())
+    }
+}
+
 /// Maximum size of a Zcash transaction memo in bytes.
 pub const MEMO_SIZE: usize = 512;
 
-/// Size of the memo header in bytes.
+/// ZIP 302 leading byte marking "do not display" arbitrary data.
+pub const ZIP302_ARBITRARY_MARKER: u8 = 0xF5;
+
+/// ZIP 302 leading byte marking an empty memo.
+pub const ZIP302_EMPTY_MARKER: u8 = 0xF6;
+
+/// Size of our protocol header in bytes, not counting the ZIP 302 marker byte.
 pub const HEADER_SIZE: usize = 14;
 
-/// Maximum size of the payload (memo size - header size).
-pub const MAX_PAYLOAD_SIZE: usize = MEMO_SIZE - HEADER_SIZE;
+/// Smallest possible encoding of the extensions block: a single varint byte
+/// recording zero bytes of TLV records.
+pub const MIN_EXTENSIONS_SIZE: usize = 1;
+
+/// Maximum size of the payload (memo size - ZIP 302 marker - header size -
+/// the minimum extensions block). Messages that carry extensions have less
+/// room than this for their text.
+pub const MAX_PAYLOAD_SIZE: usize = MEMO_SIZE - 1 - HEADER_SIZE - MIN_EXTENSIONS_SIZE;
 
-/// Protocol version for memo format.
+/// Protocol version for our memo format.
 pub const MEMO_VERSION: u8 = 0x01;
 
-/// Memo type codes.
+/// Extension record type for a reply-to reference, pointing at the
+/// timestamp+nonce of the message being replied to. Odd type code: safe to
+/// skip if unrecognized.
+pub const EXTENSION_TYPE_REPLY_TO: u64 = 1;
+
+/// Extension record type carrying a MIME-style content type string for the
+/// payload (e.g. `text/plain`). Odd type code: safe to skip if unrecognized.
+pub const EXTENSION_TYPE_CONTENT_TYPE: u64 = 3;
+
+/// A guaranteed-512-byte, null-padded memo buffer.
+///
+/// `MemoBytes` only enforces the one rule every Zcash memo must satisfy: it
+/// fits in [`MEMO_SIZE`] bytes. Interpreting the contents (ZIP 302 text,
+/// arbitrary data, or our own message protocol) is [`Memo`]'s job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoBytes(Box<[u8; MEMO_SIZE]>);
+
+impl MemoBytes {
+    /// Build a `MemoBytes` from up to 512 bytes, null-padding the remainder.
+    ///
+    /// The only failure mode is the input being longer than [`MEMO_SIZE`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MemoError> {
+        if data.len() > MEMO_SIZE {
+            return Err(MemoError::MessageTooLong(data.len()));
+        }
+        let mut buf = [0u8; MEMO_SIZE];
+        buf[..data.len()].copy_from_slice(data);
+        Ok(Self(Box::new(buf)))
+    }
+
+    /// Build a `MemoBytes` from an exact 512-byte array.
+    pub fn from_array(bytes: [u8; MEMO_SIZE]) -> Self {
+        Self(Box::new(bytes))
+    }
+
+    /// The ZIP 302 empty memo: byte 0 is 0xF6, everything else zero.
+    pub fn empty() -> Self {
+        let mut buf = [0u8; MEMO_SIZE];
+        buf[0] = ZIP302_EMPTY_MARKER;
+        Self(Box::new(buf))
+    }
+
+    /// Borrow the full 512-byte buffer.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    /// Borrow the full 512-byte array.
+    pub fn as_array(&self) -> &[u8; MEMO_SIZE] {
+        &self.0
+    }
+
+    /// Consume this value, returning the underlying 512-byte array.
+    pub fn into_array(self) -> [u8; MEMO_SIZE] {
+        *self.0
+    }
+}
+
+/// A ZIP 302-interpreted memo.
+///
+/// Round-trips through [`MemoBytes`] via [`Memo::from_bytes`] /
+/// [`Memo::to_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Memo {
+    /// No memo was provided (ZIP 302 byte[0] == 0xF6, rest zero).
+    Empty,
+    /// A UTF-8 text memo (ZIP 302 byte[0] <= 0xF4).
+    Text(String),
+    /// "Do not display" arbitrary binary data (ZIP 302 byte[0] == 0xF5).
+    ///
+    /// Our own messaging protocol lives in this namespace - see
+    /// [`encode_message_memo`] and [`decode_message_memo`].
+    Arbitrary(MemoBytes),
+    /// Reserved for future ZIP 302 use (byte[0] in 0xF7-0xFF, or any pattern
+    /// that doesn't otherwise parse). Preserved verbatim rather than
+    /// rejected, so forward-compatible clients don't lose data.
+    Future(MemoBytes),
+}
+
+impl Memo {
+    /// Interpret a raw memo buffer according to ZIP 302.
+    pub fn from_bytes(raw: &MemoBytes) -> Result<Self, MemoError> {
+        let bytes = raw.as_slice();
+
+        match bytes[0] {
+            ZIP302_EMPTY_MARKER if bytes[1..].iter().all(|&b| b == 0) => Ok(Memo::Empty),
+            0x00..=0xF4 => {
+                let end = bytes
+                    .iter()
+                    .rposition(|&b| b != 0)
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                let text = core::str::from_utf8(&bytes[..end])
+                    .map_err(|_| MemoError::InvalidUtf8)?
+                    .to_string();
+                Ok(Memo::Text(text))
+            }
+            ZIP302_ARBITRARY_MARKER => Ok(Memo::Arbitrary(raw.clone())),
+            _ => Ok(Memo::Future(raw.clone())),
+        }
+    }
+
+    /// Serialize back to a raw 512-byte memo buffer.
+    pub fn to_bytes(&self) -> MemoBytes {
+        match self {
+            Memo::Empty => MemoBytes::empty(),
+            Memo::Text(text) => {
+                // Valid UTF-8 can never start with a byte > 0xF4 (the highest
+                // leading byte of a 4-byte sequence), so this always round-trips.
+                MemoBytes::from_bytes(text.as_bytes())
+                    .expect("caller must ensure text fits in MEMO_SIZE bytes")
+            }
+            Memo::Arbitrary(bytes) | Memo::Future(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// JSON representation of a [`Memo`], tagged by kind so JavaScript callers
+/// can reliably branch on it instead of guessing from field shape.
+///
+/// `Arbitrary`/`Future` payloads are hex-encoded rather than emitted as a
+/// byte array, since they're opaque binary data from the caller's
+/// perspective. The leading ZIP 302 marker byte is stripped from `data` for
+/// `Arbitrary` (it's always `0xF5`) but kept explicit as `tag` for `Future`,
+/// since that's the byte that distinguishes one reserved-use memo from
+/// another.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum MemoJson {
+    Empty,
+    Text { text: String },
+    Arbitrary { data: String },
+    Future { tag: u8, data: String },
+}
+
+impl Serialize for Memo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let json = match self {
+            Memo::Empty => MemoJson::Empty,
+            Memo::Text(text) => MemoJson::Text { text: text.clone() },
+            Memo::Arbitrary(bytes) => MemoJson::Arbitrary {
+                data: hex::encode(&bytes.as_slice()[1..]),
+            },
+            Memo::Future(bytes) => MemoJson::Future {
+                tag: bytes.as_slice()[0],
+                data: hex::encode(&bytes.as_slice()[1..]),
+            },
+        };
+        json.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Memo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let json = MemoJson::deserialize(deserializer)?;
+        Ok(match json {
+            MemoJson::Empty => Memo::Empty,
+            MemoJson::Text { text } => Memo::Text(text),
+            MemoJson::Arbitrary { data } => {
+                let mut buf = [0u8; MEMO_SIZE];
+                buf[0] = ZIP302_ARBITRARY_MARKER;
+                let payload = hex::decode(&data).map_err(serde::de::Error::custom)?;
+                let end = (payload.len()).min(MEMO_SIZE - 1);
+                buf[1..1 + end].copy_from_slice(&payload[..end]);
+                Memo::Arbitrary(MemoBytes::from_array(buf))
+            }
+            MemoJson::Future { tag, data } => {
+                let mut buf = [0u8; MEMO_SIZE];
+                buf[0] = tag;
+                let payload = hex::decode(&data).map_err(serde::de::Error::custom)?;
+                let end = (payload.len()).min(MEMO_SIZE - 1);
+                buf[1..1 + end].copy_from_slice(&payload[..end]);
+                Memo::Future(MemoBytes::from_array(buf))
+            }
+        })
+    }
+}
+
+/// Memo type codes for our own messaging protocol.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum MemoType {
@@ -45,6 +315,11 @@ pub enum MemoType {
     Ack = 0x01,
     /// Message fragment (for messages > MAX_PAYLOAD_SIZE).
     Fragment = 0x02,
+    /// Plain text message, DEFLATE-compressed (see [`MAX_PAYLOAD_SIZE`] and
+    /// the module-level "Compression" section).
+    CompressedText = 0x03,
+    /// Message fragment, DEFLATE-compressed.
+    CompressedFragment = 0x04,
 }
 
 impl MemoType {
@@ -54,9 +329,21 @@ impl MemoType {
             0x00 => Ok(Self::Text),
             0x01 => Ok(Self::Ack),
             0x02 => Ok(Self::Fragment),
+            0x03 => Ok(Self::CompressedText),
+            0x04 => Ok(Self::CompressedFragment),
             _ => Err(MemoError::InvalidType(value)),
         }
     }
+
+    /// Whether this type represents a message fragment (plain or compressed).
+    fn is_fragment(self) -> bool {
+        matches!(self, Self::Fragment | Self::CompressedFragment)
+    }
+
+    /// Whether this type's payload is DEFLATE-compressed.
+    fn is_compressed(self) -> bool {
+        matches!(self, Self::CompressedText | Self::CompressedFragment)
+    }
 }
 
 /// Fragment information for multi-part messages.
@@ -68,6 +355,18 @@ pub struct FragmentInfo {
     pub index: u16,
 }
 
+/// A delivery acknowledgment, referencing the acknowledged message by the
+/// `timestamp`/`nonce` pair carried in the [`Message`]'s header (see
+/// [`encode_ack_memo`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AckInfo {
+    /// Total number of fragments in the acknowledged message (1 for an
+    /// unfragmented message).
+    pub total_fragments: u16,
+    /// Indices of fragments received so far, ascending, no duplicates.
+    pub received_fragments: Vec<u16>,
+}
+
 /// A decoded message from a memo.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Message {
@@ -82,8 +381,22 @@ pub struct Message {
     pub nonce: u32,
     /// Fragment information (only for Fragment type).
     pub fragment_info: Option<FragmentInfo>,
+    /// Delivery-acknowledgment payload (only for [`MemoType::Ack`]).
+    pub ack: Option<AckInfo>,
+    /// TLV extension records, sorted ascending by type. Unknown odd-typed
+    /// records are silently dropped during decoding, per the "it's okay to
+    /// be odd" rule - so this only ever contains recognized records.
+    pub extensions: Vec<(u64, Vec<u8>)>,
     /// Message content (UTF-8 text).
+    ///
+    /// For a [`MemoType::CompressedFragment`] message, the payload cannot be
+    /// inflated until every fragment is known, so this is empty and the raw
+    /// bytes are carried in [`Message::raw_payload`] instead - use
+    /// [`reassemble_fragments`] to get the final content.
     pub content: String,
+    /// Raw, not-yet-decompressed payload bytes. Only populated for
+    /// [`MemoType::CompressedFragment`] messages; empty for every other type.
+    pub raw_payload: Vec<u8>,
 }
 
 /// Errors that can occur during memo operations.
@@ -101,6 +414,14 @@ pub enum MemoError {
     MessageTooLong(usize),
     /// Invalid fragment info.
     InvalidFragmentInfo(String),
+    /// Memo did not fall into our own namespace (ZIP 302 byte[0] != 0xF5).
+    NotOurNamespace(u8),
+    /// Invalid extension records (bad ordering, duplicate type, or an
+    /// unrecognized critical/even type).
+    InvalidExtension(String),
+    /// A compressed payload failed to inflate, or inflated to a size other
+    /// than the length recorded by the encoder.
+    InvalidCompression(String),
 }
 
 impl core::fmt::Display for MemoError {
@@ -111,7 +432,8 @@ impl core::fmt::Display for MemoError {
             Self::TooShort(len) => write!(
                 f,
                 "Memo too short: {} bytes (expected at least {})",
-                len, HEADER_SIZE
+                len,
+                HEADER_SIZE + 1
             ),
             Self::InvalidUtf8 => write!(f, "Invalid UTF-8 in memo payload"),
             Self::MessageTooLong(len) => write!(
@@ -120,6 +442,13 @@ impl core::fmt::Display for MemoError {
                 len, MAX_PAYLOAD_SIZE
             ),
             Self::InvalidFragmentInfo(msg) => write!(f, "Invalid fragment info: {}", msg),
+            Self::NotOurNamespace(b) => write!(
+                f,
+                "Memo is not one of our structured messages (ZIP 302 leading byte 0x{:02x}, expected 0x{:02x})",
+                b, ZIP302_ARBITRARY_MARKER
+            ),
+            Self::InvalidExtension(msg) => write!(f, "Invalid extension records: {}", msg),
+            Self::InvalidCompression(msg) => write!(f, "Invalid compressed payload: {}", msg),
         }
     }
 }
@@ -128,8 +457,15 @@ impl core::error::Error for MemoError {}
 
 /// Encode a text message into a memo.
 ///
-/// If the message fits in a single memo (‚â§498 bytes), creates a Text memo.
-/// Otherwise, returns an error - use `encode_message_fragments` for long messages.
+/// The text is DEFLATE-compressed when that makes it smaller (see the
+/// module-level "Compression" section). If the resulting payload fits in a
+/// single memo (<= [`MAX_PAYLOAD_SIZE`] bytes), creates a Text or
+/// CompressedText memo. Otherwise, returns an error - use
+/// `encode_message_fragments` for long messages.
+///
+/// The resulting bytes are a ZIP 302 "arbitrary" memo (leading byte 0xF5)
+/// so standards-compliant wallets display "arbitrary memo" instead of
+/// misinterpreting our structured header as text.
 ///
 /// # Arguments
 ///
@@ -141,26 +477,30 @@ impl core::error::Error for MemoError {}
 ///
 /// A 512-byte memo with the encoded message.
 pub fn encode_message_memo(text: &str, timestamp: u32, nonce: u32) -> Result<Vec<u8>, MemoError> {
-    let text_bytes = text.as_bytes();
-
-    if text_bytes.len() > MAX_PAYLOAD_SIZE {
-        return Err(MemoError::MessageTooLong(text_bytes.len()));
-    }
-
-    let mut memo = vec![0u8; MEMO_SIZE];
+    encode_message_memo_with_extensions(text, timestamp, nonce, &[])
+}
 
-    // Write header
-    memo[0] = MEMO_VERSION;
-    memo[1] = MemoType::Text as u8;
-    memo[2..6].copy_from_slice(&timestamp.to_be_bytes());
-    memo[6..10].copy_from_slice(&nonce.to_be_bytes());
-    // Fragment info (bytes 10-13) left as zeros for non-fragment messages
+/// Like [`encode_message_memo`], but additionally attaches TLV extension
+/// records - see [`EXTENSION_TYPE_REPLY_TO`] and [`EXTENSION_TYPE_CONTENT_TYPE`].
+///
+/// `extensions` must already be sorted ascending by type with no duplicate
+/// types; each extension eats into the payload budget, so fewer/smaller
+/// extensions leave more room for text.
+pub fn encode_message_memo_with_extensions(
+    text: &str,
+    timestamp: u32,
+    nonce: u32,
+    extensions: &[(u64, Vec<u8>)],
+) -> Result<Vec<u8>, MemoError> {
+    let (msg_type, payload) =
+        compress_payload_if_smaller(text.as_bytes(), MemoType::Text, MemoType::CompressedText);
 
-    // Write payload
-    memo[HEADER_SIZE..HEADER_SIZE + text_bytes.len()].copy_from_slice(text_bytes);
-    // Remaining bytes already zeroed (null-padded)
+    let mut enc = Encoder::new();
+    write_header(&mut enc, msg_type, timestamp, nonce, None)?;
+    encode_extensions(&mut enc, extensions)?;
+    enc.encode_bytes(&payload);
 
-    Ok(memo)
+    Ok(enc.finish_padded(MEMO_SIZE)?)
 }
 
 /// Encode a long message into multiple memo fragments.
@@ -182,73 +522,352 @@ pub fn encode_message_fragments(
     timestamp: u32,
     nonce: u32,
 ) -> Result<Vec<Vec<u8>>, MemoError> {
-    let text_bytes = text.as_bytes();
+    let (msg_type, payload) = compress_payload_if_smaller(
+        text.as_bytes(),
+        MemoType::Fragment,
+        MemoType::CompressedFragment,
+    );
 
     // Calculate number of fragments needed
-    let total_fragments = text_bytes.len().div_ceil(MAX_PAYLOAD_SIZE);
+    let total_fragments = payload.len().div_ceil(MAX_PAYLOAD_SIZE);
 
     if total_fragments > u16::MAX as usize {
-        return Err(MemoError::MessageTooLong(text_bytes.len()));
+        return Err(MemoError::MessageTooLong(payload.len()));
     }
 
     let mut fragments = Vec::new();
 
-    for (index, chunk) in text_bytes.chunks(MAX_PAYLOAD_SIZE).enumerate() {
-        let mut memo = vec![0u8; MEMO_SIZE];
+    for (index, chunk) in payload.chunks(MAX_PAYLOAD_SIZE).enumerate() {
+        let mut enc = Encoder::new();
+        write_header(
+            &mut enc,
+            msg_type,
+            timestamp,
+            nonce,
+            Some(FragmentInfo {
+                total_fragments: total_fragments as u16,
+                index: index as u16,
+            }),
+        )?;
+        encode_extensions(&mut enc, &[])?;
+        enc.encode_bytes(chunk);
+
+        fragments.push(enc.finish_padded(MEMO_SIZE)?);
+    }
+
+    Ok(fragments)
+}
+
+/// Write the ZIP 302 arbitrary marker followed by our protocol header
+/// (version/type/timestamp/nonce/fragment info).
+fn write_header(
+    enc: &mut Encoder,
+    msg_type: MemoType,
+    timestamp: u32,
+    nonce: u32,
+    fragment_info: Option<FragmentInfo>,
+) -> Result<(), MemoError> {
+    enc.encode_byte(ZIP302_ARBITRARY_MARKER)
+        .encode_byte(MEMO_VERSION)
+        .encode_byte(msg_type as u8)
+        .encode_uint(4, timestamp as u64)?
+        .encode_uint(4, nonce as u64)?;
+
+    let (total_fragments, index) = fragment_info
+        .map(|info| (info.total_fragments, info.index))
+        .unwrap_or((0, 0));
+    enc.encode_uint(2, total_fragments as u64)?
+        .encode_uint(2, index as u64)?;
+
+    Ok(())
+}
 
-        // Write header
-        memo[0] = MEMO_VERSION;
-        memo[1] = MemoType::Fragment as u8;
-        memo[2..6].copy_from_slice(&timestamp.to_be_bytes());
-        memo[6..10].copy_from_slice(&nonce.to_be_bytes());
+/// Encode the extensions block: a varint byte length followed by that many
+/// bytes of sorted, deduplicated TLV records.
+fn encode_extensions(enc: &mut Encoder, extensions: &[(u64, Vec<u8>)]) -> Result<(), MemoError> {
+    for pair in extensions.windows(2) {
+        if pair[0].0 >= pair[1].0 {
+            return Err(MemoError::InvalidExtension(format!(
+                "extension records must be sorted ascending by type with no duplicates (got {} then {})",
+                pair[0].0, pair[1].0
+            )));
+        }
+    }
 
-        // Write fragment info
-        memo[10..12].copy_from_slice(&(total_fragments as u16).to_be_bytes());
-        memo[12..14].copy_from_slice(&(index as u16).to_be_bytes());
+    let mut body = Encoder::new();
+    for (ty, value) in extensions {
+        body.encode_varint(*ty)
+            .encode_varint(value.len() as u64)
+            .encode_bytes(value);
+    }
+    let body = body.finish();
 
-        // Write payload
-        memo[HEADER_SIZE..HEADER_SIZE + chunk.len()].copy_from_slice(chunk);
+    enc.encode_varint(body.len() as u64).encode_bytes(&body);
+    Ok(())
+}
 
-        fragments.push(memo);
+/// Decode the extensions block. Unknown even-typed records are a hard error;
+/// unknown odd-typed records are skipped ("it's okay to be odd").
+fn decode_extensions(dec: &mut Decoder) -> Result<Vec<(u64, Vec<u8>)>, MemoError> {
+    let len = dec.decode_varint()? as usize;
+    let body = dec.decode_bytes(len)?;
+    let mut body_dec = Decoder::new(body);
+
+    let mut extensions = Vec::new();
+    let mut last_type: Option<u64> = None;
+
+    while body_dec.remaining() > 0 {
+        let ty = body_dec.decode_varint()?;
+        let value_len = body_dec.decode_varint()? as usize;
+        let value = body_dec.decode_bytes(value_len)?.to_vec();
+
+        if let Some(last) = last_type
+            && ty <= last
+        {
+            return Err(MemoError::InvalidExtension(format!(
+                "extension records must be sorted ascending by type with no duplicates (got {} after {})",
+                ty, last
+            )));
+        }
+        last_type = Some(ty);
+
+        let known = matches!(ty, EXTENSION_TYPE_REPLY_TO | EXTENSION_TYPE_CONTENT_TYPE);
+        if !known {
+            if ty % 2 == 0 {
+                return Err(MemoError::InvalidExtension(format!(
+                    "unrecognized critical (even) extension type {}",
+                    ty
+                )));
+            }
+            // Unknown, odd: skip it.
+            continue;
+        }
+
+        extensions.push((ty, value));
     }
 
-    Ok(fragments)
+    Ok(extensions)
+}
+
+/// Try DEFLATE-compressing `raw`, returning the compressed message type and
+/// payload (`varint(raw.len()) ++ deflate_bytes`) when that's strictly
+/// smaller than `raw` itself, or the uncompressed type and `raw` unchanged
+/// otherwise.
+fn compress_payload_if_smaller(
+    raw: &[u8],
+    uncompressed_type: MemoType,
+    compressed_type: MemoType,
+) -> (MemoType, Vec<u8>) {
+    let deflated = deflate(raw);
+
+    let mut candidate = Encoder::new();
+    candidate.encode_varint(raw.len() as u64).encode_bytes(&deflated);
+    let candidate = candidate.finish();
+
+    if candidate.len() < raw.len() {
+        (compressed_type, candidate)
+    } else {
+        (uncompressed_type, raw.to_vec())
+    }
+}
+
+/// DEFLATE-compress `raw`.
+fn deflate(raw: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(raw)
+        .expect("writing to a Vec<u8> cannot fail");
+    encoder.finish().expect("writing to a Vec<u8> cannot fail")
+}
+
+/// Inflate `compressed`, expecting exactly `original_len` bytes out.
+///
+/// Reads at most `original_len + 1` bytes so an unexpectedly large (or
+/// maliciously crafted "decompression bomb") inflated size is caught rather
+/// than fully materialized; any mismatch against `original_len` - too much
+/// or too little - is a [`MemoError::InvalidCompression`].
+fn inflate(compressed: &[u8], original_len: usize) -> Result<Vec<u8>, MemoError> {
+    use std::io::Read;
+
+    let mut decoder = flate2::read::DeflateDecoder::new(compressed);
+    let mut buf = Vec::new();
+    decoder
+        .by_ref()
+        .take(original_len as u64 + 1)
+        .read_to_end(&mut buf)?;
+
+    if buf.len() != original_len {
+        return Err(MemoError::InvalidCompression(format!(
+            "expected {} inflated bytes, got {}",
+            original_len,
+            buf.len()
+        )));
+    }
+
+    Ok(buf)
+}
+
+/// Encode an acknowledgment memo for the message identified by
+/// `original_timestamp`/`original_nonce` (mirroring how those header fields
+/// already identify a message for dedup/ordering).
+///
+/// `received_fragments` are the indices (into `0..total_fragments`) of
+/// fragments received so far; pass `&[0]` and `total_fragments: 1` to
+/// acknowledge a whole unfragmented message. The indices are packed into a
+/// bitmap so the sender learns exactly which fragments to retransmit
+/// instead of resending the whole message.
+pub fn encode_ack_memo(
+    original_timestamp: u32,
+    original_nonce: u32,
+    received_fragments: &[u16],
+    total_fragments: u16,
+) -> Result<Vec<u8>, MemoError> {
+    let mut enc = Encoder::new();
+    write_header(
+        &mut enc,
+        MemoType::Ack,
+        original_timestamp,
+        original_nonce,
+        None,
+    )?;
+    encode_extensions(&mut enc, &[])?;
+    enc.encode_uint(2, total_fragments as u64)?;
+    encode_fragment_bitmap(&mut enc, total_fragments, received_fragments)?;
+
+    Ok(enc.finish_padded(MEMO_SIZE)?)
+}
+
+/// Pack `received_fragments` into a `ceil(total_fragments / 8)`-byte bitmap,
+/// one bit per fragment index (LSB-first within each byte).
+fn encode_fragment_bitmap(
+    enc: &mut Encoder,
+    total_fragments: u16,
+    received_fragments: &[u16],
+) -> Result<(), MemoError> {
+    let mut bitmap = vec![0u8; (total_fragments as usize).div_ceil(8)];
+    for &index in received_fragments {
+        if index >= total_fragments {
+            return Err(MemoError::InvalidFragmentInfo(format!(
+                "received fragment index {} >= total fragments {}",
+                index, total_fragments
+            )));
+        }
+        bitmap[index as usize / 8] |= 1 << (index % 8);
+    }
+    enc.encode_bytes(&bitmap);
+    Ok(())
+}
+
+/// Decode an ack payload (`total_fragments` followed by its bitmap) into the
+/// list of received fragment indices.
+fn decode_ack_payload(dec: &mut Decoder) -> Result<AckInfo, MemoError> {
+    let total_fragments = dec.decode_uint(2)? as u16;
+    let bitmap = dec.decode_bytes((total_fragments as usize).div_ceil(8))?;
+
+    let received_fragments = (0..total_fragments)
+        .filter(|&index| bitmap[index as usize / 8] & (1 << (index % 8)) != 0)
+        .collect();
+
+    Ok(AckInfo {
+        total_fragments,
+        received_fragments,
+    })
+}
+
+/// A reference to another message, by its timestamp+nonce pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplyTo {
+    /// Timestamp of the message being replied to.
+    pub timestamp: u32,
+    /// Nonce of the message being replied to.
+    pub nonce: u32,
+}
+
+/// Build a reply-to extension record pointing at `reply_to`.
+pub fn encode_reply_to(reply_to: ReplyTo) -> (u64, Vec<u8>) {
+    let mut enc = Encoder::new();
+    enc.encode_uint(4, reply_to.timestamp as u64)
+        .expect("u32 fits in 4 bytes");
+    enc.encode_uint(4, reply_to.nonce as u64)
+        .expect("u32 fits in 4 bytes");
+    (EXTENSION_TYPE_REPLY_TO, enc.finish())
+}
+
+/// Parse a reply-to extension record's value.
+pub fn decode_reply_to(value: &[u8]) -> Result<ReplyTo, MemoError> {
+    let mut dec = Decoder::new(value);
+    let timestamp = dec.decode_uint(4)? as u32;
+    let nonce = dec.decode_uint(4)? as u32;
+    Ok(ReplyTo { timestamp, nonce })
+}
+
+/// Build a content-type extension record from a MIME-style type string.
+pub fn encode_content_type(mime_type: &str) -> (u64, Vec<u8>) {
+    (EXTENSION_TYPE_CONTENT_TYPE, mime_type.as_bytes().to_vec())
+}
+
+/// Parse a content-type extension record's value.
+pub fn decode_content_type(value: &[u8]) -> Result<String, MemoError> {
+    core::str::from_utf8(value)
+        .map(|s| s.to_string())
+        .map_err(|_| MemoError::InvalidUtf8)
 }
 
 /// Decode a message from a memo.
 ///
-/// Parses the memo header and extracts the message content.
+/// First interprets the memo per ZIP 302; only attempts our structured
+/// parsing if the leading byte marks our namespace (0xF5, "arbitrary").
+/// A standards-compliant wallet would stop at `Memo::Arbitrary` and show
+/// "arbitrary memo" - we go one step further and parse the payload.
+///
 /// For fragment messages, returns the fragment - use `reassemble_fragments`
 /// to combine multiple fragments.
 ///
 /// # Arguments
 ///
-/// * `memo` - A 512-byte memo
+/// * `memo` - A memo, up to [`MEMO_SIZE`] bytes (shorter buffers are treated
+///   as null-padded, matching how memos arrive from decrypted notes).
 ///
 /// # Returns
 ///
 /// The decoded message.
 pub fn decode_message_memo(memo: &[u8]) -> Result<Message, MemoError> {
-    if memo.len() < HEADER_SIZE {
+    if memo.len() < 1 + HEADER_SIZE {
         return Err(MemoError::TooShort(memo.len()));
     }
 
-    // Parse header
-    let version = memo[0];
+    let memo_bytes = MemoBytes::from_bytes(memo)?;
+    let parsed = Memo::from_bytes(&memo_bytes)?;
+
+    let inner = match parsed {
+        Memo::Arbitrary(bytes) => bytes,
+        other => {
+            let leading = other.to_bytes().as_slice()[0];
+            return Err(MemoError::NotOurNamespace(leading));
+        }
+    };
+
+    // The ZIP 302 marker byte itself was already consumed by `Memo::from_bytes`
+    // (it's how we know we're in our namespace); the rest of our header starts
+    // at offset 1 of the arbitrary-memo payload.
+    let mut dec = Decoder::new(&inner.as_slice()[1..]);
+
+    let version = dec.decode_byte()?;
     if version != MEMO_VERSION {
         return Err(MemoError::InvalidVersion(version));
     }
 
-    let msg_type = MemoType::from_u8(memo[1])?;
+    let msg_type = MemoType::from_u8(dec.decode_byte()?)?;
 
-    let timestamp = u32::from_be_bytes([memo[2], memo[3], memo[4], memo[5]]);
-    let nonce = u32::from_be_bytes([memo[6], memo[7], memo[8], memo[9]]);
+    let timestamp = dec.decode_uint(4)? as u32;
+    let nonce = dec.decode_uint(4)? as u32;
+    let total_fragments = dec.decode_uint(2)? as u16;
+    let index = dec.decode_uint(2)? as u16;
 
     // Parse fragment info (if applicable)
-    let fragment_info = if msg_type == MemoType::Fragment {
-        let total_fragments = u16::from_be_bytes([memo[10], memo[11]]);
-        let index = u16::from_be_bytes([memo[12], memo[13]]);
-
+    let fragment_info = if msg_type.is_fragment() {
         if total_fragments == 0 {
             return Err(MemoError::InvalidFragmentInfo(
                 "Total fragments cannot be zero".to_string(),
@@ -270,18 +889,45 @@ pub fn decode_message_memo(memo: &[u8]) -> Result<Message, MemoError> {
         None
     };
 
-    // Parse payload
-    let payload = &memo[HEADER_SIZE..];
-
-    // Find the end of the string (first null byte or end of memo)
-    let end = payload
-        .iter()
-        .position(|&b| b == 0)
-        .unwrap_or(payload.len());
-
-    let content = core::str::from_utf8(&payload[..end])
-        .map_err(|_| MemoError::InvalidUtf8)?
-        .to_string();
+    let extensions = decode_extensions(&mut dec)?;
+
+    // Parse payload. A `CompressedFragment`'s payload is an arbitrary slice
+    // of the full DEFLATE stream - it can't be inflated (or even trusted to
+    // be valid UTF-8) until every fragment is known, so it's carried raw for
+    // `reassemble_fragments` to piece back together. An `Ack`'s payload is a
+    // structured fragment bitmap, not text.
+    let (content, raw_payload, ack) = if msg_type == MemoType::Ack {
+        (String::new(), Vec::new(), Some(decode_ack_payload(&mut dec)?))
+    } else if msg_type == MemoType::CompressedFragment {
+        (String::new(), dec.decode_remainder().to_vec(), None)
+    } else {
+        let payload = dec.decode_remainder();
+
+        if msg_type.is_compressed() {
+            // Self-contained: `varint(original_len) ++ deflate_bytes`, with
+            // the DEFLATE stream's own end marker ignoring any trailing
+            // memo padding after it.
+            let mut payload_dec = Decoder::new(payload);
+            let original_len = payload_dec.decode_varint()? as usize;
+            let inflated = inflate(payload_dec.decode_remainder(), original_len)?;
+            (
+                String::from_utf8(inflated).map_err(|_| MemoError::InvalidUtf8)?,
+                Vec::new(),
+                None,
+            )
+        } else {
+            // Find the end of the string (first null byte or end of memo)
+            let end = payload
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(payload.len());
+
+            let content = core::str::from_utf8(&payload[..end])
+                .map_err(|_| MemoError::InvalidUtf8)?
+                .to_string();
+            (content, Vec::new(), None)
+        }
+    };
 
     Ok(Message {
         version,
@@ -289,7 +935,10 @@ pub fn decode_message_memo(memo: &[u8]) -> Result<Message, MemoError> {
         timestamp,
         nonce,
         fragment_info,
+        ack,
+        extensions,
         content,
+        raw_payload,
     })
 }
 
@@ -313,14 +962,21 @@ pub fn reassemble_fragments(fragments: &[Message]) -> Result<String, MemoError>
     // Verify all fragments have the same timestamp and nonce
     let timestamp = fragments[0].timestamp;
     let nonce = fragments[0].nonce;
+    let msg_type = fragments[0].msg_type;
 
     for fragment in fragments {
-        if fragment.msg_type != MemoType::Fragment {
+        if !fragment.msg_type.is_fragment() {
             return Err(MemoError::InvalidFragmentInfo(
                 "Non-fragment message in fragment list".to_string(),
             ));
         }
 
+        if fragment.msg_type != msg_type {
+            return Err(MemoError::InvalidFragmentInfo(
+                "Fragments are a mix of compressed and uncompressed".to_string(),
+            ));
+        }
+
         if fragment.timestamp != timestamp || fragment.nonce != nonce {
             return Err(MemoError::InvalidFragmentInfo(
                 "Fragments have different timestamp or nonce".to_string(),
@@ -355,6 +1011,21 @@ pub fn reassemble_fragments(fragments: &[Message]) -> Result<String, MemoError>
         }
     }
 
+    if msg_type == MemoType::CompressedFragment {
+        // The fragments' raw bytes are an arbitrary chunking of
+        // `varint(original_len) ++ deflate_bytes` - concatenate them back
+        // into one buffer before parsing either piece.
+        let mut raw = Vec::new();
+        for fragment in &sorted_fragments {
+            raw.extend_from_slice(&fragment.raw_payload);
+        }
+
+        let mut dec = Decoder::new(&raw);
+        let original_len = dec.decode_varint()? as usize;
+        let inflated = inflate(dec.decode_remainder(), original_len)?;
+        return String::from_utf8(inflated).map_err(|_| MemoError::InvalidUtf8);
+    }
+
     // Combine content
     let mut combined = String::new();
     for fragment in &sorted_fragments {
@@ -364,10 +1035,103 @@ pub fn reassemble_fragments(fragments: &[Message]) -> Result<String, MemoError>
     Ok(combined)
 }
 
+/// Per-message delivery state tracked by [`ConversationTracker`]: the
+/// fragment count we sent and which of those fragments have been
+/// acknowledged so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SentMessageState {
+    total_fragments: u16,
+    acked_fragments: std::collections::BTreeSet<u16>,
+}
+
+/// Tracks delivery state for messages this wallet has sent, so a caller can
+/// tell which fragments are still unacknowledged and request a resend
+/// instead of resending the whole message.
+///
+/// Call [`ConversationTracker::record_sent`] when a message (fragmented or
+/// not) goes out, then feed every decoded [`Message`] back in through
+/// [`ConversationTracker::ingest`] - acks update delivery state for the
+/// message they reference; anything else is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ConversationTracker {
+    sent: std::collections::HashMap<(u32, u32), SentMessageState>,
+}
+
+impl ConversationTracker {
+    /// An empty tracker with nothing sent yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a message we just sent, identified by its `timestamp`/
+    /// `nonce` pair, so incoming acks can be matched against it.
+    /// `total_fragments` is 1 for an unfragmented message.
+    pub fn record_sent(&mut self, timestamp: u32, nonce: u32, total_fragments: u16) {
+        self.sent.insert(
+            (timestamp, nonce),
+            SentMessageState {
+                total_fragments,
+                acked_fragments: std::collections::BTreeSet::new(),
+            },
+        );
+    }
+
+    /// Ingest a decoded message. If it's an ack for a message we're
+    /// tracking, merge its received-fragment bitmap into that message's
+    /// delivery state; everything else is a no-op.
+    pub fn ingest(&mut self, message: &Message) {
+        let Some(ack) = &message.ack else {
+            return;
+        };
+        if let Some(state) = self.sent.get_mut(&(message.timestamp, message.nonce)) {
+            state.total_fragments = ack.total_fragments;
+            state.acked_fragments.extend(&ack.received_fragments);
+        }
+    }
+
+    /// Fragment indices of `(timestamp, nonce)` that have not yet been
+    /// acknowledged, so the caller can request a resend. Returns `None` if
+    /// the message isn't tracked (never sent via [`Self::record_sent`], or
+    /// already forgotten via [`Self::forget`]).
+    pub fn outstanding_fragments(&self, timestamp: u32, nonce: u32) -> Option<Vec<u16>> {
+        let state = self.sent.get(&(timestamp, nonce))?;
+        Some(
+            (0..state.total_fragments)
+                .filter(|index| !state.acked_fragments.contains(index))
+                .collect(),
+        )
+    }
+
+    /// Whether every fragment of `(timestamp, nonce)` has been acknowledged.
+    /// `false` for an untracked message.
+    pub fn is_fully_acked(&self, timestamp: u32, nonce: u32) -> bool {
+        self.outstanding_fragments(timestamp, nonce)
+            .is_some_and(|outstanding| outstanding.is_empty())
+    }
+
+    /// Stop tracking a message, e.g. once fully acked or abandoned.
+    pub fn forget(&mut self, timestamp: u32, nonce: u32) {
+        self.sent.remove(&(timestamp, nonce));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A pseudo-random ASCII string that DEFLATE can't meaningfully shrink,
+    /// for tests that need text compression won't help with.
+    fn incompressible_text(len: usize) -> String {
+        let mut state: u64 = 0x9E3779B97F4A7C15;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                let byte = (state >> 56) as u8;
+                (0x20 + (byte % 95)) as char
+            })
+            .collect()
+    }
+
     #[test]
     fn test_encode_decode_short_message() {
         let text = "Hello, Zcash!";
@@ -376,6 +1140,7 @@ mod tests {
 
         let memo = encode_message_memo(text, timestamp, nonce).unwrap();
         assert_eq!(memo.len(), MEMO_SIZE);
+        assert_eq!(memo[0], ZIP302_ARBITRARY_MARKER);
 
         let message = decode_message_memo(&memo).unwrap();
         assert_eq!(message.version, MEMO_VERSION);
@@ -400,7 +1165,10 @@ mod tests {
 
     #[test]
     fn test_encode_message_too_long() {
-        let text = "a".repeat(MAX_PAYLOAD_SIZE + 1);
+        // A repeated character would now compress down to a single memo -
+        // use text that DEFLATE can't shrink enough to fit, so this still
+        // exercises the "too long even after compression" path.
+        let text = incompressible_text(MAX_PAYLOAD_SIZE * 3);
         let timestamp = 1672531200;
         let nonce = 12345;
 
@@ -410,18 +1178,21 @@ mod tests {
 
     #[test]
     fn test_encode_decode_fragments() {
-        let text = "a".repeat(MAX_PAYLOAD_SIZE * 2 + 100);
+        let text = incompressible_text(MAX_PAYLOAD_SIZE * 2 + 100);
         let timestamp = 1672531200;
         let nonce = 12345;
 
         let fragments = encode_message_fragments(&text, timestamp, nonce).unwrap();
-        assert_eq!(fragments.len(), 3); // Should create 3 fragments
+        assert!(fragments.len() > 1, "expected multiple fragments");
 
         // Decode all fragments
         let mut messages = Vec::new();
         for fragment in &fragments {
             let message = decode_message_memo(fragment).unwrap();
-            assert_eq!(message.msg_type, MemoType::Fragment);
+            assert!(matches!(
+                message.msg_type,
+                MemoType::Fragment | MemoType::CompressedFragment
+            ));
             assert_eq!(message.timestamp, timestamp);
             assert_eq!(message.nonce, nonce);
             messages.push(message);
@@ -457,7 +1228,8 @@ mod tests {
     #[test]
     fn test_decode_invalid_version() {
         let mut memo = vec![0u8; MEMO_SIZE];
-        memo[0] = 0xFF; // Invalid version
+        memo[0] = ZIP302_ARBITRARY_MARKER;
+        memo[1] = 0xFF; // Invalid version
 
         let result = decode_message_memo(&memo);
         assert!(matches!(result, Err(MemoError::InvalidVersion(0xFF))));
@@ -466,8 +1238,9 @@ mod tests {
     #[test]
     fn test_decode_invalid_type() {
         let mut memo = vec![0u8; MEMO_SIZE];
-        memo[0] = MEMO_VERSION;
-        memo[1] = 0xFF; // Invalid type
+        memo[0] = ZIP302_ARBITRARY_MARKER;
+        memo[1] = MEMO_VERSION;
+        memo[2] = 0xFF; // Invalid type
 
         let result = decode_message_memo(&memo);
         assert!(matches!(result, Err(MemoError::InvalidType(0xFF))));
@@ -490,7 +1263,7 @@ mod tests {
         let memo = encode_message_memo(text, timestamp, nonce).unwrap();
 
         // Verify null padding
-        let payload_start = HEADER_SIZE + text.len();
+        let payload_start = 1 + HEADER_SIZE + MIN_EXTENSIONS_SIZE + text.len();
         assert!(memo[payload_start..].iter().all(|&b| b == 0));
 
         let message = decode_message_memo(&memo).unwrap();
@@ -500,21 +1273,22 @@ mod tests {
     #[test]
     fn test_fragment_info_validation() {
         let mut memo = vec![0u8; MEMO_SIZE];
-        memo[0] = MEMO_VERSION;
-        memo[1] = MemoType::Fragment as u8;
-        memo[2..6].copy_from_slice(&1672531200u32.to_be_bytes());
-        memo[6..10].copy_from_slice(&12345u32.to_be_bytes());
+        memo[0] = ZIP302_ARBITRARY_MARKER;
+        memo[1] = MEMO_VERSION;
+        memo[2] = MemoType::Fragment as u8;
+        memo[3..7].copy_from_slice(&1672531200u32.to_be_bytes());
+        memo[7..11].copy_from_slice(&12345u32.to_be_bytes());
 
         // total_fragments = 0 (invalid)
-        memo[10..12].copy_from_slice(&0u16.to_be_bytes());
-        memo[12..14].copy_from_slice(&0u16.to_be_bytes());
+        memo[11..13].copy_from_slice(&0u16.to_be_bytes());
+        memo[13..15].copy_from_slice(&0u16.to_be_bytes());
 
         let result = decode_message_memo(&memo);
         assert!(matches!(result, Err(MemoError::InvalidFragmentInfo(_))));
 
         // index >= total_fragments (invalid)
-        memo[10..12].copy_from_slice(&2u16.to_be_bytes()); // total = 2
-        memo[12..14].copy_from_slice(&2u16.to_be_bytes()); // index = 2
+        memo[11..13].copy_from_slice(&2u16.to_be_bytes()); // total = 2
+        memo[13..15].copy_from_slice(&2u16.to_be_bytes()); // index = 2
 
         let result = decode_message_memo(&memo);
         assert!(matches!(result, Err(MemoError::InvalidFragmentInfo(_))));
@@ -522,7 +1296,7 @@ mod tests {
 
     #[test]
     fn test_reassemble_missing_fragments() {
-        let text = "a".repeat(MAX_PAYLOAD_SIZE * 3);
+        let text = incompressible_text(MAX_PAYLOAD_SIZE * 3);
         let timestamp = 1672531200;
         let nonce = 12345;
 
@@ -541,7 +1315,7 @@ mod tests {
 
     #[test]
     fn test_unicode_message() {
-        let text = "Hello ‰∏ñÁïå! üåç Zcash";
+        let text = "Hello 世界! 🌍 Zcash";
         let timestamp = 1672531200;
         let nonce = 12345;
 
@@ -550,4 +1324,323 @@ mod tests {
 
         assert_eq!(message.content, text);
     }
+
+    #[test]
+    fn test_zip302_empty_memo() {
+        let memo = MemoBytes::empty();
+        let parsed = Memo::from_bytes(&memo).unwrap();
+        assert_eq!(parsed, Memo::Empty);
+        assert_eq!(parsed.to_bytes(), memo);
+    }
+
+    #[test]
+    fn test_zip302_plain_text_memo_from_other_wallet() {
+        // A memo as written by a standards-compliant (non-namespaced) wallet:
+        // byte[0] <= 0xF4, whole buffer is UTF-8 text.
+        let memo = Memo::Text("gm from another wallet".to_string()).to_bytes();
+        assert!(memo.as_slice()[0] <= 0xF4);
+
+        let parsed = Memo::from_bytes(&memo).unwrap();
+        assert_eq!(parsed, Memo::Text("gm from another wallet".to_string()));
+
+        // Our structured decoder must refuse to treat this as one of our messages.
+        assert!(matches!(
+            decode_message_memo(memo.as_slice()),
+            Err(MemoError::NotOurNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_zip302_future_memo_preserved_verbatim() {
+        let mut raw = [0u8; MEMO_SIZE];
+        raw[0] = 0xFA;
+        raw[1] = 0x42;
+        let memo = MemoBytes::from_array(raw);
+
+        let parsed = Memo::from_bytes(&memo).unwrap();
+        assert_eq!(parsed, Memo::Future(memo.clone()));
+        assert_eq!(parsed.to_bytes(), memo);
+    }
+
+    #[test]
+    fn test_memo_json_round_trip() {
+        let cases = [
+            Memo::Empty,
+            Memo::Text("gm".to_string()),
+            Memo::Arbitrary(MemoBytes::from_array({
+                let mut raw = [0u8; MEMO_SIZE];
+                raw[0] = ZIP302_ARBITRARY_MARKER;
+                raw[1] = 0xAB;
+                raw
+            })),
+            Memo::Future(MemoBytes::from_array({
+                let mut raw = [0u8; MEMO_SIZE];
+                raw[0] = 0xFA;
+                raw[1] = 0x42;
+                raw
+            })),
+        ];
+
+        for memo in cases {
+            let json = serde_json::to_value(&memo).unwrap();
+            let round_tripped: Memo = serde_json::from_value(json).unwrap();
+            assert_eq!(round_tripped, memo);
+        }
+    }
+
+    #[test]
+    fn test_memo_json_is_tagged_by_kind() {
+        assert_eq!(
+            serde_json::to_value(Memo::Empty).unwrap(),
+            serde_json::json!({"type": "empty"}),
+        );
+        assert_eq!(
+            serde_json::to_value(Memo::Text("gm".to_string())).unwrap(),
+            serde_json::json!({"type": "text", "text": "gm"}),
+        );
+    }
+
+    #[test]
+    fn test_our_message_is_zip302_arbitrary() {
+        let memo = encode_message_memo("hi", 1, 1).unwrap();
+        let memo_bytes = MemoBytes::from_bytes(&memo).unwrap();
+        let parsed = Memo::from_bytes(&memo_bytes).unwrap();
+        assert!(matches!(parsed, Memo::Arbitrary(_)));
+    }
+
+    #[test]
+    fn test_roundtrip_reply_to_extension() {
+        let reply = ReplyTo {
+            timestamp: 1672531200,
+            nonce: 999,
+        };
+        let extensions = vec![encode_reply_to(reply)];
+
+        let memo =
+            encode_message_memo_with_extensions("got it", 1672531300, 1000, &extensions).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        assert_eq!(message.content, "got it");
+        assert_eq!(message.extensions.len(), 1);
+        assert_eq!(message.extensions[0].0, EXTENSION_TYPE_REPLY_TO);
+        assert_eq!(decode_reply_to(&message.extensions[0].1).unwrap(), reply);
+    }
+
+    #[test]
+    fn test_roundtrip_content_type_extension() {
+        let extensions = vec![encode_content_type("text/markdown")];
+
+        let memo =
+            encode_message_memo_with_extensions("# hi", 1672531200, 1, &extensions).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        assert_eq!(message.extensions.len(), 1);
+        let (ty, value) = &message.extensions[0];
+        assert_eq!(*ty, EXTENSION_TYPE_CONTENT_TYPE);
+        assert_eq!(decode_content_type(value).unwrap(), "text/markdown");
+    }
+
+    #[test]
+    fn test_extensions_must_be_sorted_with_no_duplicates() {
+        // Out of order.
+        let extensions = vec![
+            encode_content_type("text/plain"),
+            encode_reply_to(ReplyTo {
+                timestamp: 1,
+                nonce: 1,
+            }),
+        ];
+        let result = encode_message_memo_with_extensions("x", 1, 1, &extensions);
+        assert!(matches!(result, Err(MemoError::InvalidExtension(_))));
+
+        // Duplicate type.
+        let extensions = vec![
+            encode_content_type("text/plain"),
+            encode_content_type("text/html"),
+        ];
+        let result = encode_message_memo_with_extensions("x", 1, 1, &extensions);
+        assert!(matches!(result, Err(MemoError::InvalidExtension(_))));
+    }
+
+    #[test]
+    fn test_unknown_odd_extension_is_skipped() {
+        let extensions = vec![(9u64, vec![1, 2, 3])];
+        let memo =
+            encode_message_memo_with_extensions("hello", 1, 1, &extensions).unwrap();
+
+        let message = decode_message_memo(&memo).unwrap();
+        assert_eq!(message.content, "hello");
+        assert!(message.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_even_extension_is_a_hard_error() {
+        let extensions = vec![(8u64, vec![1, 2, 3])];
+        let memo =
+            encode_message_memo_with_extensions("hello", 1, 1, &extensions).unwrap();
+
+        let result = decode_message_memo(&memo);
+        assert!(matches!(result, Err(MemoError::InvalidExtension(_))));
+    }
+
+    #[test]
+    fn test_highly_compressible_single_memo_uses_compressed_text() {
+        // Repeats well past MAX_PAYLOAD_SIZE - only fits because compression
+        // kicks in.
+        let text = "Zcash ".repeat(200);
+        assert!(text.len() > MAX_PAYLOAD_SIZE);
+
+        let memo = encode_message_memo(&text, 1672531200, 1).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        assert_eq!(message.msg_type, MemoType::CompressedText);
+        assert_eq!(message.content, text);
+    }
+
+    #[test]
+    fn test_incompressible_short_message_stays_uncompressed() {
+        let text = incompressible_text(20);
+        let memo = encode_message_memo(&text, 1672531200, 1).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        // DEFLATE overhead means a short, random string never wins.
+        assert_eq!(message.msg_type, MemoType::Text);
+        assert_eq!(message.content, text);
+    }
+
+    #[test]
+    fn test_compressible_long_message_fragments_as_compressed_fragment() {
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(200);
+        assert!(text.len() > MAX_PAYLOAD_SIZE);
+
+        let fragments = encode_message_fragments(&text, 1672531200, 12345).unwrap();
+
+        let messages: Vec<_> = fragments
+            .iter()
+            .map(|f| decode_message_memo(f).unwrap())
+            .collect();
+        assert!(
+            messages
+                .iter()
+                .all(|m| m.msg_type == MemoType::CompressedFragment)
+        );
+
+        let reassembled = reassemble_fragments(&messages).unwrap();
+        assert_eq!(reassembled, text);
+    }
+
+    #[test]
+    fn test_compressed_fragments_reassemble_out_of_order() {
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(200);
+        let fragments = encode_message_fragments(&text, 1672531200, 12345).unwrap();
+
+        let mut messages: Vec<_> = fragments
+            .iter()
+            .map(|f| decode_message_memo(f).unwrap())
+            .collect();
+        messages.reverse();
+
+        let reassembled = reassemble_fragments(&messages).unwrap();
+        assert_eq!(reassembled, text);
+    }
+
+    #[test]
+    fn test_decode_compressed_text_rejects_length_mismatch() {
+        // Repetitive enough to compress, but short enough that the stored
+        // original length is a single-byte varint we can corrupt directly.
+        let text = "ab".repeat(100);
+        assert!(text.len() <= 252);
+
+        let mut memo = encode_message_memo(&text, 1672531200, 1).unwrap();
+        assert_eq!(memo[2], MemoType::CompressedText as u8);
+
+        // The varint original-length byte immediately follows the fixed
+        // header and the single-byte (empty) extensions block.
+        let len_offset = 1 + HEADER_SIZE + MIN_EXTENSIONS_SIZE;
+        memo[len_offset] = memo[len_offset].wrapping_add(50);
+
+        let result = decode_message_memo(&memo);
+        assert!(matches!(result, Err(MemoError::InvalidCompression(_))));
+    }
+
+    #[test]
+    fn test_roundtrip_ack_memo() {
+        let memo = encode_ack_memo(1672531200, 12345, &[0, 2, 3], 4).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        assert_eq!(message.msg_type, MemoType::Ack);
+        assert_eq!(message.timestamp, 1672531200);
+        assert_eq!(message.nonce, 12345);
+
+        let ack = message.ack.unwrap();
+        assert_eq!(ack.total_fragments, 4);
+        assert_eq!(ack.received_fragments, vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_ack_memo_rejects_out_of_range_fragment_index() {
+        let result = encode_ack_memo(1, 1, &[5], 4);
+        assert!(matches!(result, Err(MemoError::InvalidFragmentInfo(_))));
+    }
+
+    #[test]
+    fn test_ack_memo_many_fragments_spans_multiple_bitmap_bytes() {
+        let received: Vec<u16> = (0..20).step_by(2).collect();
+        let memo = encode_ack_memo(1, 1, &received, 20).unwrap();
+        let message = decode_message_memo(&memo).unwrap();
+
+        let ack = message.ack.unwrap();
+        assert_eq!(ack.total_fragments, 20);
+        assert_eq!(ack.received_fragments, received);
+    }
+
+    #[test]
+    fn test_conversation_tracker_tracks_outstanding_fragments() {
+        let mut tracker = ConversationTracker::new();
+        tracker.record_sent(1672531200, 12345, 3);
+
+        assert_eq!(
+            tracker.outstanding_fragments(1672531200, 12345),
+            Some(vec![0, 1, 2])
+        );
+        assert!(!tracker.is_fully_acked(1672531200, 12345));
+
+        let ack_memo = encode_ack_memo(1672531200, 12345, &[0, 2], 3).unwrap();
+        let ack_message = decode_message_memo(&ack_memo).unwrap();
+        tracker.ingest(&ack_message);
+
+        assert_eq!(
+            tracker.outstanding_fragments(1672531200, 12345),
+            Some(vec![1])
+        );
+        assert!(!tracker.is_fully_acked(1672531200, 12345));
+
+        let final_ack = encode_ack_memo(1672531200, 12345, &[1], 3).unwrap();
+        tracker.ingest(&decode_message_memo(&final_ack).unwrap());
+
+        assert_eq!(
+            tracker.outstanding_fragments(1672531200, 12345),
+            Some(vec![])
+        );
+        assert!(tracker.is_fully_acked(1672531200, 12345));
+    }
+
+    #[test]
+    fn test_conversation_tracker_ignores_ack_for_untracked_message() {
+        let mut tracker = ConversationTracker::new();
+        let ack_memo = encode_ack_memo(1, 1, &[0], 1).unwrap();
+        tracker.ingest(&decode_message_memo(&ack_memo).unwrap());
+
+        assert_eq!(tracker.outstanding_fragments(1, 1), None);
+        assert!(!tracker.is_fully_acked(1, 1));
+    }
+
+    #[test]
+    fn test_conversation_tracker_forget() {
+        let mut tracker = ConversationTracker::new();
+        tracker.record_sent(1, 1, 1);
+        tracker.forget(1, 1);
+
+        assert_eq!(tracker.outstanding_fragments(1, 1), None);
+    }
 }
@@ -0,0 +1,313 @@
+//! Transaction proposals for split-device (watch-only / air-gapped) signing.
+//!
+//! A [`Proposal`] is an intended transfer - a list of [`Payment`]s plus the
+//! previously-scanned notes/outputs selected to fund them - in a form that
+//! can cross a process boundary. The in-browser watch-only wallet has the
+//! viewing key and the scan history needed to build one, but not the spend
+//! authority to sign it; an air-gapped device has the spend key but no
+//! visibility into the chain. [`Proposal::to_bytes`]/[`Proposal::from_bytes`]
+//! give the two sides a stable wire format to hand a proposal across that
+//! boundary (e.g. a QR code or a file on a USB stick), versioned so a future
+//! format change doesn't silently misparse an older proposal.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::codec::{CodecError, Decoder, Encoder};
+use crate::memo::{MEMO_SIZE, Memo, MemoBytes, MemoError};
+use crate::types::Pool;
+
+/// Version tag for the current [`Proposal`] binary format.
+pub const PROPOSAL_SER_V1: u8 = 1;
+
+/// Errors that can occur serializing or parsing a [`Proposal`].
+#[derive(Error, Debug)]
+pub enum ProposalError {
+    /// The leading version byte isn't one this build knows how to parse.
+    #[error("Unsupported proposal format version: {0}")]
+    UnknownVersion(u8),
+
+    /// An input's txid field isn't a 32-byte hex string.
+    #[error("Malformed txid: {0}")]
+    InvalidTxid(String),
+
+    /// An input's pool tag isn't one of the three known pools.
+    #[error("Invalid pool tag: {0}")]
+    InvalidPoolTag(u8),
+
+    /// The buffer ran out, or a varint/field was malformed, mid-parse.
+    #[error("Malformed proposal bytes: {0}")]
+    Codec(#[from] CodecError),
+
+    /// A payment's memo bytes don't decode to a valid memo.
+    #[error("Invalid memo: {0}")]
+    Memo(#[from] MemoError),
+}
+
+/// A single payment a [`Proposal`] intends to make.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    /// The recipient's address, in its original encoded form.
+    pub recipient_address: String,
+    /// The amount to send, in zatoshis.
+    pub value: u64,
+    /// An optional memo, for a shielded recipient.
+    pub memo: Option<Memo>,
+}
+
+/// A reference to a previously-scanned note or transparent output, selected
+/// as one of a [`Proposal`]'s inputs.
+///
+/// Identifies the note/output the same way [`crate::types::TransparentSpend`]
+/// identifies a spent transparent output: by the txid of the transaction
+/// that created it and its index within that transaction. The pool
+/// disambiguates which of that transaction's `ScannedNote`s is meant, since
+/// Sapling, Orchard, and transparent outputs are all indexed independently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalInput {
+    /// Which pool this input belongs to.
+    pub pool: Pool,
+    /// Txid of the transaction that created the note/output, as a hex string.
+    pub txid: String,
+    /// Index of the note/output within that transaction.
+    pub output_index: u32,
+}
+
+/// An intended transfer: payments to make, funded by selected inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    /// The payments to make.
+    pub payments: Vec<Payment>,
+    /// The notes/outputs selected to fund them.
+    pub inputs: Vec<ProposalInput>,
+}
+
+fn pool_tag(pool: Pool) -> u8 {
+    match pool {
+        Pool::Transparent => 0,
+        Pool::Sapling => 1,
+        Pool::Orchard => 2,
+    }
+}
+
+fn pool_from_tag(tag: u8) -> Result<Pool, ProposalError> {
+    match tag {
+        0 => Ok(Pool::Transparent),
+        1 => Ok(Pool::Sapling),
+        2 => Ok(Pool::Orchard),
+        _ => Err(ProposalError::InvalidPoolTag(tag)),
+    }
+}
+
+/// A 32-byte txid, hex-decoded and length-checked.
+fn decode_txid(txid: &str) -> Result<(), ProposalError> {
+    match hex::decode(txid) {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => Err(ProposalError::InvalidTxid(txid.to_string())),
+    }
+}
+
+impl Proposal {
+    /// Build a proposal from its payments and selected inputs.
+    pub fn from_parts(payments: Vec<Payment>, inputs: Vec<ProposalInput>) -> Self {
+        Self { payments, inputs }
+    }
+
+    /// Total value of all payments, in zatoshis.
+    pub fn total(&self) -> u64 {
+        self.payments.iter().map(|payment| payment.value).sum()
+    }
+
+    /// Serialize to the versioned `PROPOSAL_SER_V1` binary format:
+    ///
+    /// ```text
+    /// version: u8
+    /// payments: varint count, then for each:
+    ///     recipient_address: varint length, then UTF-8 bytes
+    ///     value: u64 (8 bytes, big-endian)
+    ///     memo: 1 byte (0 = absent, 1 = present), then 512 memo bytes if present
+    /// inputs: varint count, then for each:
+    ///     pool: u8 tag
+    ///     txid: varint length, then hex-string UTF-8 bytes
+    ///     output_index: u32 (4 bytes, big-endian)
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut enc = Encoder::new();
+        enc.encode_byte(PROPOSAL_SER_V1);
+
+        enc.encode_varint(self.payments.len() as u64);
+        for payment in &self.payments {
+            let address_bytes = payment.recipient_address.as_bytes();
+            enc.encode_varint(address_bytes.len() as u64)
+                .encode_bytes(address_bytes);
+            enc.encode_uint(8, payment.value)
+                .expect("a u64 value always fits in 8 bytes");
+            match &payment.memo {
+                Some(memo) => {
+                    enc.encode_byte(1).encode_bytes(memo.to_bytes().as_array());
+                }
+                None => {
+                    enc.encode_byte(0);
+                }
+            }
+        }
+
+        enc.encode_varint(self.inputs.len() as u64);
+        for input in &self.inputs {
+            let txid_bytes = input.txid.as_bytes();
+            enc.encode_byte(pool_tag(input.pool));
+            enc.encode_varint(txid_bytes.len() as u64)
+                .encode_bytes(txid_bytes);
+            enc.encode_uint(4, input.output_index as u64)
+                .expect("a u32 output index always fits in 4 bytes");
+        }
+
+        enc.finish()
+    }
+
+    /// Parse a proposal previously serialized with [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProposalError::UnknownVersion`] if the leading version byte
+    /// isn't [`PROPOSAL_SER_V1`], [`ProposalError::InvalidTxid`] if an
+    /// input's txid isn't a 32-byte hex string, and [`ProposalError::Codec`]
+    /// if the buffer is truncated or otherwise malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProposalError> {
+        let mut dec = Decoder::new(bytes);
+
+        let version = dec.decode_byte()?;
+        if version != PROPOSAL_SER_V1 {
+            return Err(ProposalError::UnknownVersion(version));
+        }
+
+        let payment_count = dec.decode_varint()?;
+        let mut payments = Vec::with_capacity(payment_count as usize);
+        for _ in 0..payment_count {
+            let address_len = dec.decode_varint()? as usize;
+            let recipient_address =
+                String::from_utf8_lossy(dec.decode_bytes(address_len)?).into_owned();
+            let value = dec.decode_uint(8)?;
+            let memo = match dec.decode_byte()? {
+                1 => {
+                    let mut array = [0u8; MEMO_SIZE];
+                    array.copy_from_slice(dec.decode_bytes(MEMO_SIZE)?);
+                    Some(Memo::from_bytes(&MemoBytes::from_array(array))?)
+                }
+                _ => None,
+            };
+            payments.push(Payment {
+                recipient_address,
+                value,
+                memo,
+            });
+        }
+
+        let input_count = dec.decode_varint()?;
+        let mut inputs = Vec::with_capacity(input_count as usize);
+        for _ in 0..input_count {
+            let pool = pool_from_tag(dec.decode_byte()?)?;
+            let txid_len = dec.decode_varint()? as usize;
+            let txid = String::from_utf8_lossy(dec.decode_bytes(txid_len)?).into_owned();
+            decode_txid(&txid)?;
+            let output_index = dec.decode_uint(4)? as u32;
+            inputs.push(ProposalInput {
+                pool,
+                txid,
+                output_index,
+            });
+        }
+
+        Ok(Self { payments, inputs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_proposal() -> Proposal {
+        Proposal::from_parts(
+            vec![
+                Payment {
+                    recipient_address: "u1exampleunifiedaddress".to_string(),
+                    value: 50_000,
+                    memo: Some(Memo::Text("thanks!".to_string())),
+                },
+                Payment {
+                    recipient_address: "t1exampletransparentaddress".to_string(),
+                    value: 25_000,
+                    memo: None,
+                },
+            ],
+            vec![
+                ProposalInput {
+                    pool: Pool::Orchard,
+                    txid: "ab".repeat(32),
+                    output_index: 0,
+                },
+                ProposalInput {
+                    pool: Pool::Transparent,
+                    txid: "cd".repeat(32),
+                    output_index: 3,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_total_sums_payment_values() {
+        assert_eq!(sample_proposal().total(), 75_000);
+    }
+
+    #[test]
+    fn test_round_trip_through_bytes() {
+        let proposal = sample_proposal();
+        let bytes = proposal.to_bytes();
+        let decoded = Proposal::from_bytes(&bytes).expect("round trip should succeed");
+
+        assert_eq!(decoded.payments.len(), 2);
+        assert_eq!(decoded.payments[0].recipient_address, "u1exampleunifiedaddress");
+        assert_eq!(decoded.payments[0].value, 50_000);
+        assert!(matches!(decoded.payments[0].memo, Some(Memo::Text(ref t)) if t == "thanks!"));
+        assert!(decoded.payments[1].memo.is_none());
+
+        assert_eq!(decoded.inputs.len(), 2);
+        assert_eq!(decoded.inputs[0].pool, Pool::Orchard);
+        assert_eq!(decoded.inputs[1].output_index, 3);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_unknown_version() {
+        let mut bytes = sample_proposal().to_bytes();
+        bytes[0] = 0xFF;
+
+        let err = Proposal::from_bytes(&bytes).unwrap_err();
+        assert!(matches!(err, ProposalError::UnknownVersion(0xFF)));
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_malformed_txid() {
+        let mut enc = Encoder::new();
+        enc.encode_byte(PROPOSAL_SER_V1);
+        enc.encode_varint(0); // no payments
+        enc.encode_varint(1); // one input
+        enc.encode_byte(pool_tag(Pool::Sapling));
+        let bad_txid = b"not-hex";
+        enc.encode_varint(bad_txid.len() as u64);
+        enc.encode_bytes(bad_txid);
+        enc.encode_uint(4, 0).unwrap();
+
+        let err = Proposal::from_bytes(&enc.finish()).unwrap_err();
+        assert!(matches!(err, ProposalError::InvalidTxid(_)));
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_truncated_buffer() {
+        let mut bytes = sample_proposal().to_bytes();
+        bytes.truncate(3);
+
+        let err = Proposal::from_bytes(&bytes).unwrap_err();
+        assert!(matches!(err, ProposalError::Codec(_)));
+    }
+}
@@ -5,16 +5,79 @@
 //! to the wallet and extracts nullifiers to track spent notes.
 
 use orchard::keys::{FullViewingKey as OrchardFvk, PreparedIncomingViewingKey, Scope};
-use orchard::note_encryption::OrchardDomain;
+use orchard::note_encryption::{CompactAction, OrchardDomain};
+use sapling_crypto::note_encryption::{
+    PreparedIncomingViewingKey as SaplingPreparedIvk, SaplingDomain,
+};
+use sapling_crypto::zip32::DiversifiableFullViewingKey as SaplingFvk;
 use thiserror::Error;
 use zcash_address::unified::{self, Container, Encoding};
-use zcash_note_encryption::try_note_decryption;
-use zcash_primitives::transaction::Transaction;
-use zcash_protocol::consensus::{BranchId, Network};
+use zcash_client_backend::proto::compact_formats::{CompactBlock, CompactTx};
+use zcash_keys::address::UnifiedAddress;
+use zcash_keys::encoding::AddressCodec;
+use zcash_note_encryption::{
+    batch, try_compact_note_decryption, try_note_decryption, try_output_recovery_with_ovk,
+    BatchDomain, Domain, ShieldedOutput,
+};
+use zcash_primitives::transaction::components::sapling::zip212_enforcement;
+use zcash_primitives::transaction::{Transaction, TxId};
+use zcash_protocol::consensus::{BlockHeight, BranchId, Network};
+use zcash_script::script;
+use zcash_transparent::address::TransparentAddress;
 
+use crate::memo::{Memo, MemoBytes};
 use crate::types::{
-    Pool, ScanResult, ScannedNote, ScannedTransparentOutput, SpentNullifier, TransparentSpend,
+    NetworkKind, Pool, ScanResult, ScannedNote, ScannedTransparentOutput, SpentNullifier,
+    TransferType, TransparentSpend, TxInspection, ViewingKeyInfo,
 };
+use crate::zip321::{self, Payment};
+
+/// Classify a decrypted Sapling or Orchard memo per ZIP 302.
+///
+/// Bytes that don't round-trip as valid ZIP 302 (e.g. a leading byte in the
+/// text range whose content isn't valid UTF-8) are preserved as `Arbitrary`
+/// rather than discarded, so no note data is lost.
+fn memo_from_bytes(memo_bytes: &[u8; 512]) -> Memo {
+    let raw = MemoBytes::from_array(*memo_bytes);
+    Memo::from_bytes(&raw).unwrap_or(Memo::Arbitrary(raw))
+}
+
+/// If a memo is a ZIP 321 `zcash:` payment request URI, parse its first
+/// payment. This lets the wallet recognize a reply-to or payment-request
+/// memo instead of just displaying the raw URI text.
+fn parse_memo_payment_request(memo: &Memo) -> Option<Payment> {
+    let Memo::Text(text) = memo else {
+        return None;
+    };
+    if !text.starts_with("zcash:") {
+        return None;
+    }
+    zip321::parse_payment_uri(text)
+        .ok()
+        .and_then(|payments| payments.into_iter().next())
+}
+
+/// Encode a decrypted Sapling receiver as a Unified Address string.
+///
+/// Wrapping even a single shielded receiver in a Unified Address (rather
+/// than the legacy Sapling address format) matches how the rest of the
+/// wallet represents addresses, and is unambiguous about which network the
+/// receiver belongs to.
+fn encode_sapling_address(network: Network, addr: sapling_crypto::PaymentAddress) -> Option<String> {
+    UnifiedAddress::from_receivers(None, Some(addr), None).map(|ua| ua.encode(&network))
+}
+
+/// Encode a decrypted Orchard receiver as a Unified Address string.
+fn encode_orchard_address(network: Network, addr: orchard::Address) -> Option<String> {
+    UnifiedAddress::from_receivers(Some(addr), None, None).map(|ua| ua.encode(&network))
+}
+
+/// Decode a transparent output's recipient address from its scriptPubKey,
+/// for the common P2PKH/P2SH cases.
+fn decode_transparent_address(network: Network, script_pubkey: &[u8]) -> Option<String> {
+    let parsed = script::PubKey::parse(&script::Code(script_pubkey.to_vec())).ok()?;
+    TransparentAddress::from_script_pubkey(&parsed).map(|addr| addr.encode(&network))
+}
 
 /// Errors that can occur during scanning operations.
 #[derive(Error, Debug)]
@@ -27,22 +90,17 @@ pub enum ScannerError {
 
     #[error("Unrecognized viewing key format")]
     UnrecognizedViewingKey,
+
+    #[error("heights, sapling_start_positions, and orchard_start_positions must each have the same length as txs")]
+    MismatchedBatchLengths,
 }
 
-/// Parse a transaction from hex bytes.
+/// Parse a transaction from hex bytes, along with the branch ID that
+/// successfully deserialized it.
 ///
 /// Attempts parsing with multiple branch IDs (Nu6, Nu5, Canopy, Heartwood)
 /// to support transactions from different network upgrades.
-///
-/// # Arguments
-///
-/// * `tx_hex` - The raw transaction as a hexadecimal string
-/// * `_network` - The network (currently unused but included for future use)
-///
-/// # Returns
-///
-/// The parsed `Transaction` or an error if parsing fails.
-pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction, ScannerError> {
+fn parse_transaction_with_branch_id(tx_hex: &str) -> Result<(Transaction, BranchId), ScannerError> {
     let tx_bytes = hex::decode(tx_hex.trim())
         .map_err(|e| ScannerError::InvalidTransactionHex(e.to_string()))?;
 
@@ -56,7 +114,7 @@ pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction,
 
     for branch_id in branch_ids {
         if let Ok(tx) = Transaction::read(&tx_bytes[..], branch_id) {
-            return Ok(tx);
+            return Ok((tx, branch_id));
         }
     }
 
@@ -65,6 +123,40 @@ pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction,
     ))
 }
 
+/// Parse a transaction from hex bytes.
+///
+/// Attempts parsing with multiple branch IDs (Nu6, Nu5, Canopy, Heartwood)
+/// to support transactions from different network upgrades.
+///
+/// # Arguments
+///
+/// * `tx_hex` - The raw transaction as a hexadecimal string
+/// * `_network` - The network (currently unused but included for future use)
+///
+/// # Returns
+///
+/// The parsed `Transaction` or an error if parsing fails.
+pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction, ScannerError> {
+    parse_transaction_with_branch_id(tx_hex).map(|(tx, _)| tx)
+}
+
+/// Name of a consensus branch ID, for display in diagnostic output.
+fn branch_id_name(branch_id: BranchId) -> &'static str {
+    match branch_id {
+        BranchId::Sprout => "sprout",
+        BranchId::Overwinter => "overwinter",
+        BranchId::Sapling => "sapling",
+        BranchId::Blossom => "blossom",
+        BranchId::Heartwood => "heartwood",
+        BranchId::Canopy => "canopy",
+        BranchId::Nu5 => "nu5",
+        BranchId::Nu6 => "nu6",
+        // Later branch IDs aren't tried by `parse_transaction_with_branch_id`
+        // yet, but are matched here so this stays exhaustive as the enum grows.
+        _ => "unknown",
+    }
+}
+
 /// Extract nullifiers from a transaction.
 ///
 /// Nullifiers indicate which notes have been spent. By tracking nullifiers
@@ -104,6 +196,195 @@ pub fn extract_nullifiers(tx: &Transaction) -> Vec<SpentNullifier> {
     nullifiers
 }
 
+/// Inspect a transaction's bundle structure without any viewing key.
+///
+/// Reuses the same branch-ID detection as [`parse_transaction`], then reports
+/// what's publicly visible in the transaction - pool counts, value balances,
+/// nullifiers, and commitments - without attempting trial decryption. This
+/// gives a diagnostic view of an arbitrary raw transaction for debugging and
+/// auditing, independent of whether the caller holds a viewing key for it.
+///
+/// # Arguments
+///
+/// * `tx_hex` - The raw transaction as a hexadecimal string
+/// * `_network` - The network (currently unused but included for symmetry
+///   with `scan_transaction`/`parse_transaction`)
+///
+/// # Returns
+///
+/// A `TxInspection` describing the transaction's structure, or an error if
+/// the transaction couldn't be parsed.
+pub fn inspect_transaction(tx_hex: &str, _network: Network) -> Result<TxInspection, ScannerError> {
+    let (tx, branch_id) = parse_transaction_with_branch_id(tx_hex)?;
+
+    let (transparent_input_count, transparent_output_count, transparent_output_total) = tx
+        .transparent_bundle()
+        .map(|bundle| {
+            let output_total = bundle.vout.iter().map(|out| u64::from(out.value())).sum();
+            (bundle.vin.len(), bundle.vout.len(), output_total)
+        })
+        .unwrap_or((0, 0, 0));
+
+    let (sapling_spend_count, sapling_output_count, sapling_value_balance, sapling_commitments) =
+        tx.sapling_bundle()
+            .map(|bundle| {
+                let commitments = bundle
+                    .shielded_outputs()
+                    .iter()
+                    .map(|output| hex::encode(output.cmu().to_bytes()))
+                    .collect();
+                (
+                    bundle.shielded_spends().len(),
+                    bundle.shielded_outputs().len(),
+                    i64::from(*bundle.value_balance()),
+                    commitments,
+                )
+            })
+            .unwrap_or_default();
+
+    let (orchard_action_count, orchard_value_balance, orchard_commitments) = tx
+        .orchard_bundle()
+        .map(|bundle| {
+            let commitments = bundle
+                .actions()
+                .iter()
+                .map(|action| hex::encode(action.cmx().to_bytes()))
+                .collect();
+            (
+                bundle.actions().len(),
+                i64::from(*bundle.value_balance()),
+                commitments,
+            )
+        })
+        .unwrap_or_default();
+
+    Ok(TxInspection {
+        txid: tx.txid().to_string(),
+        branch_id: branch_id_name(branch_id).to_string(),
+        expiry_height: tx.expiry_height().into(),
+        transparent_input_count,
+        transparent_output_count,
+        transparent_output_total,
+        sapling_spend_count,
+        sapling_output_count,
+        sapling_value_balance,
+        orchard_action_count,
+        orchard_value_balance,
+        spent_nullifiers: extract_nullifiers(&tx),
+        sapling_commitments,
+        orchard_commitments,
+    })
+}
+
+/// The prepared incoming viewing keys for a single shielded pool, covering
+/// both the external (incoming payments) and internal (change/self-send)
+/// scopes.
+///
+/// Bundling both scopes here means the "try external, then fall back to
+/// internal" trial decryption order - and the [`TransferType`] it implies -
+/// lives in one place instead of being repeated per pool.
+struct ScanningKeys<Ivk> {
+    external: Option<Ivk>,
+    internal: Option<Ivk>,
+}
+
+/// A note decrypted by [`ScanningKeys::try_decrypt`], together with its
+/// recipient, memo, and which key scope matched.
+type DecryptedScopedNote<D> = (
+    <D as Domain>::Note,
+    <D as Domain>::Recipient,
+    <D as Domain>::Memo,
+    TransferType,
+);
+
+impl<Ivk> ScanningKeys<Ivk> {
+    fn new(external: Option<Ivk>, internal: Option<Ivk>) -> Self {
+        Self { external, internal }
+    }
+
+    /// Try trial decryption of a full (non-compact) output against the
+    /// external key, then the internal key, returning the decrypted note,
+    /// recipient, and memo along with which scope matched.
+    fn try_decrypt<D, Output>(&self, domain: &D, output: &Output) -> Option<DecryptedScopedNote<D>>
+    where
+        D: Domain<IncomingViewingKey = Ivk>,
+        Output: ShieldedOutput<D, { zcash_note_encryption::ENC_CIPHERTEXT_SIZE }>,
+    {
+        if let Some((note, recipient, memo)) = self
+            .external
+            .as_ref()
+            .and_then(|ivk| try_note_decryption(domain, ivk, output))
+        {
+            return Some((note, recipient, memo, TransferType::Incoming));
+        }
+        self.internal
+            .as_ref()
+            .and_then(|ivk| try_note_decryption(domain, ivk, output))
+            .map(|(note, recipient, memo)| (note, recipient, memo, TransferType::WalletInternal))
+    }
+
+    /// Try trial decryption of a compact output against the external key,
+    /// then the internal key. Compact outputs don't carry a memo.
+    fn try_decrypt_compact<D, Output>(
+        &self,
+        domain: &D,
+        output: &Output,
+    ) -> Option<(D::Note, D::Recipient, TransferType)>
+    where
+        D: Domain<IncomingViewingKey = Ivk>,
+        Output: ShieldedOutput<D, { zcash_note_encryption::COMPACT_NOTE_SIZE }>,
+    {
+        if let Some((note, recipient)) = self
+            .external
+            .as_ref()
+            .and_then(|ivk| try_compact_note_decryption(domain, ivk, output))
+        {
+            return Some((note, recipient, TransferType::Incoming));
+        }
+        self.internal
+            .as_ref()
+            .and_then(|ivk| try_compact_note_decryption(domain, ivk, output))
+            .map(|(note, recipient)| (note, recipient, TransferType::WalletInternal))
+    }
+}
+
+impl<Ivk: Clone> ScanningKeys<Ivk> {
+    /// Batch trial-decrypt a set of outputs - each paired with its own
+    /// domain, since e.g. Sapling outputs from different transactions may
+    /// need different ZIP 212 enforcement - against the external key, then
+    /// the internal key.
+    ///
+    /// Unlike [`Self::try_decrypt`], this amortizes the expensive
+    /// ephemeral-key preparation and KDF across every output at once instead
+    /// of repeating it per output, which is the main cost of scanning many
+    /// transactions.
+    fn try_decrypt_batch<D, Output>(&self, items: &[(D, Output)]) -> Vec<Option<DecryptedScopedNote<D>>>
+    where
+        D: BatchDomain<IncomingViewingKey = Ivk>,
+        Output: ShieldedOutput<D, { zcash_note_encryption::ENC_CIPHERTEXT_SIZE }>,
+    {
+        let mut ivks = Vec::new();
+        let mut scopes = Vec::new();
+        if let Some(ivk) = &self.external {
+            ivks.push(ivk.clone());
+            scopes.push(TransferType::Incoming);
+        }
+        if let Some(ivk) = &self.internal {
+            ivks.push(ivk.clone());
+            scopes.push(TransferType::WalletInternal);
+        }
+
+        batch::try_note_decryption(&ivks, items)
+            .into_iter()
+            .map(|result| {
+                result.map(|((note, recipient, memo), ivk_index)| {
+                    (note, recipient, memo, scopes[ivk_index])
+                })
+            })
+            .collect()
+    }
+}
+
 /// Extract the Orchard full viewing key from a UFVK string.
 fn extract_orchard_fvk(viewing_key: &str) -> Option<OrchardFvk> {
     if let Ok((_, ufvk)) = unified::Ufvk::decode(viewing_key) {
@@ -118,6 +399,20 @@ fn extract_orchard_fvk(viewing_key: &str) -> Option<OrchardFvk> {
     None
 }
 
+/// Extract the Sapling full viewing key from a UFVK string.
+fn extract_sapling_fvk(viewing_key: &str) -> Option<SaplingFvk> {
+    if let Ok((_, ufvk)) = unified::Ufvk::decode(viewing_key) {
+        for item in ufvk.items() {
+            if let unified::Fvk::Sapling(sapling_bytes) = item
+                && let Some(fvk) = SaplingFvk::from_bytes(&sapling_bytes)
+            {
+                return Some(fvk);
+            }
+        }
+    }
+    None
+}
+
 /// Parse a viewing key and determine its capabilities.
 ///
 /// # Returns
@@ -171,6 +466,76 @@ pub fn parse_viewing_key_capabilities(
     Err(ScannerError::UnrecognizedViewingKey)
 }
 
+/// Parse a viewing key string and report what it is.
+///
+/// Unlike [`parse_viewing_key_capabilities`], this also reports the key's
+/// own type label and encoded network, so a caller can display a viewing
+/// key the same way [`crate::address::parse_address`] displays a parsed
+/// address.
+pub fn parse_viewing_key(viewing_key: &str) -> ViewingKeyInfo {
+    let viewing_key = viewing_key.trim();
+
+    if let Ok((network, ufvk)) = unified::Ufvk::decode(viewing_key) {
+        let items = ufvk.items();
+        let has_sapling = items.iter().any(|item| matches!(item, unified::Fvk::Sapling(_)));
+        let has_orchard = items.iter().any(|item| matches!(item, unified::Fvk::Orchard(_)));
+
+        return ViewingKeyInfo {
+            valid: true,
+            key_type: "UFVK".to_string(),
+            has_sapling,
+            has_orchard,
+            network: Some(NetworkKind::from(network)),
+            error: None,
+        };
+    }
+
+    if let Ok((network, uivk)) = unified::Uivk::decode(viewing_key) {
+        let items = uivk.items();
+        let has_sapling = items.iter().any(|item| matches!(item, unified::Ivk::Sapling(_)));
+        let has_orchard = items.iter().any(|item| matches!(item, unified::Ivk::Orchard(_)));
+
+        return ViewingKeyInfo {
+            valid: true,
+            key_type: "UIVK".to_string(),
+            has_sapling,
+            has_orchard,
+            network: Some(NetworkKind::from(network)),
+            error: None,
+        };
+    }
+
+    // Legacy Sapling extended viewing key: "zxviews..." (mainnet) or
+    // "zxviewtestsapling..." (testnet).
+    if viewing_key.starts_with("zxviews") || viewing_key.starts_with("zxviewtestsapling") {
+        let network = if viewing_key.starts_with("zxviews") {
+            NetworkKind::Mainnet
+        } else {
+            NetworkKind::Testnet
+        };
+
+        if bech32::decode(viewing_key).is_ok() {
+            return ViewingKeyInfo {
+                valid: true,
+                key_type: "Sapling ExtFVK".to_string(),
+                has_sapling: true,
+                has_orchard: false,
+                network: Some(network),
+                error: None,
+            };
+        }
+    }
+
+    ViewingKeyInfo {
+        valid: false,
+        key_type: String::new(),
+        has_sapling: false,
+        has_orchard: false,
+        network: None,
+        error: Some("Unrecognized viewing key format".to_string()),
+    }
+}
+
 /// Scan a transaction for notes belonging to a viewing key.
 ///
 /// Performs trial decryption on all shielded outputs to find notes
@@ -181,8 +546,18 @@ pub fn parse_viewing_key_capabilities(
 ///
 /// * `tx` - The parsed transaction
 /// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
-/// * `_network` - The network (currently unused)
-/// * `_height` - Block height (currently unused, needed for full Sapling decryption)
+/// * `network` - The network, used to determine ZIP 212 enforcement for Sapling
+/// * `height` - Block height, used to determine ZIP 212 enforcement for Sapling.
+///   If `None` (e.g. an unmined transaction), the current (post-grace-period)
+///   rules are assumed, since that's the common case for a transaction without
+///   a height yet.
+/// * `sapling_start_position` - The size of the Sapling commitment tree
+///   immediately before this transaction's outputs, i.e. the absolute
+///   position of its first output. `None` if the tree size isn't known (e.g.
+///   an unmined transaction), in which case found notes are not assigned a
+///   `position` and the Sapling nullifier can't be computed.
+/// * `orchard_start_position` - As `sapling_start_position`, but for the
+///   Orchard commitment tree.
 ///
 /// # Returns
 ///
@@ -190,8 +565,10 @@ pub fn parse_viewing_key_capabilities(
 pub fn scan_transaction(
     tx: &Transaction,
     viewing_key: &str,
-    _network: Network,
-    _height: Option<u32>,
+    network: Network,
+    height: Option<u32>,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
 ) -> Result<ScanResult, ScannerError> {
     let txid = tx.txid().to_string();
     let mut notes = Vec::new();
@@ -221,10 +598,13 @@ pub fn scan_transaction(
         for (i, output) in transparent_bundle.vout.iter().enumerate() {
             let value = u64::from(output.value());
             transparent_received += value;
+            let address = output
+                .recipient_address()
+                .map(|addr| addr.encode(&network));
             transparent_outputs.push(ScannedTransparentOutput {
                 index: i,
                 value,
-                address: None, // TODO: decode address from script
+                address: address.clone(),
             });
             // Also add to notes for unified tracking
             notes.push(ScannedNote {
@@ -234,78 +614,172 @@ pub fn scan_transaction(
                 commitment: String::new(), // Transparent outputs don't have commitments
                 nullifier: None,           // Transparent outputs use input references instead
                 memo: None,                // Transparent outputs don't have memos
-                address: None,             // TODO: decode address from script
+                payment_request: None,
+                address,
+                transfer_type: None,       // Transparent outputs aren't classified
+                position: None,            // Transparent outputs aren't in a commitment tree
+                rho: None,
+                rseed: None,
             });
         }
     }
 
-    // Process Sapling outputs (without full decryption - focusing on Orchard)
+    // Process Sapling outputs with trial decryption
     if has_sapling && let Some(sapling_bundle) = tx.sapling_bundle() {
+        let zip212_height = height
+            .map(BlockHeight::from_u32)
+            .unwrap_or(BlockHeight::from_u32(u32::MAX));
+        let domain = SaplingDomain::new(zip212_enforcement(&network, zip212_height));
+
+        let sapling_fvk = extract_sapling_fvk(viewing_key);
+        let scanning_keys = ScanningKeys::new(
+            sapling_fvk
+                .as_ref()
+                .map(|fvk| SaplingPreparedIvk::new(&fvk.to_ivk(Scope::External))),
+            sapling_fvk
+                .as_ref()
+                .map(|fvk| SaplingPreparedIvk::new(&fvk.to_ivk(Scope::Internal))),
+        );
+        let ovk = sapling_fvk.as_ref().map(|fvk| fvk.to_ovk(Scope::External));
+
+        // Unlike Orchard, Sapling's nullifier-deriving key differs between
+        // the external and internal scope, so both are needed to compute the
+        // nullifier correctly depending on which scope decrypted the note.
+        let nk_external = sapling_fvk.as_ref().map(|fvk| fvk.to_nk(Scope::External));
+        let nk_internal = sapling_fvk.as_ref().map(|fvk| fvk.to_nk(Scope::Internal));
+
         for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
             let cmu = output.cmu();
             let commitment = hex::encode(cmu.to_bytes());
+            let position = sapling_start_position.map(|start| start + i as u64);
+
+            let mut value = 0u64;
+            let mut memo = None;
+            let mut payment_request = None;
+            let mut nullifier = None;
+            let mut address = None;
+            let mut transfer_type = None;
+
+            if let Some((note, recipient_addr, memo_bytes, scope)) =
+                scanning_keys.try_decrypt(&domain, output)
+            {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                let nk = match scope {
+                    TransferType::WalletInternal => nk_internal.as_ref(),
+                    _ => nk_external.as_ref(),
+                };
+                if let (Some(nk), Some(position)) = (nk, position) {
+                    nullifier = Some(hex::encode(note.nf(nk, position).0));
+                }
+                address = encode_sapling_address(network, recipient_addr);
+                transfer_type = Some(scope);
+            } else if let Some((note, recipient_addr, memo_bytes)) =
+                ovk.as_ref().and_then(|ovk| {
+                    try_output_recovery_with_ovk(
+                        &domain,
+                        ovk,
+                        output,
+                        output.cv(),
+                        output.out_ciphertext(),
+                    )
+                })
+            {
+                // Recovered via our own outgoing viewing key: this is a note
+                // we sent to someone else, so we hold no spend authority over
+                // it and can't derive its nullifier.
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                address = encode_sapling_address(network, recipient_addr);
+                transfer_type = Some(TransferType::Outgoing);
+            }
 
             notes.push(ScannedNote {
                 output_index: i,
                 pool: Pool::Sapling,
-                value: 0, // Sapling decryption requires height context
+                value,
                 commitment,
-                nullifier: None,
-                memo: None,
-                address: None,
+                nullifier,
+                memo,
+                payment_request,
+                address,
+                transfer_type,
+                position,
+                rho: None,
+                rseed: None,
             });
         }
     }
 
     // Process Orchard actions with trial decryption
     if has_orchard && let Some(orchard_bundle) = tx.orchard_bundle() {
-        // Prepare the incoming viewing key for decryption
-        let prepared_ivk = orchard_fvk
-            .as_ref()
-            .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External)));
+        // Prepare the incoming viewing keys for decryption: external for
+        // payments received from others, internal for change returned by our
+        // own spends. The outgoing viewing key additionally recovers outputs
+        // we sent to someone else - we can't spend those, but we still want
+        // to know where our money went.
+        let scanning_keys = ScanningKeys::new(
+            orchard_fvk
+                .as_ref()
+                .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External))),
+            orchard_fvk
+                .as_ref()
+                .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::Internal))),
+        );
+        let ovk = orchard_fvk.as_ref().map(|fvk| fvk.to_ovk(Scope::External));
 
         for (i, action) in orchard_bundle.actions().iter().enumerate() {
             let cmx = action.cmx();
             let commitment = hex::encode(cmx.to_bytes());
+            let domain = OrchardDomain::for_action(action);
+            let position = orchard_start_position.map(|start| start + i as u64);
 
             let mut value = 0u64;
             let mut memo = None;
+            let mut payment_request = None;
             let mut nullifier = None;
             let mut address = None;
+            let mut transfer_type = None;
+            let mut rho = None;
+            let mut rseed = None;
 
-            // Attempt trial decryption if we have the viewing key
-            if let Some(ref ivk) = prepared_ivk {
-                let domain = OrchardDomain::for_action(action);
-
-                if let Some((note, recipient_addr, memo_bytes)) =
-                    try_note_decryption(&domain, ivk, action)
-                {
-                    // Successfully decrypted!
-                    value = note.value().inner();
-
-                    // Extract memo (strip trailing zeros and convert to string if valid UTF-8)
-                    let memo_trimmed: Vec<u8> = memo_bytes
-                        .iter()
-                        .rev()
-                        .skip_while(|&&b| b == 0)
-                        .copied()
-                        .collect::<Vec<_>>()
-                        .into_iter()
-                        .rev()
-                        .collect();
-                    if !memo_trimmed.is_empty() {
-                        memo = String::from_utf8(memo_trimmed).ok();
-                    }
-
-                    // Compute the nullifier for this note
-                    if let Some(ref fvk) = orchard_fvk {
-                        let nf = note.nullifier(fvk);
-                        nullifier = Some(hex::encode(nf.to_bytes()));
-                    }
-
-                    // Encode the recipient address
-                    address = Some(format!("{:?}", recipient_addr));
+            if let Some((note, recipient_addr, memo_bytes, scope)) =
+                scanning_keys.try_decrypt(&domain, action)
+            {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                if let Some(ref fvk) = orchard_fvk {
+                    nullifier = Some(hex::encode(note.nullifier(fvk).to_bytes()));
                 }
+                // The Orchard note isn't recoverable from `commitment`/
+                // `value`/`address` alone; `rho`/`rseed` let a downstream
+                // module reconstruct it to build a witness at spend time.
+                rho = Some(hex::encode(note.rho().to_bytes()));
+                rseed = Some(hex::encode(note.rseed().as_bytes()));
+                address = encode_orchard_address(network, recipient_addr);
+                transfer_type = Some(scope);
+            } else if let Some((note, recipient_addr, memo_bytes)) =
+                ovk.as_ref().and_then(|ovk| {
+                    try_output_recovery_with_ovk(
+                        &domain,
+                        ovk,
+                        action,
+                        action.cv_net(),
+                        &action.encrypted_note().out_ciphertext,
+                    )
+                })
+            {
+                // Recovered via our own outgoing viewing key: this is a note
+                // we sent to someone else, so we hold no spend authority over
+                // it and can't derive its nullifier.
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                address = encode_orchard_address(network, recipient_addr);
+                transfer_type = Some(TransferType::Outgoing);
             }
 
             notes.push(ScannedNote {
@@ -315,7 +789,12 @@ pub fn scan_transaction(
                 commitment,
                 nullifier,
                 memo,
+                payment_request,
                 address,
+                transfer_type,
+                position,
+                rho,
+                rseed,
             });
         }
     }
@@ -343,6 +822,8 @@ pub fn scan_transaction(
 /// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
 /// * `network` - The network to use for parsing
 /// * `height` - Optional block height (needed for full Sapling decryption)
+/// * `sapling_start_position` - See [`scan_transaction`]
+/// * `orchard_start_position` - See [`scan_transaction`]
 ///
 /// # Returns
 ///
@@ -352,9 +833,531 @@ pub fn scan_transaction_hex(
     viewing_key: &str,
     network: Network,
     height: Option<u32>,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
 ) -> Result<ScanResult, ScannerError> {
     let tx = parse_transaction(tx_hex, network)?;
-    scan_transaction(&tx, viewing_key, network, height)
+    scan_transaction(
+        &tx,
+        viewing_key,
+        network,
+        height,
+        sapling_start_position,
+        orchard_start_position,
+    )
+}
+
+/// Scan multiple transactions for notes belonging to a viewing key, batching
+/// trial decryption across all of their Sapling outputs and Orchard actions.
+///
+/// [`scan_transaction`] decrypts one output at a time, independently
+/// re-deriving the ephemeral key and IVK-shared secret for each. Batching
+/// lets `zcash_note_encryption` amortize that work across every output in
+/// every supplied transaction at once and is the biggest lever for scan
+/// throughput when scanning many transactions, e.g. in a browser wallet.
+/// Transparent outputs, transparent spends, and nullifier extraction aren't
+/// part of that hot path and are handled the same way as `scan_transaction`.
+///
+/// # Arguments
+///
+/// * `txs` - The parsed transactions to scan, in any order
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network, used to determine ZIP 212 enforcement for
+///   Sapling and to encode recovered addresses
+/// * `heights` - Block height for each transaction, indexed the same as
+///   `txs`. See [`scan_transaction`] for the meaning of `None`.
+/// * `sapling_start_positions` - The Sapling commitment tree position of
+///   each transaction's first output, indexed the same as `txs`. See
+///   [`scan_transaction`].
+/// * `orchard_start_positions` - As `sapling_start_positions`, but for the
+///   Orchard commitment tree.
+///
+/// # Returns
+///
+/// One `ScanResult` per transaction, in the same order as `txs`.
+pub fn scan_transactions_batch(
+    txs: &[Transaction],
+    viewing_key: &str,
+    network: Network,
+    heights: &[Option<u32>],
+    sapling_start_positions: &[Option<u64>],
+    orchard_start_positions: &[Option<u64>],
+) -> Result<Vec<ScanResult>, ScannerError> {
+    if heights.len() != txs.len()
+        || sapling_start_positions.len() != txs.len()
+        || orchard_start_positions.len() != txs.len()
+    {
+        return Err(ScannerError::MismatchedBatchLengths);
+    }
+
+    let (has_sapling, has_orchard, has_transparent) = parse_viewing_key_capabilities(viewing_key)?;
+    let orchard_fvk = extract_orchard_fvk(viewing_key);
+    let sapling_fvk = extract_sapling_fvk(viewing_key);
+
+    // Seed one `ScanResult` per transaction with everything outside the
+    // batched trial-decryption hot path: transparent outputs/spends and
+    // nullifiers. Sapling and Orchard notes are appended to `notes` below,
+    // once decryption has run.
+    let mut results: Vec<ScanResult> = txs
+        .iter()
+        .map(|tx| {
+            let txid = tx.txid().to_string();
+            let mut transparent_received = 0u64;
+            let mut transparent_outputs = Vec::new();
+            let mut notes = Vec::new();
+
+            if has_transparent && let Some(transparent_bundle) = tx.transparent_bundle() {
+                for (i, output) in transparent_bundle.vout.iter().enumerate() {
+                    let value = u64::from(output.value());
+                    transparent_received += value;
+                    let address = output
+                        .recipient_address()
+                        .map(|addr| addr.encode(&network));
+                    transparent_outputs.push(ScannedTransparentOutput {
+                        index: i,
+                        value,
+                        address: address.clone(),
+                    });
+                    notes.push(ScannedNote {
+                        output_index: i,
+                        pool: Pool::Transparent,
+                        value,
+                        commitment: String::new(),
+                        nullifier: None,
+                        memo: None,
+                        payment_request: None,
+                        address,
+                        transfer_type: None,
+                        position: None,
+                        rho: None,
+                        rseed: None,
+                    });
+                }
+            }
+
+            let mut transparent_spends = Vec::new();
+            if let Some(transparent_bundle) = tx.transparent_bundle() {
+                for input in transparent_bundle.vin.iter() {
+                    let prevout = input.prevout();
+                    transparent_spends.push(TransparentSpend {
+                        prevout_txid: hex::encode(prevout.hash()),
+                        prevout_index: prevout.n(),
+                    });
+                }
+            }
+
+            ScanResult {
+                txid,
+                notes,
+                spent_nullifiers: extract_nullifiers(tx),
+                transparent_spends,
+                transparent_received,
+                transparent_outputs,
+            }
+        })
+        .collect();
+
+    // Process Sapling outputs with batched trial decryption.
+    if has_sapling {
+        let scanning_keys = ScanningKeys::new(
+            sapling_fvk
+                .as_ref()
+                .map(|fvk| SaplingPreparedIvk::new(&fvk.to_ivk(Scope::External))),
+            sapling_fvk
+                .as_ref()
+                .map(|fvk| SaplingPreparedIvk::new(&fvk.to_ivk(Scope::Internal))),
+        );
+        let ovk = sapling_fvk.as_ref().map(|fvk| fvk.to_ovk(Scope::External));
+        let nk_external = sapling_fvk.as_ref().map(|fvk| fvk.to_nk(Scope::External));
+        let nk_internal = sapling_fvk.as_ref().map(|fvk| fvk.to_nk(Scope::Internal));
+
+        let mut items = Vec::new();
+        let mut locations = Vec::new();
+        for (tx_index, tx) in txs.iter().enumerate() {
+            if let Some(sapling_bundle) = tx.sapling_bundle() {
+                let zip212_height = heights[tx_index]
+                    .map(BlockHeight::from_u32)
+                    .unwrap_or(BlockHeight::from_u32(u32::MAX));
+                let zip212_enforcement = zip212_enforcement(&network, zip212_height);
+                for (output_index, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
+                    items.push((SaplingDomain::new(zip212_enforcement), output.clone()));
+                    locations.push((tx_index, output_index));
+                }
+            }
+        }
+
+        let decrypted = scanning_keys.try_decrypt_batch(&items);
+
+        for (((tx_index, output_index), (domain, output)), decrypted_note) in
+            locations.into_iter().zip(items.iter()).zip(decrypted)
+        {
+            let commitment = hex::encode(output.cmu().to_bytes());
+            let position =
+                sapling_start_positions[tx_index].map(|start| start + output_index as u64);
+
+            let mut value = 0u64;
+            let mut memo = None;
+            let mut payment_request = None;
+            let mut nullifier = None;
+            let mut address = None;
+            let mut transfer_type = None;
+
+            if let Some((note, recipient_addr, memo_bytes, scope)) = decrypted_note {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                let nk = match scope {
+                    TransferType::WalletInternal => nk_internal.as_ref(),
+                    _ => nk_external.as_ref(),
+                };
+                if let (Some(nk), Some(position)) = (nk, position) {
+                    nullifier = Some(hex::encode(note.nf(nk, position).0));
+                }
+                address = encode_sapling_address(network, recipient_addr);
+                transfer_type = Some(scope);
+            } else if let Some((note, recipient_addr, memo_bytes)) =
+                ovk.as_ref().and_then(|ovk| {
+                    try_output_recovery_with_ovk(
+                        domain,
+                        ovk,
+                        output,
+                        output.cv(),
+                        output.out_ciphertext(),
+                    )
+                })
+            {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                address = encode_sapling_address(network, recipient_addr);
+                transfer_type = Some(TransferType::Outgoing);
+            }
+
+            results[tx_index].notes.push(ScannedNote {
+                output_index,
+                pool: Pool::Sapling,
+                value,
+                commitment,
+                nullifier,
+                memo,
+                payment_request,
+                address,
+                transfer_type,
+                position,
+                rho: None,
+                rseed: None,
+            });
+        }
+    }
+
+    // Process Orchard actions with batched trial decryption.
+    if has_orchard {
+        let scanning_keys = ScanningKeys::new(
+            orchard_fvk
+                .as_ref()
+                .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External))),
+            orchard_fvk
+                .as_ref()
+                .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::Internal))),
+        );
+        let ovk = orchard_fvk.as_ref().map(|fvk| fvk.to_ovk(Scope::External));
+
+        let mut items = Vec::new();
+        let mut locations = Vec::new();
+        for (tx_index, tx) in txs.iter().enumerate() {
+            if let Some(orchard_bundle) = tx.orchard_bundle() {
+                for (action_index, action) in orchard_bundle.actions().iter().enumerate() {
+                    items.push((OrchardDomain::for_action(action), action.clone()));
+                    locations.push((tx_index, action_index));
+                }
+            }
+        }
+
+        let decrypted = scanning_keys.try_decrypt_batch(&items);
+
+        for (((tx_index, action_index), (domain, action)), decrypted_note) in
+            locations.into_iter().zip(items.iter()).zip(decrypted)
+        {
+            let commitment = hex::encode(action.cmx().to_bytes());
+            let position =
+                orchard_start_positions[tx_index].map(|start| start + action_index as u64);
+
+            let mut value = 0u64;
+            let mut memo = None;
+            let mut payment_request = None;
+            let mut nullifier = None;
+            let mut address = None;
+            let mut transfer_type = None;
+            let mut rho = None;
+            let mut rseed = None;
+
+            if let Some((note, recipient_addr, memo_bytes, scope)) = decrypted_note {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                if let Some(ref fvk) = orchard_fvk {
+                    nullifier = Some(hex::encode(note.nullifier(fvk).to_bytes()));
+                }
+                rho = Some(hex::encode(note.rho().to_bytes()));
+                rseed = Some(hex::encode(note.rseed().as_bytes()));
+                address = encode_orchard_address(network, recipient_addr);
+                transfer_type = Some(scope);
+            } else if let Some((note, recipient_addr, memo_bytes)) =
+                ovk.as_ref().and_then(|ovk| {
+                    try_output_recovery_with_ovk(
+                        domain,
+                        ovk,
+                        action,
+                        action.cv_net(),
+                        &action.encrypted_note().out_ciphertext,
+                    )
+                })
+            {
+                value = note.value().inner();
+                memo = Some(memo_from_bytes(&memo_bytes));
+                payment_request = memo.as_ref().and_then(parse_memo_payment_request);
+                address = encode_orchard_address(network, recipient_addr);
+                transfer_type = Some(TransferType::Outgoing);
+            }
+
+            results[tx_index].notes.push(ScannedNote {
+                output_index: action_index,
+                pool: Pool::Orchard,
+                value,
+                commitment,
+                nullifier,
+                memo,
+                payment_request,
+                address,
+                transfer_type,
+                position,
+                rho,
+                rseed,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Scan a single compact transaction for notes and nullifiers.
+///
+/// Unlike [`scan_transaction`], only Orchard outputs are trial-decrypted
+/// here: a `CompactSaplingOutput`/`CompactOrchardAction` only carries a
+/// 52-byte ciphertext prefix, which is enough to recover the note's value
+/// and recipient for Orchard, while Sapling outputs are recorded with their
+/// commitment and position only. Either way, the memo is never recoverable from compact data
+/// alone and requires fetching the full transaction.
+#[allow(clippy::too_many_arguments)]
+fn scan_compact_tx(
+    tx: &CompactTx,
+    network: Network,
+    orchard_fvk: Option<&OrchardFvk>,
+    has_sapling: bool,
+    has_orchard: bool,
+    has_transparent: bool,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
+) -> ScanResult {
+    let txid = <[u8; 32]>::try_from(tx.txid.as_slice())
+        .map(|bytes| TxId::from_bytes(bytes).to_string())
+        .unwrap_or_else(|_| hex::encode(&tx.txid));
+
+    let mut notes = Vec::new();
+    let mut transparent_received = 0u64;
+    let mut transparent_outputs = Vec::new();
+
+    if has_transparent {
+        for (i, output) in tx.vout.iter().enumerate() {
+            transparent_received += output.value;
+            let address = decode_transparent_address(network, &output.script_pub_key);
+            transparent_outputs.push(ScannedTransparentOutput {
+                index: i,
+                value: output.value,
+                address: address.clone(),
+            });
+            notes.push(ScannedNote {
+                output_index: i,
+                pool: Pool::Transparent,
+                value: output.value,
+                commitment: String::new(),
+                nullifier: None,
+                memo: None,
+                payment_request: None,
+                address,
+                transfer_type: None,
+                position: None,
+                rho: None,
+                rseed: None,
+            });
+        }
+    }
+
+    if has_sapling {
+        for (i, output) in tx.outputs.iter().enumerate() {
+            let position = sapling_start_position.map(|start| start + i as u64);
+            notes.push(ScannedNote {
+                output_index: i,
+                pool: Pool::Sapling,
+                value: 0, // Sapling decryption requires height context
+                commitment: hex::encode(&output.cmu),
+                nullifier: None,
+                memo: None,
+                payment_request: None,
+                address: None,
+                transfer_type: None,
+                position,
+                rho: None,
+                rseed: None,
+            });
+        }
+    }
+
+    if has_orchard {
+        // As in `scan_transaction`, try the external (incoming) key first and
+        // fall back to the internal (change) key. Compact actions don't carry
+        // the outgoing ciphertext, so outgoing recovery isn't possible here -
+        // a full transaction fetch is required to classify our own sends.
+        let scanning_keys = ScanningKeys::new(
+            orchard_fvk.map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External))),
+            orchard_fvk.map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::Internal))),
+        );
+
+        for (i, action) in tx.actions.iter().enumerate() {
+            let commitment = hex::encode(&action.cmx);
+            let position = orchard_start_position.map(|start| start + i as u64);
+
+            let mut value = 0u64;
+            let mut nullifier = None;
+            let mut address = None;
+            let mut transfer_type = None;
+
+            if let Ok(compact_action) = CompactAction::try_from(action) {
+                let domain = OrchardDomain::for_compact_action(&compact_action);
+
+                if let Some((note, recipient_addr, kind)) =
+                    scanning_keys.try_decrypt_compact(&domain, &compact_action)
+                {
+                    value = note.value().inner();
+
+                    if let Some(fvk) = orchard_fvk {
+                        let nf = note.nullifier(fvk);
+                        nullifier = Some(hex::encode(nf.to_bytes()));
+                    }
+
+                    address = encode_orchard_address(network, recipient_addr);
+                    transfer_type = Some(kind);
+                }
+            }
+
+            notes.push(ScannedNote {
+                output_index: i,
+                pool: Pool::Orchard,
+                value,
+                commitment,
+                nullifier,
+                memo: None,
+                payment_request: None,
+                address,
+                transfer_type,
+                position,
+                rho: None,
+                rseed: None,
+            });
+        }
+    }
+
+    let mut spent_nullifiers = Vec::new();
+    if has_sapling {
+        for spend in &tx.spends {
+            spent_nullifiers.push(SpentNullifier {
+                pool: Pool::Sapling,
+                nullifier: hex::encode(&spend.nf),
+            });
+        }
+    }
+    if has_orchard {
+        for action in &tx.actions {
+            spent_nullifiers.push(SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: hex::encode(&action.nullifier),
+            });
+        }
+    }
+
+    let mut transparent_spends = Vec::new();
+    if has_transparent {
+        for input in &tx.vin {
+            transparent_spends.push(TransparentSpend {
+                prevout_txid: hex::encode(&input.prevout_txid),
+                prevout_index: input.prevout_index,
+            });
+        }
+    }
+
+    ScanResult {
+        txid,
+        notes,
+        spent_nullifiers,
+        transparent_spends,
+        transparent_received,
+        transparent_outputs,
+    }
+}
+
+/// Scan every transaction in a compact block for notes and nullifiers
+/// belonging to a viewing key.
+///
+/// # Arguments
+///
+/// * `block` - The compact block, as streamed from a lightwalletd
+///   `CompactTxStreamer` endpoint.
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network, used to encode any transparent/Orchard
+///   addresses recovered from the block
+/// * `sapling_start_position` - The size of the Sapling commitment tree
+///   immediately before this block, used to compute absolute commitment
+///   positions for every note in the block. `None` if unknown, in which
+///   case no note in the block gets a position.
+/// * `orchard_start_position` - As `sapling_start_position`, but for the
+///   Orchard commitment tree.
+///
+/// # Returns
+///
+/// One `ScanResult` per transaction in the block (including transactions
+/// with no relevant notes), in block order.
+pub fn scan_compact_block(
+    block: &CompactBlock,
+    viewing_key: &str,
+    network: Network,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
+) -> Result<Vec<ScanResult>, ScannerError> {
+    let (has_sapling, has_orchard, has_transparent) = parse_viewing_key_capabilities(viewing_key)?;
+    let orchard_fvk = extract_orchard_fvk(viewing_key);
+
+    let mut sapling_position = sapling_start_position;
+    let mut orchard_position = orchard_start_position;
+
+    Ok(block
+        .vtx
+        .iter()
+        .map(|tx| {
+            let result = scan_compact_tx(
+                tx,
+                network,
+                orchard_fvk.as_ref(),
+                has_sapling,
+                has_orchard,
+                has_transparent,
+                sapling_position,
+                orchard_position,
+            );
+            sapling_position = sapling_position.map(|pos| pos + tx.outputs.len() as u64);
+            orchard_position = orchard_position.map(|pos| pos + tx.actions.len() as u64);
+            result
+        })
+        .collect())
 }
 
 #[cfg(test)]
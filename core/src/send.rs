@@ -0,0 +1,341 @@
+//! Client-side shielded transaction construction (a `z_sendmany` equivalent).
+//!
+//! [`crate::shield`] moves the wallet's own transparent funds into its own
+//! Orchard pool. This module completes the spending story: it spends the
+//! wallet's own previously witnessed notes and sends the proceeds to
+//! arbitrary recipients, returning a signed raw transaction ready to
+//! broadcast.
+//!
+//! As in [`crate::shield`], only the Orchard pool can be proved in this
+//! wallet - Orchard's proving key is built entirely in-memory at runtime,
+//! while Sapling's Groth16 prover needs an external parameter file this
+//! wallet doesn't ship. So [`build_transaction`] only accepts
+//! [`WitnessedNote`]s from the Orchard pool as inputs, and only accepts
+//! transparent or Orchard-capable addresses as recipients; a destination
+//! that only resolves to a Sapling receiver is rejected rather than
+//! silently built into an unprovable transaction.
+
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use thiserror::Error;
+use zcash_keys::address::Address;
+use zcash_keys::keys::UnifiedSpendingKey;
+use zcash_primitives::transaction::builder::{BuildConfig, BundlePadding, Builder};
+use zcash_primitives::transaction::fees::zip317::{FeeRule, GRACE_ACTIONS, MARGINAL_FEE};
+use zcash_protocol::consensus::{BlockHeight, Network};
+use zcash_protocol::memo::MemoBytes;
+use zcash_protocol::value::Zatoshis;
+use zcash_transparent::builder::TransparentSigningSet;
+use zip32::AccountId;
+
+use crate::block_scanner::WitnessedNote;
+use crate::shield::UnusedSaplingProver;
+use crate::tree::deserialize_orchard_witness;
+use crate::types::Pool;
+use crate::wallet::WalletError;
+
+/// The depth of the Orchard note commitment tree, matching
+/// [`orchard::NOTE_COMMITMENT_TREE_DEPTH`] - the fixed-size auth path length
+/// [`orchard::tree::MerklePath::from_parts`] expects.
+const ORCHARD_MERKLE_DEPTH: usize = orchard::NOTE_COMMITMENT_TREE_DEPTH;
+
+/// The Orchard bundle is padded to a minimum of 2 logical actions.
+const ORCHARD_BUNDLE_MIN_ACTIONS: usize = 2;
+
+/// Errors that can occur while building a send transaction.
+#[derive(Error, Debug)]
+pub enum SendError {
+    #[error("Failed to derive spending keys: {0}")]
+    KeyDerivation(#[from] WalletError),
+
+    #[error("No input notes were provided to spend")]
+    NoInputs,
+
+    #[error("No outputs were provided to send to")]
+    NoOutputs,
+
+    #[error("Invalid input note: {0}")]
+    InvalidInput(String),
+
+    #[error(
+        "build_transaction only spends Orchard notes; Sapling spending would need a proving parameter file this wallet doesn't ship"
+    )]
+    UnsupportedSaplingInput,
+
+    #[error("Orchard input notes must all share the same witnessed anchor")]
+    InconsistentAnchor,
+
+    #[error("{0} is not a valid recipient address for this network")]
+    InvalidRecipient(String),
+
+    #[error(
+        "{0} has no transparent or Orchard receiver; Sapling output proving would need a parameter file this wallet doesn't ship"
+    )]
+    UnsupportedSaplingRecipient(String),
+
+    #[error("A memo can only be attached to a shielded output, not {0}")]
+    MemoToTransparent(String),
+
+    #[error("Total input value is too small to cover the outputs and fee")]
+    InsufficientFunds,
+
+    #[error("Failed to build transaction: {0}")]
+    Build(String),
+
+    #[error("Failed to serialize transaction: {0}")]
+    Serialization(String),
+}
+
+/// A single payment to make as part of a [`build_transaction`] call.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SendOutput {
+    /// The recipient's address - transparent, unified, or legacy Sapling.
+    /// See the module docs for which of these are actually supported.
+    pub address: String,
+    /// The amount to send, in zatoshis.
+    pub amount: u64,
+    /// An optional ZIP 302 plaintext memo. Only valid when `address`
+    /// resolves to a shielded receiver.
+    pub memo: Option<String>,
+}
+
+/// Build and sign a transaction spending `inputs` and paying `outputs`,
+/// returning the raw transaction hex for broadcast.
+///
+/// `inputs` must be Orchard notes previously witnessed by the wallet's own
+/// scanner (see [`WitnessedNote`]), all witnessed against the same tree
+/// state. Any leftover value after `outputs` and the ZIP 317 fee is sent
+/// back to the wallet's own Orchard internal (change) address.
+/// `target_height` is the height the transaction targets for inclusion
+/// (used for the expiry height and branch id).
+pub fn build_transaction(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    passphrase: Option<&str>,
+    inputs: &[WitnessedNote],
+    outputs: &[SendOutput],
+    target_height: u32,
+) -> Result<String, SendError> {
+    if inputs.is_empty() {
+        return Err(SendError::NoInputs);
+    }
+    if outputs.is_empty() {
+        return Err(SendError::NoOutputs);
+    }
+
+    let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, seed_phrase.trim())
+        .map_err(|e| SendError::KeyDerivation(WalletError::InvalidSeedPhrase(e.to_string())))?;
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    let account = AccountId::try_from(account_index).map_err(|_| {
+        SendError::KeyDerivation(WalletError::InvalidAccountIndex(format!(
+            "Account index {} is out of valid range",
+            account_index
+        )))
+    })?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| SendError::KeyDerivation(WalletError::SpendingKeyDerivation(format!("{:?}", e))))?;
+
+    let orchard_sk = usk.orchard();
+    let orchard_fvk = orchard::keys::FullViewingKey::from(orchard_sk);
+    let orchard_sak = orchard::keys::SpendAuthorizingKey::from(orchard_sk);
+    let change_address = orchard_fvk.address_at(0u32, orchard::keys::Scope::Internal);
+
+    // The anchor the builder proves every spend against must be the root
+    // each input's witness actually authenticates against, not the empty
+    // tree - decode every input's note and witness first so we have that
+    // anchor in hand before constructing `Builder`, which is configured
+    // with it up front.
+    let mut total_input_value = Zatoshis::ZERO;
+    let mut common_anchor: Option<orchard::Anchor> = None;
+    let mut orchard_spends = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        if input.note.pool != Pool::Orchard {
+            return Err(SendError::UnsupportedSaplingInput);
+        }
+
+        let address_str = input
+            .note
+            .address
+            .as_ref()
+            .ok_or_else(|| SendError::InvalidInput("missing recipient address".to_string()))?;
+        let recipient = match Address::decode(&network, address_str) {
+            Some(Address::Unified(ua)) => *ua
+                .orchard()
+                .ok_or_else(|| SendError::InvalidInput("note's own address has no Orchard receiver".to_string()))?,
+            _ => return Err(SendError::InvalidInput("note's own address is not a unified Orchard address".to_string())),
+        };
+
+        let rho_hex = input
+            .note
+            .rho
+            .as_ref()
+            .ok_or_else(|| SendError::InvalidInput("missing rho".to_string()))?;
+        let rho_bytes: [u8; 32] = hex::decode(rho_hex)
+            .map_err(|e| SendError::InvalidInput(format!("bad rho: {}", e)))?
+            .try_into()
+            .map_err(|_| SendError::InvalidInput("rho must be 32 bytes".to_string()))?;
+        let rho = Option::from(orchard::note::Rho::from_bytes(&rho_bytes))
+            .ok_or_else(|| SendError::InvalidInput("invalid rho".to_string()))?;
+
+        let rseed_hex = input
+            .note
+            .rseed
+            .as_ref()
+            .ok_or_else(|| SendError::InvalidInput("missing rseed".to_string()))?;
+        let rseed_bytes: [u8; 32] = hex::decode(rseed_hex)
+            .map_err(|e| SendError::InvalidInput(format!("bad rseed: {}", e)))?
+            .try_into()
+            .map_err(|_| SendError::InvalidInput("rseed must be 32 bytes".to_string()))?;
+        let rseed = Option::from(orchard::note::RandomSeed::from_bytes(rseed_bytes, &rho))
+            .ok_or_else(|| SendError::InvalidInput("invalid rseed".to_string()))?;
+
+        let value = orchard::value::NoteValue::from_raw(input.note.value);
+        let note = Option::from(orchard::Note::from_parts(
+            recipient,
+            value,
+            rho,
+            rseed,
+            orchard::NoteVersion::V2,
+        ))
+        .ok_or_else(|| SendError::InvalidInput("note components do not form a valid note".to_string()))?;
+
+        let witness = deserialize_orchard_witness(&input.witness)
+            .map_err(|e| SendError::InvalidInput(format!("bad witness: {}", e)))?;
+        let anchor: orchard::Anchor = witness.root().into();
+        match common_anchor {
+            None => common_anchor = Some(anchor),
+            Some(existing) if existing == anchor => {}
+            Some(_) => return Err(SendError::InconsistentAnchor),
+        }
+
+        let path = witness
+            .path()
+            .ok_or_else(|| SendError::InvalidInput("witness has no authentication path".to_string()))?;
+        let position: u64 = path.position().into();
+        let auth_path: [orchard::tree::MerkleHashOrchard; ORCHARD_MERKLE_DEPTH] = path
+            .path_elems()
+            .to_vec()
+            .try_into()
+            .map_err(|_| SendError::InvalidInput("witness authentication path has the wrong depth".to_string()))?;
+        let merkle_path = orchard::tree::MerklePath::from_parts(position as u32, auth_path);
+
+        orchard_spends.push((note, merkle_path));
+
+        total_input_value = (total_input_value + Zatoshis::const_from_u64(input.note.value))
+            .ok_or(SendError::InsufficientFunds)?;
+    }
+
+    let target_height_for_builder = BlockHeight::from_u32(target_height);
+    let mut builder = Builder::new(
+        network,
+        target_height_for_builder,
+        BuildConfig::Standard {
+            sapling_anchor: None,
+            orchard_anchor: Some(common_anchor.unwrap_or_else(orchard::Anchor::empty_tree)),
+            ironwood_anchor: None,
+            orchard_padding: BundlePadding::DEFAULT,
+            ironwood_padding: BundlePadding::DEFAULT,
+        },
+    );
+
+    for (note, merkle_path) in orchard_spends {
+        builder
+            .add_orchard_spend::<std::convert::Infallible>(orchard_fvk.clone(), note, merkle_path)
+            .map_err(|e| SendError::Build(format!("{:?}", e)))?;
+    }
+
+    let mut total_output_value = Zatoshis::ZERO;
+
+    for output in outputs {
+        let amount = Zatoshis::from_u64(output.amount)
+            .map_err(|e| SendError::InvalidRecipient(format!("bad amount: {}", e)))?;
+
+        match Address::decode(&network, &output.address) {
+            Some(Address::Transparent(addr)) => {
+                if output.memo.is_some() {
+                    return Err(SendError::MemoToTransparent(output.address.clone()));
+                }
+                builder
+                    .add_transparent_output(&addr, amount)
+                    .map_err(|e| SendError::Build(format!("{:?}", e)))?;
+            }
+            Some(Address::Unified(ua)) => {
+                let recipient = *ua.orchard().ok_or_else(|| {
+                    SendError::UnsupportedSaplingRecipient(output.address.clone())
+                })?;
+                let memo = match &output.memo {
+                    Some(text) => MemoBytes::from_bytes(text.as_bytes())
+                        .map_err(|e| SendError::InvalidRecipient(format!("bad memo: {}", e)))?,
+                    None => MemoBytes::empty(),
+                };
+                builder
+                    .add_orchard_output::<std::convert::Infallible>(
+                        Some(orchard_fvk.to_ovk(orchard::keys::Scope::External)),
+                        recipient,
+                        amount,
+                        memo,
+                    )
+                    .map_err(|e| SendError::Build(format!("{:?}", e)))?;
+            }
+            Some(Address::Sapling(_)) | Some(Address::Tex(_)) => {
+                return Err(SendError::UnsupportedSaplingRecipient(output.address.clone()));
+            }
+            None => return Err(SendError::InvalidRecipient(output.address.clone())),
+        }
+
+        total_output_value = (total_output_value + amount).ok_or(SendError::InsufficientFunds)?;
+    }
+
+    // ZIP 317 fees depend on the shape of the finished transaction, but we
+    // need a change value before we can build it. This slightly
+    // overestimates the fee in most cases by assuming a minimum-padded
+    // Orchard bundle and no transparent pool activity beyond outputs - an
+    // acceptable trade-off that just leaves a little extra in the change
+    // output rather than risking a failed build, mirroring the same
+    // trade-off `build_shielding_transaction` makes.
+    let orchard_logical_actions = inputs.len().max(outputs.len()).max(ORCHARD_BUNDLE_MIN_ACTIONS);
+    let logical_actions = orchard_logical_actions.max(GRACE_ACTIONS);
+    let fee_rule = FeeRule::standard();
+    let estimated_fee = Zatoshis::const_from_u64(MARGINAL_FEE.into_u64() * logical_actions as u64);
+
+    let change_value = (total_input_value - total_output_value)
+        .and_then(|remaining| remaining - estimated_fee)
+        .ok_or(SendError::InsufficientFunds)?;
+
+    if change_value > Zatoshis::ZERO {
+        builder
+            .add_orchard_change_output::<std::convert::Infallible>(
+                orchard_fvk,
+                None,
+                change_address,
+                change_value,
+                MemoBytes::empty(),
+            )
+            .map_err(|e| SendError::Build(format!("{:?}", e)))?;
+    }
+
+    let signing_set = TransparentSigningSet::new();
+    let build_result = builder
+        .build(
+            &signing_set,
+            &[],
+            &[orchard_sak],
+            OsRng,
+            &UnusedSaplingProver,
+            &UnusedSaplingProver,
+            &fee_rule,
+        )
+        .map_err(|e| SendError::Build(format!("{:?}", e)))?;
+
+    let mut tx_bytes = Vec::new();
+    build_result
+        .transaction()
+        .write(&mut tx_bytes)
+        .map_err(|e| SendError::Serialization(e.to_string()))?;
+
+    Ok(hex::encode(tx_bytes))
+}
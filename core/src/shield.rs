@@ -0,0 +1,264 @@
+//! Auto-shielding: moving tracked transparent funds into the Orchard pool.
+//!
+//! The wallet derives a transparent receiver but, until now, had no way to
+//! spend from it - transparent funds just sat there. This module builds a
+//! transaction that spends a set of the wallet's own tracked transparent
+//! UTXOs and sends the total (minus the ZIP 317 fee) to the wallet's own
+//! Orchard internal (change) address.
+//!
+//! The destination pool is Orchard rather than Sapling because Orchard's
+//! proving key is built entirely in-memory at runtime
+//! ([`orchard::circuit::ProvingKey::build`]); Sapling's Groth16 parameters
+//! require an external parameter file this wallet doesn't ship. Since the
+//! transaction this module builds never has Sapling spends or outputs, the
+//! Sapling prover traits required by [`Builder::build`]'s generic bounds are
+//! satisfied with a prover stub whose methods are never actually called.
+
+use rand::rngs::OsRng;
+use thiserror::Error;
+use zcash_keys::keys::UnifiedSpendingKey;
+use zcash_primitives::transaction::builder::{BuildConfig, BundlePadding, Builder};
+use zcash_primitives::transaction::fees::zip317::FeeRule;
+use zcash_protocol::consensus::{BlockHeight, Network};
+use zcash_protocol::memo::MemoBytes;
+use zcash_protocol::value::Zatoshis;
+use zcash_transparent::address::{Script, TransparentAddress};
+use zcash_transparent::bundle::{OutPoint, TxOut};
+use zcash_transparent::builder::TransparentSigningSet;
+use zip32::AccountId;
+
+use crate::types::TransparentUtxo;
+use crate::wallet::WalletError;
+
+/// Errors that can occur while building a shielding transaction.
+#[derive(Error, Debug)]
+pub enum ShieldError {
+    #[error("Failed to derive spending keys: {0}")]
+    KeyDerivation(#[from] WalletError),
+
+    #[error("No transparent UTXOs were provided to shield")]
+    NoInputs,
+
+    #[error("Invalid transparent UTXO: {0}")]
+    InvalidUtxo(String),
+
+    #[error("Total input value is too small to cover the transaction fee")]
+    InsufficientFunds,
+
+    #[error("Failed to build transaction: {0}")]
+    Build(String),
+
+    #[error("Failed to serialize transaction: {0}")]
+    Serialization(String),
+}
+
+/// A no-op Sapling prover that satisfies [`Builder::build`]'s generic
+/// `SpendProver`/`OutputProver` bounds without real Groth16 parameters.
+///
+/// [`Builder::build`] is generic over the Sapling prover regardless of
+/// whether the resulting transaction actually has any Sapling spends or
+/// outputs, so for an Orchard-only transaction these methods are never
+/// invoked - they only exist to make the type check. Shared with
+/// [`crate::send`], which builds Orchard-only transactions for the same
+/// reason.
+pub(crate) struct UnusedSaplingProver;
+
+impl sapling_crypto::prover::SpendProver for UnusedSaplingProver {
+    type Proof = sapling_crypto::bundle::GrothProofBytes;
+
+    fn prepare_circuit(
+        _proof_generation_key: sapling_crypto::ProofGenerationKey,
+        _diversifier: sapling_crypto::Diversifier,
+        _rseed: sapling_crypto::Rseed,
+        _value: sapling_crypto::value::NoteValue,
+        _alpha: jubjub::Fr,
+        _rcv: sapling_crypto::value::ValueCommitTrapdoor,
+        _anchor: bls12_381::Scalar,
+        _merkle_path: sapling_crypto::MerklePath,
+    ) -> Option<sapling_crypto::circuit::Spend> {
+        unreachable!("no Sapling spends are ever added to a shielding transaction")
+    }
+
+    fn create_proof<R: rand::RngCore>(
+        &self,
+        _circuit: sapling_crypto::circuit::Spend,
+        _rng: &mut R,
+    ) -> Self::Proof {
+        unreachable!("no Sapling spends are ever added to a shielding transaction")
+    }
+
+    fn encode_proof(_proof: Self::Proof) -> sapling_crypto::bundle::GrothProofBytes {
+        unreachable!("no Sapling spends are ever added to a shielding transaction")
+    }
+}
+
+impl sapling_crypto::prover::OutputProver for UnusedSaplingProver {
+    type Proof = sapling_crypto::bundle::GrothProofBytes;
+
+    fn prepare_circuit(
+        _esk: &sapling_crypto::keys::EphemeralSecretKey,
+        _payment_address: sapling_crypto::PaymentAddress,
+        _rcm: jubjub::Fr,
+        _value: sapling_crypto::value::NoteValue,
+        _rcv: sapling_crypto::value::ValueCommitTrapdoor,
+    ) -> sapling_crypto::circuit::Output {
+        unreachable!("no Sapling outputs are ever added to a shielding transaction")
+    }
+
+    fn create_proof<R: rand::RngCore>(
+        &self,
+        _circuit: sapling_crypto::circuit::Output,
+        _rng: &mut R,
+    ) -> Self::Proof {
+        unreachable!("no Sapling outputs are ever added to a shielding transaction")
+    }
+
+    fn encode_proof(_proof: Self::Proof) -> sapling_crypto::bundle::GrothProofBytes {
+        unreachable!("no Sapling outputs are ever added to a shielding transaction")
+    }
+}
+
+/// Build a transaction that spends `utxos` and shields their value into the
+/// wallet's own Orchard internal (change) address, returning the raw
+/// transaction hex for broadcast.
+///
+/// `utxos` must all belong to the wallet's transparent external address at
+/// `account_index`/address index 0 (the first address
+/// [`derive_transparent_addresses`] derives from a start index of 0); only
+/// that address's key is used to sign. `target_height` is the height the transaction targets
+/// for inclusion (used for the expiry height and branch id).
+///
+/// [`derive_transparent_addresses`]: crate::wallet::derive_transparent_addresses
+pub fn build_shielding_transaction(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    passphrase: Option<&str>,
+    utxos: &[TransparentUtxo],
+    target_height: u32,
+) -> Result<String, ShieldError> {
+    if utxos.is_empty() {
+        return Err(ShieldError::NoInputs);
+    }
+
+    let mnemonic = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, seed_phrase.trim())
+        .map_err(|e| ShieldError::KeyDerivation(WalletError::InvalidSeedPhrase(e.to_string())))?;
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    let account = AccountId::try_from(account_index).map_err(|_| {
+        ShieldError::KeyDerivation(WalletError::InvalidAccountIndex(format!(
+            "Account index {} is out of valid range",
+            account_index
+        )))
+    })?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| ShieldError::KeyDerivation(WalletError::SpendingKeyDerivation(format!("{:?}", e))))?;
+
+    let transparent_sk = usk.transparent();
+    let orchard_sk = usk.orchard();
+    let orchard_fvk = orchard::keys::FullViewingKey::from(orchard_sk);
+    let change_address = orchard_fvk.address_at(0u32, orchard::keys::Scope::Internal);
+
+    let target_height = BlockHeight::from_u32(target_height);
+    let mut builder = Builder::new(
+        network,
+        target_height,
+        BuildConfig::Standard {
+            sapling_anchor: None,
+            orchard_anchor: Some(orchard::Anchor::empty_tree()),
+            ironwood_anchor: None,
+            orchard_padding: BundlePadding::DEFAULT,
+            ironwood_padding: BundlePadding::DEFAULT,
+        },
+    );
+
+    // `utxos` must all belong to the external address at index 0, so there's
+    // a single signing key to derive and register.
+    let address_index = zcash_transparent::keys::NonHardenedChildIndex::from_index(0)
+        .expect("0 is always a valid non-hardened child index");
+    let secret_key = transparent_sk
+        .derive_external_secret_key(address_index)
+        .map_err(|e| ShieldError::KeyDerivation(WalletError::SpendingKeyDerivation(format!("{:?}", e))))?;
+    let mut signing_set = TransparentSigningSet::new();
+    let pubkey = signing_set.add_key(secret_key);
+    let our_address = TransparentAddress::from_pubkey(&pubkey);
+
+    let mut total_value = Zatoshis::ZERO;
+
+    for utxo in utxos {
+        let mut txid_bytes: [u8; 32] = hex::decode(&utxo.txid)
+            .map_err(|e| ShieldError::InvalidUtxo(format!("bad txid: {}", e)))?
+            .try_into()
+            .map_err(|_| ShieldError::InvalidUtxo("txid must be 32 bytes".to_string()))?;
+        // RPC-style UTXO listings report the txid in reversed (display) byte
+        // order; `OutPoint` wants the raw, non-reversed transaction hash.
+        txid_bytes.reverse();
+        let outpoint = OutPoint::new(txid_bytes, utxo.vout);
+
+        let script_bytes = hex::decode(&utxo.script_pubkey)
+            .map_err(|e| ShieldError::InvalidUtxo(format!("bad scriptPubKey: {}", e)))?;
+        let script_pubkey = zcash_script::script::PubKey::parse(&zcash_script::script::Code(script_bytes.clone()))
+            .map_err(|_| ShieldError::InvalidUtxo("unsupported scriptPubKey".to_string()))?;
+        // Fail loudly on a mismatched UTXO rather than silently building a
+        // transaction the network would reject for an invalid signature.
+        if TransparentAddress::from_script_pubkey(&script_pubkey) != Some(our_address) {
+            return Err(ShieldError::InvalidUtxo(
+                "UTXO scriptPubKey does not match the wallet's transparent address".to_string(),
+            ));
+        }
+
+        let value = Zatoshis::from_u64(utxo.value)
+            .map_err(|e| ShieldError::InvalidUtxo(format!("bad value: {}", e)))?;
+        let coin = TxOut::new(value, Script::from(script_pubkey));
+
+        builder
+            .add_transparent_p2pkh_input(pubkey, outpoint, coin)
+            .map_err(|e| ShieldError::Build(format!("{:?}", e)))?;
+
+        total_value = (total_value + value).ok_or(ShieldError::InsufficientFunds)?;
+    }
+
+    let fee_rule = FeeRule::standard();
+    // ZIP 317 fees depend on the shape of the finished transaction, but we
+    // need an output value before we can build it. The Orchard bundle is
+    // padded to a minimum of 2 logical actions, and each transparent input
+    // is its own logical action, so this slightly overestimates the fee in
+    // most cases - an acceptable trade-off that just leaves a little extra
+    // in the change-equivalent output rather than risking a failed build.
+    use zcash_primitives::transaction::fees::zip317::{GRACE_ACTIONS, MARGINAL_FEE};
+    const ORCHARD_BUNDLE_MIN_ACTIONS: usize = 2;
+    let logical_actions = (utxos.len() + ORCHARD_BUNDLE_MIN_ACTIONS).max(GRACE_ACTIONS);
+    let estimated_fee = Zatoshis::const_from_u64(MARGINAL_FEE.into_u64() * logical_actions as u64);
+    let shield_value = (total_value - estimated_fee).ok_or(ShieldError::InsufficientFunds)?;
+
+    builder
+        .add_orchard_change_output::<std::convert::Infallible>(
+            orchard_fvk,
+            None,
+            change_address,
+            shield_value,
+            MemoBytes::empty(),
+        )
+        .map_err(|e| ShieldError::Build(format!("{:?}", e)))?;
+
+    let build_result = builder
+        .build(
+            &signing_set,
+            &[],
+            &[],
+            OsRng,
+            &UnusedSaplingProver,
+            &UnusedSaplingProver,
+            &fee_rule,
+        )
+        .map_err(|e| ShieldError::Build(format!("{:?}", e)))?;
+
+    let mut tx_bytes = Vec::new();
+    build_result
+        .transaction()
+        .write(&mut tx_bytes)
+        .map_err(|e| ShieldError::Serialization(e.to_string()))?;
+
+    Ok(hex::encode(tx_bytes))
+}
@@ -0,0 +1,1123 @@
+//! Client-side persistence shapes for notes and wallets.
+//!
+//! The wallet itself has no database - the browser (or CLI) is responsible
+//! for persisting whatever JSON these types serialize to and handing it
+//! back on the next call. [`NoteCollection`] and [`WalletCollection`] are
+//! therefore plain, serializable containers with the bookkeeping operations
+//! a caller needs (add/update, mark spent, filter, select) rather than
+//! anything backed by a real storage engine.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{NetworkKind, Pool, SpentNullifier, TransparentSpend};
+
+/// Generic success/failure envelope for a single stored value.
+///
+/// Used at the JSON boundary for operations that produce or fail to
+/// produce one [`StoredNote`]/[`StoredWallet`], analogous to how
+/// [`crate::types::WalletResult`] reports a single wallet-derivation
+/// outcome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageResult<T> {
+    /// Whether the operation completed successfully.
+    pub success: bool,
+    /// The produced value, if `success` is true.
+    pub data: Option<T>,
+    /// Error message, if `success` is false.
+    pub error: Option<String>,
+}
+
+impl<T> StorageResult<T> {
+    /// A successful result wrapping `data`.
+    pub fn ok(data: T) -> Self {
+        StorageResult {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// A failed result carrying `message`.
+    pub fn err(message: impl Into<String>) -> Self {
+        StorageResult {
+            success: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// A single note (shielded or transparent) as persisted by the caller.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredNote {
+    /// Stable identifier, derived from `txid`, `pool`, and `output_index`.
+    pub id: String,
+    /// The wallet this note belongs to.
+    pub wallet_id: String,
+    /// Transaction ID where the note was received.
+    pub txid: String,
+    /// Output index within that transaction.
+    pub output_index: u32,
+    /// Which pool the note belongs to.
+    pub pool: Pool,
+    /// Value in zatoshis.
+    pub value: u64,
+    /// Note commitment, for shielded notes.
+    pub commitment: Option<String>,
+    /// Nullifier, for shielded notes (not yet known until the note is spent
+    /// or its nullifier is otherwise derived).
+    pub nullifier: Option<String>,
+    /// Decrypted memo, if any.
+    pub memo: Option<String>,
+    /// Recipient address the note was sent to.
+    pub address: Option<String>,
+    /// Transaction ID that spent this note, once known.
+    pub spent_txid: Option<String>,
+    /// ISO 8601 timestamp of when the note was first recorded.
+    pub created_at: String,
+    /// Block height the note was received at, once confirmed. `None` means
+    /// the note hasn't been observed in a mined block yet and is therefore
+    /// unconfirmed and ineligible for spending.
+    pub received_height: Option<u32>,
+    /// Block height the spend recorded in `spent_txid` was mined at, set
+    /// alongside it by [`NoteCollection::mark_spent_by_nullifiers`]/
+    /// [`NoteCollection::mark_spent_by_transparent`]. Needed by
+    /// [`rollback_notes_to_height`] to know whether a reorg should undo
+    /// the spend.
+    pub spent_height: Option<u32>,
+    /// Transaction ID of a spend seen only in the mempool, set by
+    /// [`NoteCollection::mark_pending_spent_by_nullifiers`]. Reserves the
+    /// note against double-spending while the transaction is unconfirmed,
+    /// without the finality of `spent_txid` - it's cleared by
+    /// [`NoteCollection::clear_pending_spends`] if the transaction never
+    /// confirms, or promoted to `spent_txid` by
+    /// [`NoteCollection::mark_spent_by_nullifiers`]/
+    /// [`NoteCollection::mark_spent_by_transparent`] once it does.
+    pub pending_spent_txid: Option<String>,
+}
+
+impl StoredNote {
+    /// Derive a stable ID for a note from its origin.
+    pub fn generate_id(txid: &str, pool: Pool, output_index: u32) -> String {
+        format!("{}_{}_{}", txid, pool.as_str(), output_index)
+    }
+}
+
+/// A caller-persisted collection of [`StoredNote`]s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteCollection {
+    pub notes: Vec<StoredNote>,
+}
+
+impl NoteCollection {
+    /// Add `note`, or replace the existing note with the same ID.
+    ///
+    /// Returns `true` if a new note was added, `false` if an existing one
+    /// was updated in place.
+    pub fn add_or_update(&mut self, note: StoredNote) -> bool {
+        if let Some(existing) = self.notes.iter_mut().find(|n| n.id == note.id) {
+            *existing = note;
+            false
+        } else {
+            self.notes.push(note);
+            true
+        }
+    }
+
+    /// Mark every note whose nullifier matches one of `nullifiers` as spent
+    /// by `spending_txid`, mined at `spending_height`. Returns the number of
+    /// notes marked.
+    pub fn mark_spent_by_nullifiers(
+        &mut self,
+        nullifiers: &[SpentNullifier],
+        spending_txid: &str,
+        spending_height: u32,
+    ) -> usize {
+        let spent: std::collections::HashSet<&str> =
+            nullifiers.iter().map(|n| n.nullifier.as_str()).collect();
+
+        let mut marked = 0;
+        for note in &mut self.notes {
+            if note.spent_txid.is_none()
+                && note
+                    .nullifier
+                    .as_deref()
+                    .is_some_and(|nf| spent.contains(nf))
+            {
+                note.spent_txid = Some(spending_txid.to_string());
+                note.spent_height = Some(spending_height);
+                note.pending_spent_txid = None;
+                marked += 1;
+            }
+        }
+        marked
+    }
+
+    /// Mark every note whose nullifier matches one of `nullifiers` as
+    /// reserved by a not-yet-confirmed spend `spending_txid`, without the
+    /// finality of [`Self::mark_spent_by_nullifiers`]. A note already spent
+    /// or already reserved by a different pending spend is left untouched.
+    /// Returns the number of notes marked.
+    pub fn mark_pending_spent_by_nullifiers(
+        &mut self,
+        nullifiers: &[SpentNullifier],
+        spending_txid: &str,
+    ) -> usize {
+        let pending: std::collections::HashSet<&str> =
+            nullifiers.iter().map(|n| n.nullifier.as_str()).collect();
+
+        let mut marked = 0;
+        for note in &mut self.notes {
+            if note.spent_txid.is_none()
+                && note.pending_spent_txid.is_none()
+                && note
+                    .nullifier
+                    .as_deref()
+                    .is_some_and(|nf| pending.contains(nf))
+            {
+                note.pending_spent_txid = Some(spending_txid.to_string());
+                marked += 1;
+            }
+        }
+        marked
+    }
+
+    /// Clear `pending_spent_txid` on every note reserved by one of `txids`,
+    /// for transactions that never confirmed. Returns the number cleared.
+    pub fn clear_pending_spends(&mut self, txids: &[String]) -> usize {
+        let mut cleared = 0;
+        for note in &mut self.notes {
+            if note
+                .pending_spent_txid
+                .as_deref()
+                .is_some_and(|txid| txids.iter().any(|t| t == txid))
+            {
+                note.pending_spent_txid = None;
+                cleared += 1;
+            }
+        }
+        cleared
+    }
+
+    /// Mark every transparent note matching one of `spends` (by
+    /// `txid:output_index`) as spent by `spending_txid`, mined at
+    /// `spending_height`. Returns the number of notes marked.
+    pub fn mark_spent_by_transparent(
+        &mut self,
+        spends: &[TransparentSpend],
+        spending_txid: &str,
+        spending_height: u32,
+    ) -> usize {
+        let mut marked = 0;
+        for note in &mut self.notes {
+            if note.spent_txid.is_none()
+                && spends
+                    .iter()
+                    .any(|s| s.prevout_txid == note.txid && s.prevout_index == note.output_index)
+            {
+                note.spent_txid = Some(spending_txid.to_string());
+                note.spent_height = Some(spending_height);
+                note.pending_spent_txid = None;
+                marked += 1;
+            }
+        }
+        marked
+    }
+
+    /// Unspent, unreserved notes with a positive value - i.e. actually
+    /// available to spend. A note with a `pending_spent_txid` is excluded:
+    /// it isn't confirmed-spent yet, but spending it again while that
+    /// transaction is still in the mempool would risk a double-spend.
+    pub fn unspent_notes(&self) -> Vec<&StoredNote> {
+        self.notes
+            .iter()
+            .filter(|n| n.spent_txid.is_none() && n.pending_spent_txid.is_none() && n.value > 0)
+            .collect()
+    }
+
+    /// Notes belonging to `wallet_id`.
+    pub fn notes_for_wallet<'a>(&'a self, wallet_id: &str) -> Vec<&'a StoredNote> {
+        self.notes.iter().filter(|n| n.wallet_id == wallet_id).collect()
+    }
+
+    /// Total value of unspent notes with a positive value, across all pools.
+    pub fn total_balance(&self) -> u64 {
+        self.unspent_notes().iter().map(|n| n.value).sum()
+    }
+
+    /// Total value of unspent notes with a positive value, broken down by pool.
+    pub fn balance_by_pool(&self) -> HashMap<Pool, u64> {
+        let mut totals = HashMap::new();
+        for note in self.unspent_notes() {
+            *totals.entry(note.pool).or_insert(0) += note.value;
+        }
+        totals
+    }
+}
+
+/// A single wallet as persisted by the caller.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredWallet {
+    /// Stable identifier for this wallet.
+    pub id: String,
+    /// User-friendly name, unique (case-insensitively) within the collection.
+    pub alias: String,
+    /// Network the wallet was generated for.
+    pub network: NetworkKind,
+    /// The 24-word BIP39 seed phrase. Handle with extreme care.
+    pub seed_phrase: String,
+    /// ZIP32 account index used for derivation.
+    pub account_index: u32,
+    /// Unified address containing all receiver types.
+    pub unified_address: String,
+    /// Legacy transparent address (t-addr).
+    pub transparent_address: String,
+    /// Unified Full Viewing Key for watching incoming transactions.
+    pub unified_full_viewing_key: String,
+    /// ISO 8601 timestamp of when the wallet was first recorded.
+    pub created_at: String,
+}
+
+/// A caller-persisted collection of [`StoredWallet`]s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletCollection {
+    pub wallets: Vec<StoredWallet>,
+}
+
+impl WalletCollection {
+    /// Add `wallet`, rejecting a case-insensitive duplicate alias.
+    pub fn add(&mut self, wallet: StoredWallet) -> Result<(), String> {
+        if self.alias_exists(&wallet.alias) {
+            return Err(format!("Wallet alias already exists: {}", wallet.alias));
+        }
+        self.wallets.push(wallet);
+        Ok(())
+    }
+
+    /// Whether `alias` is already in use (case-insensitive).
+    pub fn alias_exists(&self, alias: &str) -> bool {
+        self.wallets
+            .iter()
+            .any(|w| w.alias.eq_ignore_ascii_case(alias))
+    }
+
+    /// Remove the wallet with the given ID. Returns `true` if one was removed.
+    pub fn delete(&mut self, wallet_id: &str) -> bool {
+        let before = self.wallets.len();
+        self.wallets.retain(|w| w.id != wallet_id);
+        self.wallets.len() != before
+    }
+
+    /// Look up a wallet by ID.
+    pub fn get_by_id(&self, wallet_id: &str) -> Option<&StoredWallet> {
+        self.wallets.iter().find(|w| w.id == wallet_id)
+    }
+}
+
+/// Below this many zatoshis, change isn't worth a dedicated output - it's
+/// folded into the fee instead of returned to the wallet.
+pub const DEFAULT_DUST_THRESHOLD_ZAT: u64 = 1000;
+
+/// Whether a note received at `received_height` has reached `min_confirmations`
+/// as of `current_height`. A note with no `received_height` hasn't been seen
+/// in a mined block yet and is never confirmed.
+fn is_note_confirmed(received_height: Option<u32>, min_confirmations: u32, current_height: u32) -> bool {
+    received_height.is_some_and(|height| {
+        u64::from(height) + u64::from(min_confirmations) <= u64::from(current_height) + 1
+    })
+}
+
+/// Result of [`select_spendable_notes`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSelectionResult {
+    /// Whether enough eligible value was found to cover `target_zat + fee_zat`.
+    pub success: bool,
+    /// The notes chosen to fund the payment.
+    pub selected: Vec<StoredNote>,
+    /// Sum of `selected`'s values.
+    pub total_selected: u64,
+    /// Change to return to the wallet (`total_selected - target_zat - fee_zat`),
+    /// already dust-folded - see [`DEFAULT_DUST_THRESHOLD_ZAT`].
+    pub change: u64,
+    /// The fee paid, including any dust folded into it.
+    pub fee: u64,
+    /// How many zatoshis short of `target_zat + fee_zat` the eligible notes
+    /// were, if `success` is false.
+    pub shortfall: Option<u64>,
+    /// Error message, if `success` is false.
+    pub error: Option<String>,
+}
+
+/// Greedily select unspent, confirmed notes to fund a `target_zat` payment.
+///
+/// Mirrors the spendable-note selection and anchor-offset logic of light
+/// wallets like SilentDragonLite: eligible notes are unspent, have a
+/// positive value, are shielded (Orchard/Sapling; set `include_transparent`
+/// to also allow transparent notes), and are confirmed - i.e.
+/// `received_height + min_confirmations <= current_height + 1`. A note with
+/// `received_height: None` hasn't been seen in a mined block yet and is
+/// always ineligible.
+///
+/// Eligible notes are sorted by value descending and accumulated greedily
+/// until the running total covers `target_zat + fee_zat`. If the change
+/// left over would be smaller than [`DEFAULT_DUST_THRESHOLD_ZAT`], it's
+/// folded into the fee instead of being returned as its own output.
+///
+/// Returns `success: false` with the shortfall amount if the eligible notes
+/// don't add up to `target_zat + fee_zat`.
+pub fn select_spendable_notes(
+    collection: &NoteCollection,
+    target_zat: u64,
+    fee_zat: u64,
+    min_confirmations: u32,
+    current_height: u32,
+    include_transparent: bool,
+) -> NoteSelectionResult {
+    if target_zat == 0 {
+        return NoteSelectionResult {
+            success: false,
+            selected: vec![],
+            total_selected: 0,
+            change: 0,
+            fee: fee_zat,
+            shortfall: None,
+            error: Some("Target amount must be greater than zero".to_string()),
+        };
+    }
+
+    let mut eligible: Vec<&StoredNote> = collection
+        .notes
+        .iter()
+        .filter(|n| n.spent_txid.is_none() && n.pending_spent_txid.is_none() && n.value > 0)
+        .filter(|n| include_transparent || n.pool != Pool::Transparent)
+        .filter(|n| is_note_confirmed(n.received_height, min_confirmations, current_height))
+        .collect();
+    eligible.sort_by_key(|n| std::cmp::Reverse(n.value));
+
+    let required = target_zat + fee_zat;
+    let eligible_total: u64 = eligible.iter().map(|n| n.value).sum();
+    if eligible_total < required {
+        return NoteSelectionResult {
+            success: false,
+            selected: vec![],
+            total_selected: 0,
+            change: 0,
+            fee: fee_zat,
+            shortfall: Some(required - eligible_total),
+            error: Some("Insufficient spendable funds".to_string()),
+        };
+    }
+
+    let mut selected = Vec::new();
+    let mut total_selected = 0u64;
+    for note in eligible {
+        selected.push(note.clone());
+        total_selected += note.value;
+        if total_selected >= required {
+            break;
+        }
+    }
+
+    let raw_change = total_selected - required;
+    let (change, fee) = if raw_change < DEFAULT_DUST_THRESHOLD_ZAT {
+        (0, fee_zat + raw_change)
+    } else {
+        (raw_change, fee_zat)
+    };
+
+    NoteSelectionResult {
+        success: true,
+        selected,
+        total_selected,
+        change,
+        fee,
+        shortfall: None,
+        error: None,
+    }
+}
+
+/// Result of [`calculate_balance_detailed`].
+///
+/// Unlike [`crate::types::Pool`]-agnostic totals, `confirmed` counts every
+/// positive-value note that has reached `min_confirmations` regardless of
+/// whether it's since been spent (useful for "total received"), while
+/// `spendable` is the practically-useful figure: confirmed *and* still
+/// unspent. `unconfirmed` is unspent value still waiting to clear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailedBalanceResult {
+    /// Whether the calculation completed successfully.
+    pub success: bool,
+    /// Total confirmed value, spent or not.
+    pub confirmed: u64,
+    /// Total unspent value that hasn't reached `min_confirmations` yet.
+    pub unconfirmed: u64,
+    /// Total confirmed, unspent value - what's actually available to spend.
+    pub spendable: u64,
+    /// `confirmed`, broken down by pool (keyed by [`Pool::as_str`]).
+    pub confirmed_by_pool: HashMap<String, u64>,
+    /// `unconfirmed`, broken down by pool.
+    pub unconfirmed_by_pool: HashMap<String, u64>,
+    /// `spendable`, broken down by pool.
+    pub spendable_by_pool: HashMap<String, u64>,
+    /// Error message, if `success` is false.
+    pub error: Option<String>,
+}
+
+/// Break a note collection's balance down into confirmed, unconfirmed, and
+/// spendable buckets, both in aggregate and per [`Pool`].
+///
+/// A note counts as confirmed once
+/// `received_height + min_confirmations <= current_height + 1`; a note with
+/// no `received_height` is always unconfirmed. Only notes with a positive
+/// value are counted, matching [`NoteCollection::total_balance`].
+pub fn calculate_balance_detailed(
+    collection: &NoteCollection,
+    current_height: u32,
+    min_confirmations: u32,
+) -> DetailedBalanceResult {
+    let mut confirmed_by_pool: HashMap<String, u64> = HashMap::new();
+    let mut unconfirmed_by_pool: HashMap<String, u64> = HashMap::new();
+    let mut spendable_by_pool: HashMap<String, u64> = HashMap::new();
+
+    for note in collection.notes.iter().filter(|n| n.value > 0) {
+        let pool = note.pool.as_str().to_string();
+        let available = note.spent_txid.is_none() && note.pending_spent_txid.is_none();
+        if is_note_confirmed(note.received_height, min_confirmations, current_height) {
+            *confirmed_by_pool.entry(pool.clone()).or_insert(0) += note.value;
+            if available {
+                *spendable_by_pool.entry(pool).or_insert(0) += note.value;
+            }
+        } else if available {
+            *unconfirmed_by_pool.entry(pool).or_insert(0) += note.value;
+        }
+    }
+
+    let sum = |by_pool: &HashMap<String, u64>| by_pool.values().sum();
+
+    DetailedBalanceResult {
+        success: true,
+        confirmed: sum(&confirmed_by_pool),
+        unconfirmed: sum(&unconfirmed_by_pool),
+        spendable: sum(&spendable_by_pool),
+        confirmed_by_pool,
+        unconfirmed_by_pool,
+        spendable_by_pool,
+        error: None,
+    }
+}
+
+/// The deepest reorg this crate will roll back, in blocks. Mirrors the
+/// fixed reorg-safety windows used by light wallets (e.g. zecwallet-lite's
+/// `MAX_REORG`); a rollback request deeper than this is rejected rather
+/// than silently discarding more history than a reorg should ever require.
+pub const MAX_REORG: u32 = 100;
+
+/// Result of [`rollback_notes_to_height`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackResult {
+    /// Whether the rollback was applied.
+    pub success: bool,
+    /// The collection after pruning/reverting, if `success` is true.
+    pub notes: Vec<StoredNote>,
+    /// Number of notes dropped entirely (received above `rollback_height`).
+    pub removed: usize,
+    /// Number of notes un-spent (their recorded spend was above `rollback_height`).
+    pub unspent: usize,
+    /// Error message, if `success` is false.
+    pub error: Option<String>,
+}
+
+/// Undo state above `rollback_height` after a chain reorg.
+///
+/// Any note received above `rollback_height` is dropped entirely - it was
+/// only ever seen on the now-abandoned fork. Any note whose recorded spend
+/// (`spent_height`) is above `rollback_height` has `spent_txid`/
+/// `spent_height` cleared back to `None`, re-entering the unspent set,
+/// since the transaction that spent it may not exist on the new best chain.
+///
+/// The depth of the rollback is measured against the highest height seen
+/// anywhere in the collection (received or spent) as a stand-in for the
+/// current tip; a rollback deeper than [`MAX_REORG`] blocks behind that tip
+/// is rejected; an unaffected collection is returned as a no-op failure
+/// rather than silently discarding more history than a reorg should ever
+/// require.
+pub fn rollback_notes_to_height(collection: &NoteCollection, rollback_height: u32) -> RollbackResult {
+    let tip = collection
+        .notes
+        .iter()
+        .flat_map(|n| [n.received_height, n.spent_height])
+        .flatten()
+        .max()
+        .unwrap_or(rollback_height);
+
+    if tip.saturating_sub(rollback_height) > MAX_REORG {
+        return RollbackResult {
+            success: false,
+            notes: collection.notes.clone(),
+            removed: 0,
+            unspent: 0,
+            error: Some(format!(
+                "Refusing to roll back to height {} - {} blocks behind tip {} exceeds MAX_REORG ({})",
+                rollback_height,
+                tip - rollback_height,
+                tip,
+                MAX_REORG
+            )),
+        };
+    }
+
+    let mut notes = Vec::with_capacity(collection.notes.len());
+    let mut removed = 0;
+    let mut unspent = 0;
+
+    for mut note in collection.notes.clone() {
+        if note.received_height.is_some_and(|height| height > rollback_height) {
+            removed += 1;
+            continue;
+        }
+
+        if note.spent_height.is_some_and(|height| height > rollback_height) {
+            note.spent_txid = None;
+            note.spent_height = None;
+            unspent += 1;
+        }
+
+        notes.push(note);
+    }
+
+    RollbackResult {
+        success: true,
+        notes,
+        removed,
+        unspent,
+        error: None,
+    }
+}
+
+/// Result of [`plan_note_consolidation`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteConsolidationResult {
+    /// Whether at least two eligible dust notes were found to consolidate.
+    pub success: bool,
+    /// The notes chosen to merge into one, smallest value first.
+    pub selected: Vec<StoredNote>,
+    /// Sum of `selected`'s values.
+    pub total_value: u64,
+    /// Number of notes in `selected`.
+    pub input_count: usize,
+    /// Error message, if `success` is false.
+    pub error: Option<String>,
+}
+
+/// Plan a self-send that sweeps small ("dust") notes into one.
+///
+/// Mirrors IOTA wallet's `consolidate_outputs`: eligible notes are unspent,
+/// not pending-reserved, in `pool`, confirmed per the same
+/// `received_height + min_confirmations <= current_height + 1` rule as
+/// [`select_spendable_notes`], and worth at most `value_threshold` zatoshis
+/// (`value_threshold == 0` means "no size limit - consolidate regardless of
+/// value"). Eligible notes are sorted ascending by value so the smallest are
+/// swept first, and up to `max_inputs` of them are selected.
+///
+/// The caller is responsible for building the actual self-send transaction
+/// from the returned notes; this only plans which notes to merge.
+///
+/// Returns `success: false` if fewer than two eligible notes exist, since
+/// there's nothing to consolidate.
+pub fn plan_note_consolidation(
+    collection: &NoteCollection,
+    pool: Pool,
+    max_inputs: u32,
+    min_confirmations: u32,
+    current_height: u32,
+    value_threshold: u64,
+) -> NoteConsolidationResult {
+    let mut eligible: Vec<&StoredNote> = collection
+        .notes
+        .iter()
+        .filter(|n| n.spent_txid.is_none() && n.pending_spent_txid.is_none() && n.value > 0)
+        .filter(|n| n.pool == pool)
+        .filter(|n| value_threshold == 0 || n.value <= value_threshold)
+        .filter(|n| is_note_confirmed(n.received_height, min_confirmations, current_height))
+        .collect();
+
+    if eligible.len() < 2 {
+        return NoteConsolidationResult {
+            success: false,
+            selected: vec![],
+            total_value: 0,
+            input_count: 0,
+            error: Some("Not enough eligible notes to consolidate".to_string()),
+        };
+    }
+
+    eligible.sort_by_key(|n| n.value);
+    eligible.truncate(max_inputs as usize);
+
+    let total_value: u64 = eligible.iter().map(|n| n.value).sum();
+    let selected: Vec<StoredNote> = eligible.into_iter().cloned().collect();
+    let input_count = selected.len();
+
+    NoteConsolidationResult {
+        success: true,
+        selected,
+        total_value,
+        input_count,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(value: u64, pool: Pool, received_height: Option<u32>, spent: bool) -> StoredNote {
+        StoredNote {
+            id: StoredNote::generate_id("tx1", pool, 0),
+            wallet_id: "wallet1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 0,
+            pool,
+            value,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: if spent { Some("tx2".to_string()) } else { None },
+            created_at: String::new(),
+            received_height,
+            spent_height: if spent { received_height } else { None },
+            pending_spent_txid: None,
+        }
+    }
+
+    fn note_with_spent_height(
+        value: u64,
+        pool: Pool,
+        received_height: Option<u32>,
+        spent_height: Option<u32>,
+    ) -> StoredNote {
+        let mut n = note(value, pool, received_height, spent_height.is_some());
+        n.spent_height = spent_height;
+        n
+    }
+
+    #[test]
+    fn test_selects_largest_notes_first() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(1000, Pool::Orchard, Some(100), false),
+                note(5000, Pool::Sapling, Some(100), false),
+                note(2000, Pool::Orchard, Some(100), false),
+            ],
+        };
+
+        // Change would be 5000 - 4000 - 100 = 900, below DEFAULT_DUST_THRESHOLD_ZAT,
+        // so it's folded into the fee rather than returned separately.
+        let result = select_spendable_notes(&collection, 4000, 100, 1, 110, false);
+
+        assert!(result.success);
+        assert_eq!(result.selected.len(), 1);
+        assert_eq!(result.selected[0].value, 5000);
+        assert_eq!(result.total_selected, 5000);
+        assert_eq!(result.change, 0);
+        assert_eq!(result.fee, 100 + 900);
+    }
+
+    #[test]
+    fn test_rejects_zero_target() {
+        let collection = NoteCollection { notes: vec![] };
+
+        let result = select_spendable_notes(&collection, 0, 100, 1, 110, false);
+
+        assert!(!result.success);
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn test_reports_shortfall_when_insufficient() {
+        let collection = NoteCollection {
+            notes: vec![note(1000, Pool::Orchard, Some(100), false)],
+        };
+
+        let result = select_spendable_notes(&collection, 5000, 100, 1, 110, false);
+
+        assert!(!result.success);
+        assert_eq!(result.shortfall, Some(5000 + 100 - 1000));
+    }
+
+    #[test]
+    fn test_ignores_spent_and_unconfirmed_notes() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(10_000, Pool::Orchard, Some(100), true),
+                note(10_000, Pool::Orchard, None, false),
+                note(10_000, Pool::Orchard, Some(105), false),
+                note(3000, Pool::Sapling, Some(100), false),
+            ],
+        };
+
+        // Only the last two notes are unspent and confirmed by height 110
+        // with 1 confirmation required.
+        let result = select_spendable_notes(&collection, 12_000, 0, 1, 110, false);
+
+        assert!(result.success);
+        assert_eq!(result.total_selected, 13_000);
+    }
+
+    #[test]
+    fn test_excludes_transparent_notes_by_default() {
+        let collection = NoteCollection {
+            notes: vec![note(10_000, Pool::Transparent, Some(100), false)],
+        };
+
+        let result = select_spendable_notes(&collection, 5000, 0, 1, 110, false);
+
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_includes_transparent_notes_when_requested() {
+        let collection = NoteCollection {
+            notes: vec![note(10_000, Pool::Transparent, Some(100), false)],
+        };
+
+        let result = select_spendable_notes(&collection, 5000, 0, 1, 110, true);
+
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_dust_change_is_folded_into_fee() {
+        let collection = NoteCollection {
+            notes: vec![note(5000, Pool::Orchard, Some(100), false)],
+        };
+
+        // Change would be 5000 - 4950 - 0 = 50, below DEFAULT_DUST_THRESHOLD_ZAT.
+        let result = select_spendable_notes(&collection, 4950, 0, 1, 110, false);
+
+        assert!(result.success);
+        assert_eq!(result.change, 0);
+        assert_eq!(result.fee, 50);
+    }
+
+    #[test]
+    fn test_add_or_update_replaces_existing_note_by_id() {
+        let mut collection = NoteCollection { notes: vec![] };
+        let original = note(1000, Pool::Orchard, Some(100), false);
+        let id = original.id.clone();
+
+        assert!(collection.add_or_update(original));
+
+        let mut updated = note(2000, Pool::Orchard, Some(100), false);
+        updated.id = id;
+        assert!(!collection.add_or_update(updated));
+
+        assert_eq!(collection.notes.len(), 1);
+        assert_eq!(collection.notes[0].value, 2000);
+    }
+
+    #[test]
+    fn test_calculate_balance_detailed_splits_confirmed_unconfirmed_spendable() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(1000, Pool::Orchard, Some(100), false), // confirmed + spendable
+                note(2000, Pool::Sapling, Some(111), false), // not yet confirmed
+                note(3000, Pool::Orchard, None, false),      // not yet confirmed
+                note(4000, Pool::Orchard, Some(100), true),  // confirmed, but spent
+            ],
+        };
+
+        let result = calculate_balance_detailed(&collection, 110, 1);
+
+        assert!(result.success);
+        assert_eq!(result.confirmed, 1000 + 4000);
+        assert_eq!(result.unconfirmed, 2000 + 3000);
+        assert_eq!(result.spendable, 1000);
+        assert_eq!(result.spendable_by_pool.get("orchard"), Some(&1000));
+        assert_eq!(result.unconfirmed_by_pool.get("sapling"), Some(&2000));
+    }
+
+    #[test]
+    fn test_calculate_balance_detailed_ignores_zero_value_notes() {
+        let collection = NoteCollection {
+            notes: vec![note(0, Pool::Orchard, Some(100), false)],
+        };
+
+        let result = calculate_balance_detailed(&collection, 110, 1);
+
+        assert_eq!(result.confirmed, 0);
+        assert_eq!(result.spendable, 0);
+        assert_eq!(result.unconfirmed, 0);
+    }
+
+    #[test]
+    fn test_rollback_drops_notes_received_above_the_rollback_height() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(1000, Pool::Orchard, Some(100), false),
+                note(2000, Pool::Orchard, Some(105), false),
+            ],
+        };
+
+        let result = rollback_notes_to_height(&collection, 100);
+
+        assert!(result.success);
+        assert_eq!(result.removed, 1);
+        assert_eq!(result.unspent, 0);
+        assert_eq!(result.notes.len(), 1);
+        assert_eq!(result.notes[0].value, 1000);
+    }
+
+    #[test]
+    fn test_rollback_unspends_notes_spent_above_the_rollback_height() {
+        let collection = NoteCollection {
+            notes: vec![note_with_spent_height(1000, Pool::Orchard, Some(90), Some(105))],
+        };
+
+        let result = rollback_notes_to_height(&collection, 100);
+
+        assert!(result.success);
+        assert_eq!(result.removed, 0);
+        assert_eq!(result.unspent, 1);
+        assert!(result.notes[0].spent_txid.is_none());
+        assert!(result.notes[0].spent_height.is_none());
+    }
+
+    #[test]
+    fn test_rollback_leaves_notes_below_the_height_untouched() {
+        let collection = NoteCollection {
+            notes: vec![note_with_spent_height(1000, Pool::Orchard, Some(90), Some(95))],
+        };
+
+        let result = rollback_notes_to_height(&collection, 100);
+
+        assert!(result.success);
+        assert_eq!(result.removed, 0);
+        assert_eq!(result.unspent, 0);
+        assert_eq!(result.notes[0].spent_txid.as_deref(), Some("tx2"));
+    }
+
+    #[test]
+    fn test_rollback_deeper_than_max_reorg_is_rejected() {
+        let collection = NoteCollection {
+            notes: vec![note(1000, Pool::Orchard, Some(1000), false)],
+        };
+
+        let result = rollback_notes_to_height(&collection, 1000 - MAX_REORG - 1);
+
+        assert!(!result.success);
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn test_mark_spent_by_nullifiers_records_spend_height() {
+        let mut collection = NoteCollection {
+            notes: vec![StoredNote {
+                id: StoredNote::generate_id("tx1", Pool::Orchard, 0),
+                wallet_id: "wallet1".to_string(),
+                txid: "tx1".to_string(),
+                output_index: 0,
+                pool: Pool::Orchard,
+                value: 1000,
+                commitment: None,
+                nullifier: Some("nf1".to_string()),
+                memo: None,
+                address: None,
+                spent_txid: None,
+                created_at: String::new(),
+                received_height: Some(90),
+                spent_height: None,
+                pending_spent_txid: None,
+            }],
+        };
+
+        let marked = collection.mark_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "tx2",
+            105,
+        );
+
+        assert_eq!(marked, 1);
+        assert_eq!(collection.notes[0].spent_height, Some(105));
+    }
+
+    fn pending_spendable_note(value: u64, nullifier: &str) -> StoredNote {
+        let mut n = note(value, Pool::Orchard, Some(90), false);
+        n.nullifier = Some(nullifier.to_string());
+        n
+    }
+
+    #[test]
+    fn test_mark_pending_spent_reserves_a_note() {
+        let mut collection = NoteCollection {
+            notes: vec![pending_spendable_note(1000, "nf1")],
+        };
+
+        let marked = collection.mark_pending_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+        );
+
+        assert_eq!(marked, 1);
+        assert_eq!(
+            collection.notes[0].pending_spent_txid.as_deref(),
+            Some("mempool-tx")
+        );
+    }
+
+    #[test]
+    fn test_pending_spent_note_is_excluded_from_unspent_and_balance() {
+        let mut collection = NoteCollection {
+            notes: vec![pending_spendable_note(1000, "nf1")],
+        };
+        collection.mark_pending_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+        );
+
+        assert!(collection.unspent_notes().is_empty());
+        assert_eq!(collection.total_balance(), 0);
+    }
+
+    #[test]
+    fn test_pending_spent_note_is_ineligible_for_selection() {
+        let mut collection = NoteCollection {
+            notes: vec![pending_spendable_note(10_000, "nf1")],
+        };
+        collection.mark_pending_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+        );
+
+        let result = select_spendable_notes(&collection, 5000, 0, 1, 100, false);
+
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_clear_pending_spends_un_reserves_a_dropped_transaction() {
+        let mut collection = NoteCollection {
+            notes: vec![pending_spendable_note(1000, "nf1")],
+        };
+        collection.mark_pending_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+        );
+
+        let cleared = collection.clear_pending_spends(&["mempool-tx".to_string()]);
+
+        assert_eq!(cleared, 1);
+        assert!(collection.notes[0].pending_spent_txid.is_none());
+        assert_eq!(collection.total_balance(), 1000);
+    }
+
+    #[test]
+    fn test_mark_spent_promotes_a_pending_spend() {
+        let mut collection = NoteCollection {
+            notes: vec![pending_spendable_note(1000, "nf1")],
+        };
+        collection.mark_pending_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+        );
+
+        let marked = collection.mark_spent_by_nullifiers(
+            &[SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf1".to_string(),
+            }],
+            "mempool-tx",
+            105,
+        );
+
+        assert_eq!(marked, 1);
+        assert_eq!(collection.notes[0].spent_txid.as_deref(), Some("mempool-tx"));
+        assert!(collection.notes[0].pending_spent_txid.is_none());
+    }
+
+    #[test]
+    fn test_plan_note_consolidation_sweeps_smallest_dust_first() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(500, Pool::Orchard, Some(100), false),
+                note(100, Pool::Orchard, Some(100), false),
+                note(300, Pool::Orchard, Some(100), false),
+                note(10_000, Pool::Orchard, Some(100), false),
+            ],
+        };
+
+        let result = plan_note_consolidation(&collection, Pool::Orchard, 2, 1, 110, 1000);
+
+        assert!(result.success);
+        assert_eq!(result.input_count, 2);
+        assert_eq!(result.selected[0].value, 100);
+        assert_eq!(result.selected[1].value, 300);
+        assert_eq!(result.total_value, 400);
+    }
+
+    #[test]
+    fn test_plan_note_consolidation_zero_threshold_ignores_value() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(1_000_000, Pool::Orchard, Some(100), false),
+                note(2_000_000, Pool::Orchard, Some(100), false),
+            ],
+        };
+
+        let result = plan_note_consolidation(&collection, Pool::Orchard, 10, 1, 110, 0);
+
+        assert!(result.success);
+        assert_eq!(result.input_count, 2);
+        assert_eq!(result.total_value, 3_000_000);
+    }
+
+    #[test]
+    fn test_plan_note_consolidation_fails_with_fewer_than_two_eligible() {
+        let collection = NoteCollection {
+            notes: vec![note(100, Pool::Orchard, Some(100), false)],
+        };
+
+        let result = plan_note_consolidation(&collection, Pool::Orchard, 10, 1, 110, 1000);
+
+        assert!(!result.success);
+        assert!(result.selected.is_empty());
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn test_plan_note_consolidation_ignores_other_pools_spent_and_unconfirmed() {
+        let collection = NoteCollection {
+            notes: vec![
+                note(100, Pool::Sapling, Some(100), false),
+                note(100, Pool::Orchard, Some(100), true),
+                note(100, Pool::Orchard, None, false),
+                note(100, Pool::Orchard, Some(105), false),
+            ],
+        };
+
+        let result = plan_note_consolidation(&collection, Pool::Orchard, 10, 1, 110, 1000);
+
+        assert!(!result.success);
+    }
+}
@@ -0,0 +1,337 @@
+//! Incremental note-commitment tree and witness tracking.
+//!
+//! A spend needs an authentication path ("witness") from its note's
+//! commitment up to some tree anchor the network will recognize. Rather than
+//! recomputing that path from the full commitment history on demand, this
+//! module keeps a running frontier of the Sapling and Orchard note
+//! commitment trees and, for every note of ours, an [`incrementalmerkletree`]
+//! witness that advances in lock-step as new commitments (ours or anyone
+//! else's) are appended in block order.
+//!
+//! This only maintains the in-memory data structures; persisting the
+//! serialized tree/witness state per block (so a later run can resume, and a
+//! detected reorg can roll back) is the caller's responsibility, same as
+//! note/nullifier persistence in [`crate::scanner`].
+
+use incrementalmerkletree::frontier::CommitmentTree as GenericCommitmentTree;
+use incrementalmerkletree::witness::IncrementalWitness as GenericIncrementalWitness;
+use orchard::tree::MerkleHashOrchard;
+use sapling_crypto::{
+    CommitmentTree as SaplingCommitmentTree, IncrementalWitness as SaplingWitness, Node as SaplingNode,
+};
+use thiserror::Error;
+use zcash_primitives::merkle_tree::{
+    read_commitment_tree, read_incremental_witness, write_commitment_tree, write_incremental_witness,
+};
+
+/// Orchard's commitment tree has the same depth as Sapling's (32), but
+/// [`orchard`] doesn't expose its own `CommitmentTree`/`IncrementalWitness`
+/// aliases the way [`sapling_crypto`] does, so they're defined here instead.
+const ORCHARD_DEPTH: u8 = 32;
+
+/// An Orchard note commitment tree, holding only the rightmost path needed to
+/// append further commitments and derive witnesses - not the full tree.
+pub type OrchardCommitmentTree = GenericCommitmentTree<MerkleHashOrchard, ORCHARD_DEPTH>;
+/// An authentication path from one Orchard note commitment to the tree root,
+/// advanced as later commitments are appended.
+pub type OrchardWitness = GenericIncrementalWitness<MerkleHashOrchard, ORCHARD_DEPTH>;
+
+#[derive(Error, Debug)]
+pub enum TreeError {
+    #[error("Sapling commitment tree is full")]
+    SaplingTreeFull,
+    #[error("Orchard commitment tree is full")]
+    OrchardTreeFull,
+    #[error("Invalid commitment bytes")]
+    InvalidCommitment,
+    #[error("Failed to serialize tree state: {0}")]
+    Serialize(String),
+    #[error("Failed to deserialize tree state: {0}")]
+    Deserialize(String),
+}
+
+/// The Sapling and Orchard note commitment trees, plus every witness
+/// currently being tracked for one of our own notes, keyed by the note's
+/// database id.
+#[derive(Clone)]
+pub struct NoteCommitmentTrees {
+    sapling_tree: SaplingCommitmentTree,
+    orchard_tree: OrchardCommitmentTree,
+    sapling_witnesses: Vec<(i64, SaplingWitness)>,
+    orchard_witnesses: Vec<(i64, OrchardWitness)>,
+}
+
+impl Default for NoteCommitmentTrees {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NoteCommitmentTrees {
+    /// An empty pair of trees, as at the start of a chain sync.
+    pub fn new() -> Self {
+        Self {
+            sapling_tree: SaplingCommitmentTree::empty(),
+            orchard_tree: OrchardCommitmentTree::empty(),
+            sapling_witnesses: Vec::new(),
+            orchard_witnesses: Vec::new(),
+        }
+    }
+
+    /// Reconstruct tree state from its serialized parts, e.g. after loading
+    /// the last checkpoint on startup or rolling back to it after a reorg.
+    pub fn from_parts(
+        sapling_tree: SaplingCommitmentTree,
+        orchard_tree: OrchardCommitmentTree,
+        sapling_witnesses: Vec<(i64, SaplingWitness)>,
+        orchard_witnesses: Vec<(i64, OrchardWitness)>,
+    ) -> Self {
+        Self {
+            sapling_tree,
+            orchard_tree,
+            sapling_witnesses,
+            orchard_witnesses,
+        }
+    }
+
+    /// Append a Sapling output commitment (`cmu`) to the tree, advancing
+    /// every witness tracked so far. Call this for every Sapling output in
+    /// block order, whether or not the output is ours.
+    pub fn append_sapling_commitment(&mut self, cmu: SaplingNode) -> Result<(), TreeError> {
+        for (_, witness) in self.sapling_witnesses.iter_mut() {
+            witness.append(cmu).map_err(|_| TreeError::SaplingTreeFull)?;
+        }
+        self.sapling_tree
+            .append(cmu)
+            .map_err(|_| TreeError::SaplingTreeFull)
+    }
+
+    /// Append an Orchard action commitment (`cmx`) to the tree, advancing
+    /// every witness tracked so far. Call this for every Orchard action in
+    /// block order, whether or not the action is ours.
+    pub fn append_orchard_commitment(&mut self, cmx: MerkleHashOrchard) -> Result<(), TreeError> {
+        for (_, witness) in self.orchard_witnesses.iter_mut() {
+            witness.append(cmx).map_err(|_| TreeError::OrchardTreeFull)?;
+        }
+        self.orchard_tree
+            .append(cmx)
+            .map_err(|_| TreeError::OrchardTreeFull)
+    }
+
+    /// Start tracking a witness for one of our own Sapling notes, snapshot
+    /// from the tree's current state. Call this immediately after
+    /// [`Self::append_sapling_commitment`] for that note's own commitment,
+    /// so the witness includes it.
+    pub fn track_sapling_note(&mut self, note_id: i64) -> Result<(), TreeError> {
+        let witness = SaplingWitness::from_tree(self.sapling_tree.clone())
+            .ok_or(TreeError::SaplingTreeFull)?;
+        self.sapling_witnesses.push((note_id, witness));
+        Ok(())
+    }
+
+    /// Start tracking a witness for one of our own Orchard notes, snapshot
+    /// from the tree's current state. Call this immediately after
+    /// [`Self::append_orchard_commitment`] for that action's own commitment,
+    /// so the witness includes it.
+    pub fn track_orchard_note(&mut self, note_id: i64) -> Result<(), TreeError> {
+        let witness = OrchardWitness::from_tree(self.orchard_tree.clone())
+            .ok_or(TreeError::OrchardTreeFull)?;
+        self.orchard_witnesses.push((note_id, witness));
+        Ok(())
+    }
+
+    /// The witness currently tracked for a Sapling note, if any.
+    pub fn sapling_witness(&self, note_id: i64) -> Option<&SaplingWitness> {
+        self.sapling_witnesses
+            .iter()
+            .find(|(id, _)| *id == note_id)
+            .map(|(_, witness)| witness)
+    }
+
+    /// The witness currently tracked for an Orchard note, if any.
+    pub fn orchard_witness(&self, note_id: i64) -> Option<&OrchardWitness> {
+        self.orchard_witnesses
+            .iter()
+            .find(|(id, _)| *id == note_id)
+            .map(|(_, witness)| witness)
+    }
+
+    /// Stop tracking a note's witness, e.g. once the note has been spent and
+    /// no longer needs an up-to-date authentication path.
+    pub fn drop_sapling_witness(&mut self, note_id: i64) {
+        self.sapling_witnesses.retain(|(id, _)| *id != note_id);
+    }
+
+    /// Stop tracking a note's witness, e.g. once the note has been spent and
+    /// no longer needs an up-to-date authentication path.
+    pub fn drop_orchard_witness(&mut self, note_id: i64) {
+        self.orchard_witnesses.retain(|(id, _)| *id != note_id);
+    }
+
+    /// The Sapling tree's current frontier, for checkpointing.
+    pub fn sapling_tree(&self) -> &SaplingCommitmentTree {
+        &self.sapling_tree
+    }
+
+    /// The Orchard tree's current frontier, for checkpointing.
+    pub fn orchard_tree(&self) -> &OrchardCommitmentTree {
+        &self.orchard_tree
+    }
+
+    /// The number of commitments appended to the Sapling tree so far - i.e.
+    /// the absolute position the next appended commitment will occupy. Pass
+    /// this as `scan_compact_block`'s `sapling_start_position` before
+    /// scanning the next block, so its notes get absolute tree positions.
+    pub fn sapling_size(&self) -> u64 {
+        self.sapling_tree.size() as u64
+    }
+
+    /// As [`Self::sapling_size`], but for the Orchard tree.
+    pub fn orchard_size(&self) -> u64 {
+        self.orchard_tree.size() as u64
+    }
+}
+
+/// Serialize a Sapling witness for storage, keyed by note id by the caller.
+pub fn serialize_sapling_witness(witness: &SaplingWitness) -> Result<Vec<u8>, TreeError> {
+    let mut bytes = Vec::new();
+    write_incremental_witness(witness, &mut bytes).map_err(|e| TreeError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Deserialize a Sapling witness previously produced by
+/// [`serialize_sapling_witness`].
+pub fn deserialize_sapling_witness(bytes: &[u8]) -> Result<SaplingWitness, TreeError> {
+    read_incremental_witness(bytes).map_err(|e| TreeError::Deserialize(e.to_string()))
+}
+
+/// Serialize an Orchard witness for storage, keyed by note id by the caller.
+pub fn serialize_orchard_witness(witness: &OrchardWitness) -> Result<Vec<u8>, TreeError> {
+    let mut bytes = Vec::new();
+    write_incremental_witness(witness, &mut bytes).map_err(|e| TreeError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Deserialize an Orchard witness previously produced by
+/// [`serialize_orchard_witness`].
+pub fn deserialize_orchard_witness(bytes: &[u8]) -> Result<OrchardWitness, TreeError> {
+    read_incremental_witness(bytes).map_err(|e| TreeError::Deserialize(e.to_string()))
+}
+
+/// Serialize the Sapling tree's frontier (no witnesses) for checkpointing.
+pub fn serialize_sapling_tree(tree: &SaplingCommitmentTree) -> Result<Vec<u8>, TreeError> {
+    let mut bytes = Vec::new();
+    write_commitment_tree(tree, &mut bytes).map_err(|e| TreeError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Deserialize a Sapling tree frontier previously produced by
+/// [`serialize_sapling_tree`].
+pub fn deserialize_sapling_tree(bytes: &[u8]) -> Result<SaplingCommitmentTree, TreeError> {
+    read_commitment_tree(bytes).map_err(|e| TreeError::Deserialize(e.to_string()))
+}
+
+/// Serialize the Orchard tree's frontier (no witnesses) for checkpointing.
+pub fn serialize_orchard_tree(tree: &OrchardCommitmentTree) -> Result<Vec<u8>, TreeError> {
+    let mut bytes = Vec::new();
+    write_commitment_tree(tree, &mut bytes).map_err(|e| TreeError::Serialize(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Deserialize an Orchard tree frontier previously produced by
+/// [`serialize_orchard_tree`].
+pub fn deserialize_orchard_tree(bytes: &[u8]) -> Result<OrchardCommitmentTree, TreeError> {
+    read_commitment_tree(bytes).map_err(|e| TreeError::Deserialize(e.to_string()))
+}
+
+/// Decode a hex-encoded Sapling commitment (`cmu`), as produced by
+/// [`crate::scanner::scan_compact_block`]'s `ScannedNote::commitment`.
+pub fn sapling_commitment_from_hex(hex_str: &str) -> Result<SaplingNode, TreeError> {
+    let bytes: [u8; 32] = hex::decode(hex_str)
+        .map_err(|_| TreeError::InvalidCommitment)?
+        .try_into()
+        .map_err(|_| TreeError::InvalidCommitment)?;
+    Option::from(SaplingNode::from_bytes(bytes)).ok_or(TreeError::InvalidCommitment)
+}
+
+/// Decode a hex-encoded Orchard commitment (`cmx`), as produced by
+/// [`crate::scanner::scan_compact_block`]'s `ScannedNote::commitment`.
+pub fn orchard_commitment_from_hex(hex_str: &str) -> Result<MerkleHashOrchard, TreeError> {
+    let bytes: [u8; 32] = hex::decode(hex_str)
+        .map_err(|_| TreeError::InvalidCommitment)?
+        .try_into()
+        .map_err(|_| TreeError::InvalidCommitment)?;
+    Option::from(MerkleHashOrchard::from_bytes(&bytes)).ok_or(TreeError::InvalidCommitment)
+}
+
+/// The tree root a Sapling witness currently authenticates to, hex-encoded.
+pub fn sapling_anchor_hex(witness: &SaplingWitness) -> String {
+    hex::encode(witness.root().to_bytes())
+}
+
+/// The tree root an Orchard witness currently authenticates to, hex-encoded.
+pub fn orchard_anchor_hex(witness: &OrchardWitness) -> String {
+    hex::encode(witness.root().to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use incrementalmerkletree::Hashable;
+
+    fn leaf(byte: u8) -> MerkleHashOrchard {
+        let mut repr = [0u8; 32];
+        repr[0] = byte;
+        // Not every byte string is a valid field element; fall back to the
+        // empty leaf for bytes that aren't, which is good enough to exercise
+        // tree/witness bookkeeping in these tests.
+        Option::from(MerkleHashOrchard::from_bytes(&repr))
+            .unwrap_or_else(MerkleHashOrchard::empty_leaf)
+    }
+
+    #[test]
+    fn test_witness_advances_with_later_commitments() {
+        let mut trees = NoteCommitmentTrees::new();
+
+        trees.append_orchard_commitment(leaf(1)).unwrap();
+        trees.append_orchard_commitment(leaf(2)).unwrap();
+        trees.track_orchard_note(42).unwrap();
+
+        let root_before = trees.orchard_witness(42).unwrap().root();
+
+        trees.append_orchard_commitment(leaf(3)).unwrap();
+        trees.append_orchard_commitment(leaf(4)).unwrap();
+
+        let root_after = trees.orchard_witness(42).unwrap().root();
+        assert_ne!(
+            root_before, root_after,
+            "witness root should change as later commitments are appended"
+        );
+    }
+
+    #[test]
+    fn test_witness_round_trips_through_serialization() {
+        let mut trees = NoteCommitmentTrees::new();
+        trees.append_orchard_commitment(leaf(1)).unwrap();
+        trees.track_orchard_note(1).unwrap();
+        trees.append_orchard_commitment(leaf(2)).unwrap();
+
+        let witness = trees.orchard_witness(1).unwrap();
+        let bytes = serialize_orchard_witness(witness).unwrap();
+        let decoded = deserialize_orchard_witness(&bytes).unwrap();
+
+        assert_eq!(witness.root(), decoded.root());
+        assert_eq!(witness.witnessed_position(), decoded.witnessed_position());
+    }
+
+    #[test]
+    fn test_drop_witness_stops_tracking() {
+        let mut trees = NoteCommitmentTrees::new();
+        trees.append_orchard_commitment(leaf(1)).unwrap();
+        trees.track_orchard_note(7).unwrap();
+        assert!(trees.orchard_witness(7).is_some());
+
+        trees.drop_orchard_witness(7);
+        assert!(trees.orchard_witness(7).is_none());
+    }
+}
@@ -4,7 +4,7 @@
 //! representing transactions, viewing keys, and wallet data.
 
 use serde::{Deserialize, Serialize};
-use zcash_protocol::consensus::Network;
+use zcash_protocol::consensus::{Network, NetworkType};
 
 /// Network identifier for Zcash operations.
 ///
@@ -52,6 +52,16 @@ impl From<Network> for NetworkKind {
     }
 }
 
+impl From<NetworkType> for NetworkKind {
+    fn from(network: NetworkType) -> Self {
+        match network {
+            NetworkType::Main => NetworkKind::Mainnet,
+            NetworkType::Test => NetworkKind::Testnet,
+            NetworkType::Regtest => NetworkKind::Regtest,
+        }
+    }
+}
+
 impl std::fmt::Display for NetworkKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
@@ -91,18 +101,49 @@ impl<'de> Deserialize<'de> for NetworkKind {
 pub struct DecryptedTransaction {
     /// The transaction identifier (hash) as a hex string.
     pub txid: String,
-    /// Decrypted Sapling shielded outputs.
+    /// Decrypted Sapling shielded outputs received by the wallet (`Incoming`
+    /// or `WalletInternal`).
     pub sapling_outputs: Vec<DecryptedSaplingOutput>,
-    /// Decrypted Orchard shielded actions.
+    /// Decrypted Orchard shielded actions received by the wallet (`Incoming`
+    /// or `WalletInternal`).
     pub orchard_actions: Vec<DecryptedOrchardAction>,
     /// Transparent inputs spending previous outputs.
     pub transparent_inputs: Vec<TransparentInput>,
     /// Transparent outputs creating new UTXOs.
     pub transparent_outputs: Vec<TransparentOutput>,
+    /// Shielded outputs the wallet itself sent, recovered with the outgoing
+    /// viewing key (`Outgoing`). Reconstructing these requires no spend
+    /// authority - only the OVK bundled with a full viewing key - so they're
+    /// visible to a watch-only UFVK.
+    pub sent_outputs: Vec<DecryptedSentOutput>,
     /// Transaction fee in zatoshis, if calculable.
     pub fee: Option<u64>,
 }
 
+/// A shielded output the wallet sent, recovered via the outgoing viewing key.
+///
+/// Unlike `DecryptedSaplingOutput`/`DecryptedOrchardAction`, the wallet holds
+/// no spend authority over these - they're payments made to someone else,
+/// visible here purely for transaction history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecryptedSentOutput {
+    /// Zero-based index of this output within its bundle (Sapling outputs
+    /// and Orchard actions are indexed independently).
+    pub index: usize,
+    /// Which shielded pool this output belongs to.
+    pub pool: Pool,
+    /// Note value in zatoshis.
+    pub value: u64,
+    /// Memo field contents, interpreted per ZIP 302.
+    pub memo: crate::memo::Memo,
+    /// The recipient's address.
+    pub recipient_address: Option<String>,
+    /// Note commitment (cmu/cmx) as a hex string, so the UI can match this
+    /// sent note against its later appearance in a received listing (e.g. a
+    /// self-sent transaction) or an on-chain explorer.
+    pub note_commitment: String,
+}
+
 /// A decrypted Sapling shielded output.
 ///
 /// Represents a note received in the Sapling shielded pool. The value and memo
@@ -113,14 +154,17 @@ pub struct DecryptedSaplingOutput {
     pub index: usize,
     /// Note value in zatoshis (1 ZEC = 100,000,000 zatoshis). Zero if not decrypted.
     pub value: u64,
-    /// Memo field contents. Empty or "(encrypted)" if not decrypted.
-    pub memo: String,
+    /// Memo field contents, interpreted per ZIP 302. `Memo::Empty` if not decrypted.
+    pub memo: crate::memo::Memo,
     /// Recipient address, if available from decryption.
     pub address: Option<String>,
     /// Note commitment (cmu) as a hex string. Used to identify the note on-chain.
     pub note_commitment: String,
     /// Nullifier as a hex string. Used to detect when this note is spent.
     pub nullifier: Option<String>,
+    /// Which key scope decrypted this output, if any. `None` if the output
+    /// couldn't be decrypted with the provided viewing key.
+    pub transfer_type: Option<TransferType>,
 }
 
 /// A decrypted Orchard shielded action.
@@ -133,14 +177,18 @@ pub struct DecryptedOrchardAction {
     pub index: usize,
     /// Note value in zatoshis. Zero if not decrypted.
     pub value: u64,
-    /// Memo field contents. Empty or "(encrypted)" if not decrypted.
-    pub memo: String,
+    /// Memo field contents, interpreted per ZIP 302. `Memo::Empty` if not decrypted.
+    pub memo: crate::memo::Memo,
     /// Recipient address, if available from decryption.
     pub address: Option<String>,
     /// Note commitment (cmx) as a hex string.
     pub note_commitment: String,
-    /// Nullifier as a hex string. Present for all Orchard actions.
+    /// Nullifier as a hex string. Present when the note was decrypted with
+    /// the external or internal key (not when recovered via OVK).
     pub nullifier: Option<String>,
+    /// Which key scope decrypted this output, if any. `None` if the output
+    /// couldn't be decrypted with the provided viewing key.
+    pub transfer_type: Option<TransferType>,
 }
 
 /// A transparent transaction input.
@@ -191,6 +239,34 @@ pub struct ViewingKeyInfo {
     pub error: Option<String>,
 }
 
+/// Information about a parsed recipient address.
+///
+/// Returned by `parse_address` to indicate whether an address is valid for
+/// a given network and which pools it can receive funds into, so a caller
+/// can warn before sending, e.g., shielded funds to a transparent-only
+/// address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressInfo {
+    /// Whether the address was successfully parsed for the requested network.
+    pub valid: bool,
+    /// Type of address: "unified", "sapling", "transparent-p2pkh", or
+    /// "transparent-p2sh". Empty if parsing failed.
+    pub kind: String,
+    /// Which pools this address can receive funds into.
+    pub receivers: Vec<Pool>,
+    /// Network the address was validated against.
+    pub network: Option<NetworkKind>,
+    /// Error message if parsing failed.
+    pub error: Option<String>,
+}
+
+impl AddressInfo {
+    /// Whether this address has a receiver for the given pool.
+    pub fn has_receiver_of_type(&self, pool: Pool) -> bool {
+        self.receivers.contains(&pool)
+    }
+}
+
 /// Result of a transaction decryption operation.
 ///
 /// Wraps the decryption result with success/error status for easy
@@ -264,6 +340,71 @@ impl<'de> Deserialize<'de> for Pool {
     }
 }
 
+/// Classification of how a shielded note entered the wallet's view.
+///
+/// The scanner trial-decrypts Orchard outputs with the wallet's external
+/// (incoming) key, its internal (change) key, and its outgoing viewing key,
+/// in that order. Which key succeeded determines this classification: a note
+/// found via the external key was received from someone else, one found via
+/// the internal key is change or a self-send, and one recovered via the
+/// outgoing viewing key is a payment the wallet itself sent (visible for
+/// record-keeping, but not spendable - the wallet holds no spend authority
+/// over money it sent to another party).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferType {
+    /// Received from another party, decrypted with the external viewing key.
+    Incoming,
+    /// Change or a self-send, decrypted with the internal viewing key.
+    WalletInternal,
+    /// A note the wallet sent to someone else, recovered via the outgoing
+    /// viewing key.
+    Outgoing,
+}
+
+impl TransferType {
+    /// Get the string representation of the transfer type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferType::Incoming => "incoming",
+            TransferType::WalletInternal => "wallet_internal",
+            TransferType::Outgoing => "outgoing",
+        }
+    }
+}
+
+impl std::fmt::Display for TransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for TransferType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TransferType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.to_lowercase().as_str() {
+            "incoming" => Ok(TransferType::Incoming),
+            "wallet_internal" => Ok(TransferType::WalletInternal),
+            "outgoing" => Ok(TransferType::Outgoing),
+            _ => Err(serde::de::Error::custom(format!(
+                "unknown transfer type: {}",
+                s
+            ))),
+        }
+    }
+}
+
 /// A note/output found during transaction scanning.
 ///
 /// Represents either a shielded note (Sapling or Orchard) discovered by trial
@@ -286,11 +427,34 @@ pub struct ScannedNote {
     /// Nullifier for shielded notes, used to detect when it's spent.
     /// None for transparent outputs (they use input references instead).
     pub nullifier: Option<String>,
-    /// Memo field contents if decrypted and valid UTF-8.
-    /// None for transparent outputs.
-    pub memo: Option<String>,
+    /// Memo field contents, interpreted per ZIP 302. `None` for transparent
+    /// outputs; `Some(Memo::Empty)` for a decrypted shielded note whose memo
+    /// field was empty.
+    pub memo: Option<crate::memo::Memo>,
+    /// If `memo` is a ZIP 321 `zcash:` payment request URI, its first
+    /// parsed payment - lets the wallet recognize reply-to/payment-request
+    /// memos instead of treating them as plain text. `None` otherwise.
+    pub payment_request: Option<crate::zip321::Payment>,
     /// Recipient address if available.
     pub address: Option<String>,
+    /// How this note was discovered (incoming, our own change, or an
+    /// outgoing payment recovered via the outgoing viewing key). `None` for
+    /// transparent outputs and for shielded notes that couldn't be decrypted
+    /// or recovered by any available key.
+    pub transfer_type: Option<TransferType>,
+    /// This note's absolute position (leaf index) in the pool's global
+    /// commitment tree, used to build an incremental witness/anchor at spend
+    /// time. `None` for transparent outputs and when the caller didn't
+    /// provide the tree's starting size to `scan_transaction`/
+    /// `scan_compact_block`.
+    pub position: Option<u64>,
+    /// The note's `Rho` value, hex-encoded. Orchard-only: needed, along with
+    /// `rseed`, to reconstruct an `orchard::Note` for witnessing at spend
+    /// time, since it isn't recoverable from `commitment`/`value`/`address`
+    /// alone.
+    pub rho: Option<String>,
+    /// The note's random seed, hex-encoded. Orchard-only; see `rho`.
+    pub rseed: Option<String>,
 }
 
 /// A nullifier found in a transaction, indicating a spent shielded note.
@@ -330,6 +494,24 @@ pub struct ScannedTransparentOutput {
     pub address: Option<String>,
 }
 
+/// An unspent transparent output tracked for the wallet's transparent
+/// address, as reported by an address-indexed UTXO query (e.g. a
+/// `getaddressutxos`/`listunspent` equivalent).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransparentUtxo {
+    /// Txid of the transaction that created this output, as a hex string in
+    /// the usual (reversed, display) byte order.
+    pub txid: String,
+    /// Output index within that transaction.
+    pub vout: u32,
+    /// The output's scriptPubKey, as a hex string.
+    pub script_pubkey: String,
+    /// Value of the output, in zatoshis.
+    pub value: u64,
+    /// Height of the block that mined this output, if known.
+    pub height: Option<u32>,
+}
+
 /// Result of scanning a transaction for notes and nullifiers.
 ///
 /// Contains all notes/outputs belonging to the wallet found in the transaction,
@@ -364,6 +546,141 @@ pub struct ScanTransactionResult {
     pub error: Option<String>,
 }
 
+/// Result of streaming a range of compact blocks through a [`crate::block_scanner::BlockScanner`].
+///
+/// Wraps the per-block results with success/error status for JavaScript
+/// interop, same as [`ScanTransactionResult`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCompactBlocksResult {
+    /// Whether every block scanned without errors.
+    pub success: bool,
+    /// One result per block, in the order scanned, if successful.
+    pub result: Option<Vec<crate::block_scanner::BlockScanResult>>,
+    /// Error message if scanning failed.
+    pub error: Option<String>,
+}
+
+/// Structural summary of a transaction, visible without any viewing key.
+///
+/// Unlike `ScanResult`, this performs no trial decryption - it only reports
+/// what's publicly visible in the transaction's bundles, for debugging and
+/// auditing arbitrary raw transactions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxInspection {
+    /// Transaction ID as a hex string.
+    pub txid: String,
+    /// Name of the consensus branch ID that successfully deserialized the
+    /// transaction (e.g. "nu6").
+    pub branch_id: String,
+    /// Block height after which the transaction is no longer valid. Zero
+    /// means no expiry.
+    pub expiry_height: u32,
+    /// Number of transparent inputs.
+    pub transparent_input_count: usize,
+    /// Number of transparent outputs.
+    pub transparent_output_count: usize,
+    /// Total value of transparent outputs, in zatoshis. Transparent input
+    /// values aren't recoverable from the transaction alone, so this is a
+    /// total rather than a net balance.
+    pub transparent_output_total: u64,
+    /// Number of Sapling spends.
+    pub sapling_spend_count: usize,
+    /// Number of Sapling outputs.
+    pub sapling_output_count: usize,
+    /// Net value balance of the Sapling pool, in zatoshis. Positive means
+    /// value is leaving the Sapling pool (e.g. to transparent outputs or fees).
+    pub sapling_value_balance: i64,
+    /// Number of Orchard actions.
+    pub orchard_action_count: usize,
+    /// Net value balance of the Orchard pool, in zatoshis. Positive means
+    /// value is leaving the Orchard pool.
+    pub orchard_value_balance: i64,
+    /// Nullifiers revealed by shielded spends/actions.
+    pub spent_nullifiers: Vec<SpentNullifier>,
+    /// Sapling output commitments (cmu), as hex strings.
+    pub sapling_commitments: Vec<String>,
+    /// Orchard action commitments (cmx), as hex strings.
+    pub orchard_commitments: Vec<String>,
+}
+
+/// What kind of Zcash datum [`crate::inspect::inspect`] auto-detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataKind {
+    /// A raw transaction, hex-encoded.
+    Transaction,
+    /// A unified, Sapling, or transparent address.
+    Address,
+    /// A unified or legacy Sapling viewing key.
+    ViewingKey,
+    /// Didn't match any recognized format.
+    Unrecognized,
+}
+
+impl DataKind {
+    /// Get the string representation of the data kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataKind::Transaction => "transaction",
+            DataKind::Address => "address",
+            DataKind::ViewingKey => "viewing-key",
+            DataKind::Unrecognized => "unrecognized",
+        }
+    }
+}
+
+impl std::fmt::Display for DataKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for DataKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DataKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "transaction" => Ok(DataKind::Transaction),
+            "address" => Ok(DataKind::Address),
+            "viewing-key" => Ok(DataKind::ViewingKey),
+            "unrecognized" => Ok(DataKind::Unrecognized),
+            _ => Err(serde::de::Error::custom(format!("unknown data kind: {}", s))),
+        }
+    }
+}
+
+/// Result of auto-detecting and structurally decoding an arbitrary,
+/// user-pasted Zcash datum.
+///
+/// Exactly one of `transaction`, `address`, or `viewing_key` is populated,
+/// matching `kind` - a caller that already knows what it's looking at should
+/// reach for [`inspect_transaction`](crate::inspect_transaction),
+/// [`parse_address`](crate::parse_address), or
+/// [`parse_viewing_key`](crate::parse_viewing_key) directly instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataInspection {
+    /// What kind of datum this was detected as.
+    pub kind: DataKind,
+    /// Populated when `kind` is `Transaction`.
+    pub transaction: Option<TxInspection>,
+    /// Populated when `kind` is `Address`.
+    pub address: Option<AddressInfo>,
+    /// Populated when `kind` is `ViewingKey`.
+    pub viewing_key: Option<ViewingKeyInfo>,
+    /// Populated when `kind` is `Unrecognized`.
+    pub error: Option<String>,
+}
+
 // ============================================================================
 // Wallet Types
 // ============================================================================
@@ -382,8 +699,8 @@ pub struct WalletResult {
     pub network: NetworkKind,
     /// BIP32/ZIP32 account index used for derivation.
     pub account_index: u32,
-    /// Address/diversifier index used for derivation.
-    pub address_index: u32,
+    /// ZIP32 diversifier index used for derivation (0..2^88).
+    pub address_index: u128,
     /// Unified address containing all receiver types.
     pub unified_address: Option<String>,
     /// Legacy transparent address (t-addr).
@@ -393,3 +710,46 @@ pub struct WalletResult {
     /// Error message if the operation failed.
     pub error: Option<String>,
 }
+
+/// Result of a mnemonic inspection operation.
+///
+/// Contains the recovered entropy and fingerprints, without any derived
+/// addresses or viewing keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectionResult {
+    /// Whether the inspection completed successfully.
+    pub success: bool,
+    /// The raw entropy recovered from the mnemonic phrase, hex-encoded.
+    pub entropy: Option<String>,
+    /// The number of words in the mnemonic phrase.
+    pub word_count: Option<usize>,
+    /// The BIP39 wordlist language the phrase was parsed as.
+    pub language: Option<String>,
+    /// The ZIP 32 seed fingerprint of the derived seed, hex-encoded.
+    pub seed_fingerprint: Option<String>,
+    /// A fingerprint of the account's Unified Full Viewing Key, hex-encoded.
+    pub ufvk_fingerprint: Option<String>,
+    /// Error message if the operation failed.
+    pub error: Option<String>,
+}
+
+/// Result of encrypting a wallet for at-rest storage.
+///
+/// Contains the self-describing encrypted blob, which is safe to persist
+/// in browser storage - unlike a `WalletResult`, it never carries the
+/// plaintext seed phrase or viewing key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedWalletResult {
+    /// Whether encryption completed successfully.
+    pub success: bool,
+    /// Random salt used to derive the encryption key, hex-encoded.
+    pub salt: Option<String>,
+    /// Random nonce used to seal the ciphertext, hex-encoded.
+    pub nonce: Option<String>,
+    /// The encrypted, serialized wallet, hex-encoded.
+    pub ciphertext: Option<String>,
+    /// The KDF parameters used to derive the encryption key.
+    pub kdf_params: Option<crate::wallet::KdfParams>,
+    /// Error message if encryption failed.
+    pub error: Option<String>,
+}
@@ -4,17 +4,46 @@
 //! from BIP39 seed phrases. Supports both mainnet and testnet with
 //! BIP32/ZIP32 address hierarchy derivation.
 
+use argon2::{Algorithm, Argon2, Params, Version};
 use bip39::{Language, Mnemonic};
+use blake2b_simd::Params as Blake2bParams;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use zcash_keys::address::Address;
 use zcash_keys::encoding::AddressCodec;
-use zcash_keys::keys::{UnifiedAddressRequest, UnifiedSpendingKey};
+use zcash_keys::keys::{ReceiverRequirement, UnifiedAddressRequest, UnifiedSpendingKey};
 use zcash_protocol::consensus::Network;
 use zcash_transparent::keys::{IncomingViewingKey, NonHardenedChildIndex};
+use zip32::fingerprint::SeedFingerprint;
 use zip32::{AccountId, DiversifierIndex};
 
+use crate::block_scanner::AccountBirthday;
 use crate::types::NetworkKind;
 
+/// Personalization for the wallet-core UFVK fingerprint.
+///
+/// ZIP 32 does not define a standard fingerprint for viewing keys, so this
+/// is our own BLAKE2b-256 personalization, computed over the key's string
+/// encoding. It exists purely as a cheap, local way to compare accounts
+/// without encoding the full UFVK.
+const UFVK_FINGERPRINT_PERSONALIZATION: &[u8; 16] = b"ZWWallet_UFVK_FP";
+
+/// Length, in bytes, of the random salt used to derive the encryption key
+/// from a passphrase.
+const ENCRYPTED_WALLET_SALT_LEN: usize = 16;
+
+/// Argon2id parameters used to derive the encryption key from a passphrase.
+///
+/// These match the Argon2 crate's own "recommended" minimums for
+/// interactive use rather than inventing new numbers, since the wallet is
+/// unlocked on every browser session.
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_ITERATIONS: u32 = 2;
+const ARGON2_PARALLELISM: u32 = 1;
+
 /// Errors that can occur during wallet operations.
 #[derive(Error, Debug)]
 pub enum WalletError {
@@ -32,6 +61,12 @@ pub enum WalletError {
 
     #[error("Invalid account index: {0}")]
     InvalidAccountIndex(String),
+
+    #[error("Failed to decrypt wallet: {0}")]
+    Decryption(String),
+
+    #[error("Not a recognized address for this account: {0}")]
+    AddressNotOwned(String),
 }
 
 /// Information about a derived wallet.
@@ -43,14 +78,82 @@ pub struct WalletInfo {
     pub network: NetworkKind,
     /// The account index (BIP32 level 3, ZIP32 account).
     pub account_index: u32,
-    /// The address index (diversifier index for shielded addresses).
-    pub address_index: u32,
+    /// The address index (ZIP32 diversifier index for shielded addresses, 0..2^88).
+    pub address_index: u128,
     /// The unified address containing all receiver types.
     pub unified_address: String,
+    /// The receiver types actually present in `unified_address`.
+    pub receivers: ReceiverSelection,
     /// The transparent (t-addr) address.
     pub transparent_address: Option<String>,
     /// The Unified Full Viewing Key.
     pub unified_full_viewing_key: String,
+    /// The account's birthday, if one was given - blocks below its height
+    /// need no trial decryption when scanning, since the account is
+    /// known to have no activity there.
+    pub birthday: Option<AccountBirthday>,
+}
+
+/// Which receiver types to include in a derived unified address.
+///
+/// Each `true` field is required to be present in the generated address -
+/// derivation fails with [`WalletError::AddressGeneration`] if the account
+/// cannot produce a receiver of that type (e.g. a transparent receiver
+/// requested at a diversifier index outside the transparent child-index
+/// range). A `false` field omits that receiver type entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiverSelection {
+    /// Include an Orchard receiver.
+    pub orchard: bool,
+    /// Include a Sapling receiver.
+    pub sapling: bool,
+    /// Include a transparent (p2pkh) receiver.
+    pub transparent: bool,
+}
+
+impl Default for ReceiverSelection {
+    /// Requests every receiver type, matching the previous hardcoded behavior.
+    fn default() -> Self {
+        Self {
+            orchard: true,
+            sapling: true,
+            transparent: true,
+        }
+    }
+}
+
+impl ReceiverSelection {
+    fn requirement(include: bool) -> ReceiverRequirement {
+        if include {
+            ReceiverRequirement::Require
+        } else {
+            ReceiverRequirement::Omit
+        }
+    }
+
+    /// Convert this selection into a [`UnifiedAddressRequest`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WalletError::AddressGeneration`] if neither `orchard` nor
+    /// `sapling` is selected, since a unified address must contain at least
+    /// one shielded receiver.
+    fn to_request(self) -> Result<UnifiedAddressRequest, WalletError> {
+        UnifiedAddressRequest::custom(
+            Self::requirement(self.orchard),
+            Self::requirement(self.sapling),
+            Self::requirement(self.transparent),
+        )
+        .map_err(|e| WalletError::AddressGeneration(e.to_string()))
+    }
+
+    fn from_address(ua: &zcash_keys::address::UnifiedAddress) -> Self {
+        Self {
+            orchard: ua.has_orchard(),
+            sapling: ua.has_sapling(),
+            transparent: ua.has_transparent(),
+        }
+    }
 }
 
 /// Generate a new wallet with a random seed phrase.
@@ -60,24 +163,44 @@ pub struct WalletInfo {
 /// * `entropy` - 32 bytes of random entropy for generating the mnemonic.
 /// * `network` - The network to use (MainNetwork or TestNetwork).
 /// * `account_index` - The account index (BIP32 level 3, default 0).
-/// * `address_index` - The address/diversifier index (default 0).
+/// * `address_index` - The ZIP32 diversifier index (0..2^88, default 0).
+/// * `receivers` - Which receiver types the unified address must contain.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). A different
+///   passphrase with the same seed phrase produces an entirely different
+///   wallet. `None` or an empty string reproduces the no-passphrase wallet.
+/// * `birthday_height` - The current chain tip, if known, recorded as the
+///   account's birthday - a freshly generated wallet has no activity before
+///   the block it was created at. `None` omits the birthday, so scanning
+///   must start from genesis.
 ///
 /// # Returns
 ///
 /// A `WalletInfo` containing the seed phrase and derived addresses.
+#[allow(clippy::too_many_arguments)]
 pub fn generate_wallet(
     entropy: &[u8; 32],
     network: Network,
     account_index: u32,
-    address_index: u32,
+    address_index: u128,
+    receivers: ReceiverSelection,
+    passphrase: Option<&str>,
+    birthday_height: Option<u32>,
 ) -> Result<WalletInfo, WalletError> {
     let mnemonic = Mnemonic::from_entropy_in(Language::English, entropy)
         .map_err(|e| WalletError::MnemonicGeneration(e.to_string()))?;
 
     let seed_phrase = mnemonic.to_string();
-    let seed = mnemonic.to_seed("");
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
 
-    derive_wallet(&seed, seed_phrase, network, account_index, address_index)
+    derive_wallet(
+        &seed,
+        seed_phrase,
+        network,
+        account_index,
+        address_index,
+        receivers,
+        birthday_height,
+    )
 }
 
 /// Restore a wallet from an existing seed phrase.
@@ -87,27 +210,41 @@ pub fn generate_wallet(
 /// * `seed_phrase` - A valid 24-word BIP39 mnemonic.
 /// * `network` - The network to use (MainNetwork or TestNetwork).
 /// * `account_index` - The account index (BIP32 level 3, default 0).
-/// * `address_index` - The address/diversifier index (default 0).
+/// * `address_index` - The ZIP32 diversifier index (0..2^88, default 0).
+/// * `receivers` - Which receiver types the unified address must contain.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). A different
+///   passphrase with the same seed phrase produces an entirely different
+///   wallet. `None` or an empty string reproduces the no-passphrase wallet.
+/// * `birthday_height` - The height before which the account is known to
+///   have no activity, recorded as its birthday so scanning can skip trial
+///   decryption below it. `None` omits the birthday, so scanning must start
+///   from genesis - the safe default for a phrase of unknown age.
 ///
 /// # Returns
 ///
 /// A `WalletInfo` containing the seed phrase and derived addresses.
+#[allow(clippy::too_many_arguments)]
 pub fn restore_wallet(
     seed_phrase: &str,
     network: Network,
     account_index: u32,
-    address_index: u32,
+    address_index: u128,
+    receivers: ReceiverSelection,
+    passphrase: Option<&str>,
+    birthday_height: Option<u32>,
 ) -> Result<WalletInfo, WalletError> {
     let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
         .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
 
-    let seed = mnemonic.to_seed("");
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
     derive_wallet(
         &seed,
         mnemonic.to_string(),
         network,
         account_index,
         address_index,
+        receivers,
+        birthday_height,
     )
 }
 
@@ -119,17 +256,23 @@ pub fn restore_wallet(
 /// * `seed_phrase` - The original seed phrase string.
 /// * `network` - The network to derive addresses for.
 /// * `account_index` - The account index (BIP32 level 3).
-/// * `address_index` - The address/diversifier index.
+/// * `address_index` - The ZIP32 diversifier index (0..2^88).
+/// * `receivers` - Which receiver types the unified address must contain.
+/// * `birthday_height` - Recorded on the returned `WalletInfo` as-is, with
+///   no known tree frontier - see [`generate_wallet`]/[`restore_wallet`].
 ///
 /// # Returns
 ///
 /// A `WalletInfo` containing the seed phrase and derived addresses.
+#[allow(clippy::too_many_arguments)]
 pub fn derive_wallet(
     seed: &[u8],
     seed_phrase: String,
     network: Network,
     account_index: u32,
-    address_index: u32,
+    address_index: u128,
+    receivers: ReceiverSelection,
+    birthday_height: Option<u32>,
 ) -> Result<WalletInfo, WalletError> {
     // Convert account index to AccountId
     let account = AccountId::try_from(account_index).map_err(|_| {
@@ -147,34 +290,42 @@ pub fn derive_wallet(
     let ufvk = usk.to_unified_full_viewing_key();
     let ufvk_encoded = ufvk.encode(&network);
 
-    // Create diversifier index from address_index
-    let diversifier_index = DiversifierIndex::from(address_index);
+    // Create diversifier index from address_index, validating it fits the
+    // 88-bit ZIP32 diversifier-index range.
+    let diversifier_index = DiversifierIndex::try_from(address_index).map_err(|_| {
+        WalletError::InvalidAccountIndex(format!(
+            "Address index {} exceeds the 88-bit diversifier index range",
+            address_index
+        ))
+    })?;
 
-    // Generate unified address at the specified diversifier index
-    // Use find_address to find a valid diversifier starting from the given index
+    // Generate unified address at the specified diversifier index.
+    // Use find_address to find a valid diversifier starting from the given index.
     let (ua, actual_index) = ufvk
-        .find_address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys)
+        .find_address(diversifier_index, receivers.to_request()?)
         .map_err(|e| WalletError::AddressGeneration(format!("{:?}", e)))?;
     let ua_encoded = ua.encode(&network);
+    let actual_receivers = ReceiverSelection::from_address(&ua);
 
-    // Convert the actual diversifier index back to u32 for storage
-    // Use try_from since DiversifierIndex could theoretically exceed u32::MAX
-    let actual_address_index: u32 = u32::try_from(actual_index).unwrap_or(address_index);
+    // The diversifier index space is 88 bits, so it always fits in a u128.
+    let actual_address_index: u128 = u128::from(actual_index);
 
-    // Get transparent address at the specified index
-    // Note: For transparent addresses, we use the address index directly
+    // Transparent addresses are derived from their own, narrower
+    // NonHardenedChildIndex space (0..2^31), which does not line up with the
+    // 88-bit shielded diversifier index. Reuse the numeric value when it
+    // happens to fit, and simply have no transparent receiver otherwise.
     let transparent_address = if let Some(tfvk) = ufvk.transparent() {
         match tfvk.derive_external_ivk() {
             Ok(ivk) => {
-                // Convert address_index to NonHardenedChildIndex
-                if let Some(child_index) = NonHardenedChildIndex::from_index(address_index) {
-                    // Derive transparent address at the specified index
-                    match ivk.derive_address(child_index) {
+                let child_index = u32::try_from(address_index)
+                    .ok()
+                    .and_then(NonHardenedChildIndex::from_index);
+                match child_index {
+                    Some(child_index) => match ivk.derive_address(child_index) {
                         Ok(addr) => Some(addr.encode(&network)),
                         Err(_) => None,
-                    }
-                } else {
-                    None
+                    },
+                    None => None,
                 }
             }
             Err(_) => None,
@@ -189,8 +340,10 @@ pub fn derive_wallet(
         account_index,
         address_index: actual_address_index,
         unified_address: ua_encoded,
+        receivers: actual_receivers,
         transparent_address,
         unified_full_viewing_key: ufvk_encoded,
+        birthday: birthday_height.map(AccountBirthday::at_height),
     })
 }
 
@@ -204,8 +357,12 @@ pub fn derive_wallet(
 /// * `seed_phrase` - A valid 24-word BIP39 mnemonic.
 /// * `network` - The network to derive addresses for.
 /// * `account_index` - The account index (BIP32 level 3).
-/// * `start_index` - The starting address/diversifier index.
+/// * `start_index` - The starting ZIP32 diversifier index (0..2^88).
 /// * `count` - Number of addresses to derive.
+/// * `receivers` - Which receiver types each unified address must contain.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). Must match the
+///   passphrase used to generate/restore the wallet, or the derived
+///   addresses will belong to an entirely different account.
 ///
 /// # Returns
 ///
@@ -214,13 +371,15 @@ pub fn derive_unified_addresses(
     seed_phrase: &str,
     network: Network,
     account_index: u32,
-    start_index: u32,
+    start_index: u128,
     count: u32,
+    receivers: ReceiverSelection,
+    passphrase: Option<&str>,
 ) -> Result<Vec<String>, WalletError> {
     let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
         .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
 
-    let seed = mnemonic.to_seed("");
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
 
     // Convert account index to AccountId
     let account = AccountId::try_from(account_index).map_err(|_| {
@@ -237,14 +396,15 @@ pub fn derive_unified_addresses(
     // Get the unified full viewing key
     let ufvk = usk.to_unified_full_viewing_key();
 
+    let request = receivers.to_request()?;
     let mut addresses = Vec::with_capacity(count as usize);
 
     // Derive unified addresses at each diversifier index
-    for i in start_index..(start_index + count) {
-        let diversifier_index = DiversifierIndex::from(i);
-        if let Ok((ua, _)) =
-            ufvk.find_address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys)
-        {
+    for i in start_index..(start_index + count as u128) {
+        let Ok(diversifier_index) = DiversifierIndex::try_from(i) else {
+            break;
+        };
+        if let Ok((ua, _)) = ufvk.find_address(diversifier_index, request) {
             addresses.push(ua.encode(&network));
         }
     }
@@ -252,6 +412,176 @@ pub fn derive_unified_addresses(
     Ok(addresses)
 }
 
+/// Discover unified addresses starting at a diversifier index, preserving
+/// the index that produced each address.
+///
+/// Not every diversifier index yields a valid address for a given receiver
+/// selection - e.g. requiring a transparent receiver restricts indices to
+/// the 31-bit `NonHardenedChildIndex` range. [`derive_unified_addresses`]
+/// silently skips these, so its output can't be mapped back to the
+/// diversifier index that produced it. This function instead walks the
+/// diversifier space one index at a time, pairs every valid address with
+/// its true index, and keeps going past invalid indices using a BIP44-style
+/// gap limit: it gives up only after `gap_limit` consecutive indices in a
+/// row fail to produce a valid address, rather than stopping at the first
+/// gap.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid 24-word BIP39 mnemonic.
+/// * `network` - The network to derive addresses for.
+/// * `account_index` - The account index (BIP32 level 3).
+/// * `start_index` - The starting ZIP32 diversifier index (0..2^88).
+/// * `count` - Number of valid addresses to discover.
+/// * `gap_limit` - Number of consecutive invalid indices to tolerate before
+///   giving up, even if `count` has not yet been reached.
+/// * `receivers` - Which receiver types each unified address must contain.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). Must match the
+///   passphrase used to generate/restore the wallet, or the derived
+///   addresses will belong to an entirely different account.
+///
+/// # Returns
+///
+/// A vector of `(diversifier_index, unified_address)` pairs, in increasing
+/// index order. May contain fewer than `count` entries if the diversifier
+/// space is exhausted or the gap limit is hit first.
+#[allow(clippy::too_many_arguments)]
+pub fn discover_unified_addresses(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    start_index: u128,
+    count: u32,
+    gap_limit: u32,
+    receivers: ReceiverSelection,
+    passphrase: Option<&str>,
+) -> Result<Vec<(u128, String)>, WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    // Convert account index to AccountId
+    let account = AccountId::try_from(account_index).map_err(|_| {
+        WalletError::InvalidAccountIndex(format!(
+            "Account index {} is out of valid range",
+            account_index
+        ))
+    })?;
+
+    // Create UnifiedSpendingKey from seed
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    // Get the unified full viewing key
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let request = receivers.to_request()?;
+    let mut addresses = Vec::with_capacity(count as usize);
+    let mut consecutive_misses = 0u32;
+
+    let mut i = start_index;
+    while addresses.len() < count as usize && consecutive_misses < gap_limit {
+        let Ok(diversifier_index) = DiversifierIndex::try_from(i) else {
+            break;
+        };
+        match ufvk.address(diversifier_index, request) {
+            Ok(ua) => {
+                addresses.push((i, ua.encode(&network)));
+                consecutive_misses = 0;
+            }
+            Err(_) => {
+                consecutive_misses += 1;
+            }
+        }
+        i += 1;
+    }
+
+    Ok(addresses)
+}
+
+/// Recover the ZIP 32 diversifier index that produced one of this account's
+/// own shielded addresses.
+///
+/// Unlike [`derive_unified_addresses`]/[`discover_unified_addresses`], which
+/// walk the diversifier space forwards, this goes the other way: it derives
+/// the account's diversifier key and decrypts `target_address`'s own
+/// diversifier directly, rather than searching. This only works for
+/// Sapling and Orchard receivers - a transparent receiver is derived from
+/// its own, narrower `NonHardenedChildIndex` space that doesn't correspond
+/// to a ZIP 32 diversifier index at all (see [`derive_wallet`]).
+///
+/// # Returns
+///
+/// The recovered diversifier index (0..2^88), as a decimal string.
+pub fn find_diversifier_index(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    target_address: &str,
+    passphrase: Option<&str>,
+) -> Result<String, WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    let account = AccountId::try_from(account_index).map_err(|_| {
+        WalletError::InvalidAccountIndex(format!(
+            "Account index {} is out of valid range",
+            account_index
+        ))
+    })?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let not_owned = || WalletError::AddressNotOwned(target_address.to_string());
+
+    let address = Address::decode(&network, target_address.trim()).ok_or_else(not_owned)?;
+
+    let index = match address {
+        Address::Unified(ua) => {
+            if let Some(orchard_addr) = ua.orchard() {
+                diversifier_index_for_orchard(&ufvk, orchard_addr).ok_or_else(not_owned)?
+            } else if let Some(sapling_addr) = ua.sapling() {
+                diversifier_index_for_sapling(&ufvk, sapling_addr).ok_or_else(not_owned)?
+            } else {
+                return Err(not_owned());
+            }
+        }
+        Address::Sapling(sapling_addr) => {
+            diversifier_index_for_sapling(&ufvk, &sapling_addr).ok_or_else(not_owned)?
+        }
+        Address::Transparent(_) | Address::Tex(_) => return Err(not_owned()),
+    };
+
+    Ok(u128::from(index).to_string())
+}
+
+/// Decrypt `address`'s diversifier with this account's Orchard full viewing
+/// key, trying both the external and internal (change) scopes.
+fn diversifier_index_for_orchard(
+    ufvk: &zcash_keys::keys::UnifiedFullViewingKey,
+    address: &orchard::Address,
+) -> Option<DiversifierIndex> {
+    let fvk = ufvk.orchard()?;
+    [orchard::keys::Scope::External, orchard::keys::Scope::Internal]
+        .into_iter()
+        .find_map(|scope| fvk.to_ivk(scope).diversifier_index(address))
+}
+
+/// Decrypt `address`'s diversifier with this account's Sapling diversifiable
+/// full viewing key, trying both the external and internal (change) scopes.
+fn diversifier_index_for_sapling(
+    ufvk: &zcash_keys::keys::UnifiedFullViewingKey,
+    address: &sapling_crypto::PaymentAddress,
+) -> Option<DiversifierIndex> {
+    let fvk = ufvk.sapling()?;
+    fvk.decrypt_diversifier(address).map(|(index, _scope)| index)
+}
+
 /// Derive multiple transparent addresses from a seed phrase.
 ///
 /// This is useful for scanning transactions - we need to check if transparent
@@ -264,6 +594,9 @@ pub fn derive_unified_addresses(
 /// * `account_index` - The account index (BIP32 level 3).
 /// * `start_index` - The starting address index.
 /// * `count` - Number of addresses to derive.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). Must match the
+///   passphrase used to generate/restore the wallet, or the derived
+///   addresses will belong to an entirely different account.
 ///
 /// # Returns
 ///
@@ -274,11 +607,12 @@ pub fn derive_transparent_addresses(
     account_index: u32,
     start_index: u32,
     count: u32,
+    passphrase: Option<&str>,
 ) -> Result<Vec<String>, WalletError> {
     let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
         .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
 
-    let seed = mnemonic.to_seed("");
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
 
     // Convert account index to AccountId
     let account = AccountId::try_from(account_index).map_err(|_| {
@@ -313,6 +647,214 @@ pub fn derive_transparent_addresses(
     Ok(addresses)
 }
 
+/// Information recovered from inspecting a mnemonic phrase, without deriving
+/// any addresses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MnemonicInspection {
+    /// The raw entropy recovered from the mnemonic phrase, hex-encoded.
+    pub entropy: String,
+    /// The number of words in the mnemonic phrase.
+    pub word_count: usize,
+    /// The BIP39 wordlist language the phrase was parsed as.
+    pub language: String,
+    /// The ZIP 32 seed fingerprint of the 64-byte seed derived from the
+    /// mnemonic, hex-encoded. Two wallets derived from the same seed phrase
+    /// (and passphrase) always share this fingerprint, regardless of account
+    /// or address index.
+    pub seed_fingerprint: String,
+    /// A fingerprint of the account's Unified Full Viewing Key, hex-encoded.
+    pub ufvk_fingerprint: String,
+}
+
+/// Inspect a mnemonic phrase and an account's viewing key without deriving
+/// any addresses.
+///
+/// This is a cheap, address-free identity check: callers can confirm two
+/// wallets share a seed, or that a restored phrase matches a previously
+/// recorded fingerprint, without paying the cost of full address derivation.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid BIP39 mnemonic.
+/// * `network` - The network to derive the account's UFVK for.
+/// * `account_index` - The account index (BIP32 level 3) to fingerprint.
+/// * `passphrase` - Optional BIP39 passphrase ("25th word"). Changes both
+///   fingerprints, since it changes the derived seed.
+///
+/// # Returns
+///
+/// A `MnemonicInspection` describing the phrase and the account's keys.
+pub fn inspect_mnemonic(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    passphrase: Option<&str>,
+) -> Result<MnemonicInspection, WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    let account = AccountId::try_from(account_index).map_err(|_| {
+        WalletError::InvalidAccountIndex(format!(
+            "Account index {} is out of valid range",
+            account_index
+        ))
+    })?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk_encoded = usk.to_unified_full_viewing_key().encode(&network);
+
+    // A BIP39 seed is always 64 bytes, well within the fingerprint's
+    // supported 32..=252 byte range.
+    let seed_fingerprint = SeedFingerprint::from_seed(&seed)
+        .expect("a BIP39-derived seed is always 64 bytes");
+
+    let ufvk_fingerprint = Blake2bParams::new()
+        .hash_length(32)
+        .personal(UFVK_FINGERPRINT_PERSONALIZATION)
+        .to_state()
+        .update(ufvk_encoded.as_bytes())
+        .finalize();
+
+    Ok(MnemonicInspection {
+        entropy: hex::encode(mnemonic.to_entropy()),
+        word_count: mnemonic.word_count(),
+        language: format!("{:?}", mnemonic.language()),
+        seed_fingerprint: hex::encode(seed_fingerprint.to_bytes()),
+        ufvk_fingerprint: hex::encode(ufvk_fingerprint.as_bytes()),
+    })
+}
+
+/// The Argon2id parameters used to derive an encryption key from a
+/// passphrase, recorded alongside the ciphertext so a wallet encrypted with
+/// different parameters can still be decrypted later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost, in KiB.
+    pub memory_kib: u32,
+    /// Number of iterations.
+    pub iterations: u32,
+    /// Degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: ARGON2_MEMORY_KIB,
+            iterations: ARGON2_ITERATIONS,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+/// A `WalletInfo` encrypted at rest under a user passphrase.
+///
+/// The key is derived from the passphrase with Argon2id using a random
+/// salt, and the serialized wallet is sealed with XChaCha20-Poly1305 using
+/// a random 24-byte nonce. Every field needed to decrypt the wallet (other
+/// than the passphrase itself) is stored alongside the ciphertext, so this
+/// struct can be serialized as-is into browser storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedWallet {
+    /// Random salt used to derive the encryption key, hex-encoded.
+    pub salt: String,
+    /// Random nonce used to seal the ciphertext, hex-encoded.
+    pub nonce: String,
+    /// The encrypted, serialized `WalletInfo`, hex-encoded.
+    pub ciphertext: String,
+    /// The KDF parameters used to derive the encryption key.
+    pub kdf_params: KdfParams,
+}
+
+/// Derive a 32-byte encryption key from a passphrase, salt, and KDF params.
+fn derive_encryption_key(
+    passphrase: &str,
+    salt: &[u8],
+    kdf_params: &KdfParams,
+) -> Result<[u8; 32], WalletError> {
+    let params = Params::new(
+        kdf_params.memory_kib,
+        kdf_params.iterations,
+        kdf_params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| WalletError::Decryption(format!("invalid KDF parameters: {}", e)))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| WalletError::Decryption(format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Encrypt a `WalletInfo` under a passphrase for at-rest storage.
+///
+/// The passphrase is stretched into a 256-bit key with Argon2id under a
+/// random salt, and the JSON-serialized wallet is sealed with
+/// XChaCha20-Poly1305 under a random nonce. The returned `EncryptedWallet`
+/// is self-describing: it carries everything but the passphrase needed to
+/// reverse the process with [`decrypt_wallet`].
+pub fn encrypt_wallet(wallet: &WalletInfo, passphrase: &str) -> Result<EncryptedWallet, WalletError> {
+    let kdf_params = KdfParams::default();
+
+    let mut salt = [0u8; ENCRYPTED_WALLET_SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_encryption_key(passphrase, &salt, &kdf_params)?;
+
+    let plaintext = serde_json::to_vec(wallet)
+        .map_err(|e| WalletError::Decryption(format!("failed to serialize wallet: {}", e)))?;
+
+    let cipher = XChaCha20Poly1305::new(key.as_slice().into());
+    let mut nonce_bytes = [0u8; 24];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = XNonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext.as_ref())
+        .map_err(|e| WalletError::Decryption(format!("encryption failed: {}", e)))?;
+
+    Ok(EncryptedWallet {
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce_bytes),
+        ciphertext: hex::encode(ciphertext),
+        kdf_params,
+    })
+}
+
+/// Decrypt a `WalletInfo` previously sealed with [`encrypt_wallet`].
+///
+/// Fails with [`WalletError::Decryption`] if the passphrase is wrong, the
+/// blob is malformed, or the ciphertext has been tampered with - the AEAD
+/// tag makes these indistinguishable, so no more specific error is given.
+pub fn decrypt_wallet(blob: &EncryptedWallet, passphrase: &str) -> Result<WalletInfo, WalletError> {
+    let salt = hex::decode(&blob.salt)
+        .map_err(|e| WalletError::Decryption(format!("invalid salt: {}", e)))?;
+    let nonce_bytes = hex::decode(&blob.nonce)
+        .map_err(|e| WalletError::Decryption(format!("invalid nonce: {}", e)))?;
+    let ciphertext = hex::decode(&blob.ciphertext)
+        .map_err(|e| WalletError::Decryption(format!("invalid ciphertext: {}", e)))?;
+
+    if nonce_bytes.len() != 24 {
+        return Err(WalletError::Decryption(
+            "nonce must be 24 bytes".to_string(),
+        ));
+    }
+
+    let key = derive_encryption_key(passphrase, &salt, &blob.kdf_params)?;
+    let cipher = XChaCha20Poly1305::new(key.as_slice().into());
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext.as_ref())
+        .map_err(|_| WalletError::Decryption("wrong passphrase or corrupted data".to_string()))?;
+
+    serde_json::from_slice(&plaintext)
+        .map_err(|e| WalletError::Decryption(format!("failed to deserialize wallet: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,10 +864,26 @@ mod tests {
 
     #[test]
     fn test_derive_wallet_is_deterministic_testnet() {
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet2 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let wallet2 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         assert_eq!(wallet1.unified_address, wallet2.unified_address);
         assert_eq!(wallet1.transparent_address, wallet2.transparent_address);
@@ -337,8 +895,16 @@ mod tests {
 
     #[test]
     fn test_derive_wallet_testnet_addresses() {
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         // Verify addresses are non-empty and have expected prefixes for testnet
         assert_eq!(wallet.network, NetworkKind::Testnet);
@@ -363,8 +929,16 @@ mod tests {
 
     #[test]
     fn test_derive_wallet_mainnet_addresses() {
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::MainNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         // Verify addresses are non-empty and have expected prefixes for mainnet
         assert_eq!(wallet.network, NetworkKind::Mainnet);
@@ -391,8 +965,16 @@ mod tests {
     fn test_derive_wallet_known_vector_testnet() {
         // This test uses a known seed and verifies exact output
         // If this test fails after a library update, it indicates a breaking change
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         // These are the expected values for the standard BIP39 test vector
         // "abandon abandon ... art" on Zcash testnet
@@ -411,13 +993,29 @@ mod tests {
 
     #[test]
     fn test_different_seeds_produce_different_wallets() {
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         // Different seed phrase
         let different_seed = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
-        let wallet2 = restore_wallet(different_seed, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet2 = restore_wallet(
+            different_seed,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         assert_ne!(
             wallet1.unified_address, wallet2.unified_address,
@@ -435,10 +1033,26 @@ mod tests {
 
     #[test]
     fn test_same_seed_different_networks() {
-        let testnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let mainnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let testnet_wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let mainnet_wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::MainNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         // Same seed should produce different addresses on different networks
         assert_ne!(
@@ -453,15 +1067,31 @@ mod tests {
 
     #[test]
     fn test_restore_invalid_seed_fails() {
-        let result = restore_wallet("invalid seed phrase", Network::TestNetwork, 0, 0);
+        let result = restore_wallet(
+            "invalid seed phrase",
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        );
         assert!(result.is_err(), "should fail with invalid seed phrase");
     }
 
     #[test]
     fn test_generate_wallet_testnet() {
         let entropy = [0u8; 32]; // Deterministic for testing
-        let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-            .expect("wallet generation should succeed");
+        let wallet = generate_wallet(
+            &entropy,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet generation should succeed");
 
         assert!(!wallet.seed_phrase.is_empty());
         assert!(!wallet.unified_address.is_empty());
@@ -475,18 +1105,24 @@ mod tests {
     #[test]
     fn test_generate_wallet_mainnet() {
         let entropy = [0u8; 32]; // Deterministic for testing
-        let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0)
-            .expect("wallet generation should succeed");
+        let wallet = generate_wallet(
+            &entropy,
+            Network::MainNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet generation should succeed");
 
         assert!(!wallet.seed_phrase.is_empty());
         assert!(wallet.unified_address.starts_with("u1"));
-        assert!(
-            wallet
-                .transparent_address
-                .as_ref()
-                .map(|s| s.starts_with("t1"))
-                .unwrap_or(false)
-        );
+        assert!(wallet
+            .transparent_address
+            .as_ref()
+            .map(|s| s.starts_with("t1"))
+            .unwrap_or(false));
         assert!(wallet.unified_full_viewing_key.starts_with("uview1"));
         assert_eq!(wallet.network, NetworkKind::Mainnet);
         assert_eq!(wallet.account_index, 0);
@@ -495,10 +1131,26 @@ mod tests {
 
     #[test]
     fn test_different_account_indices() {
-        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
-            .expect("wallet derivation should succeed");
+        let wallet0 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            1,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         assert_ne!(
             wallet0.unified_address, wallet1.unified_address,
@@ -518,10 +1170,26 @@ mod tests {
 
     #[test]
     fn test_different_address_indices() {
-        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 1)
-            .expect("wallet derivation should succeed");
+        let wallet0 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            1,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
 
         assert_ne!(
             wallet0.unified_address, wallet1.unified_address,
@@ -539,4 +1207,424 @@ mod tests {
         assert_eq!(wallet0.address_index, 0);
         assert_eq!(wallet1.address_index, 1);
     }
+
+    #[test]
+    fn test_address_index_beyond_88_bits_is_rejected() {
+        let result = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            1u128 << 88,
+            ReceiverSelection::default(),
+            None,
+            None,
+        );
+        assert!(
+            matches!(result, Err(WalletError::InvalidAccountIndex(_))),
+            "address index of 2^88 exceeds the ZIP32 diversifier index range"
+        );
+    }
+
+    #[test]
+    fn test_high_diversifier_index_with_transparent_omitted_has_no_transparent_receiver() {
+        // NonHardenedChildIndex only covers 0..2^31, which is far narrower
+        // than the 88-bit shielded diversifier index space, so a caller that
+        // doesn't need a transparent receiver can still derive a UA here.
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            1u128 << 32,
+            ReceiverSelection {
+                orchard: true,
+                sapling: true,
+                transparent: false,
+            },
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        assert!(!wallet.receivers.transparent);
+        assert!(
+            wallet.transparent_address.is_none(),
+            "an address index outside the transparent child-index range should have no t-addr"
+        );
+        assert!(!wallet.unified_address.is_empty());
+    }
+
+    #[test]
+    fn test_high_diversifier_index_with_required_transparent_receiver_errors() {
+        // Requiring a transparent receiver at a diversifier index outside the
+        // transparent child-index range should surface a clear error instead
+        // of silently dropping the receiver.
+        let result = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            1u128 << 32,
+            ReceiverSelection::default(),
+            None,
+            None,
+        );
+        assert!(
+            matches!(result, Err(WalletError::AddressGeneration(_))),
+            "a required transparent receiver that can't be derived should error"
+        );
+    }
+
+    #[test]
+    fn test_orchard_only_receiver_selection() {
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection {
+                orchard: true,
+                sapling: false,
+                transparent: false,
+            },
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        assert_eq!(
+            wallet.receivers,
+            ReceiverSelection {
+                orchard: true,
+                sapling: false,
+                transparent: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_receiver_selection_requires_at_least_one_shielded_receiver() {
+        let result = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection {
+                orchard: false,
+                sapling: false,
+                transparent: true,
+            },
+            None,
+            None,
+        );
+        assert!(
+            matches!(result, Err(WalletError::AddressGeneration(_))),
+            "a unified address must include at least one shielded receiver"
+        );
+    }
+
+    #[test]
+    fn test_inspect_mnemonic_is_deterministic_and_address_free() {
+        let info1 = inspect_mnemonic(TEST_SEED_PHRASE, Network::TestNetwork, 0, None)
+            .expect("inspection should succeed");
+        let info2 = inspect_mnemonic(TEST_SEED_PHRASE, Network::TestNetwork, 0, None)
+            .expect("inspection should succeed");
+
+        assert_eq!(info1.entropy, info2.entropy);
+        assert_eq!(info1.seed_fingerprint, info2.seed_fingerprint);
+        assert_eq!(info1.ufvk_fingerprint, info2.ufvk_fingerprint);
+        assert_eq!(info1.word_count, 24);
+        assert_eq!(info1.language, "English");
+        // The "abandon ... art" test vector is all-zero 32-byte entropy.
+        assert_eq!(info1.entropy, "0".repeat(64));
+    }
+
+    #[test]
+    fn test_inspect_mnemonic_seed_fingerprint_matches_across_accounts() {
+        // The seed fingerprint identifies the seed, not the account, so it
+        // must stay the same across account indices derived from that seed.
+        let account0 = inspect_mnemonic(TEST_SEED_PHRASE, Network::TestNetwork, 0, None)
+            .expect("inspection should succeed");
+        let account1 = inspect_mnemonic(TEST_SEED_PHRASE, Network::TestNetwork, 1, None)
+            .expect("inspection should succeed");
+
+        assert_eq!(account0.seed_fingerprint, account1.seed_fingerprint);
+        assert_ne!(account0.ufvk_fingerprint, account1.ufvk_fingerprint);
+    }
+
+    #[test]
+    fn test_inspect_mnemonic_invalid_phrase_fails() {
+        let result = inspect_mnemonic("invalid seed phrase", Network::TestNetwork, 0, None);
+        assert!(result.is_err(), "should fail with invalid seed phrase");
+    }
+
+    #[test]
+    fn test_passphrase_produces_disjoint_wallet() {
+        let no_passphrase = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let with_passphrase = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            Some("my secret passphrase"),
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        assert_ne!(
+            no_passphrase.unified_full_viewing_key,
+            with_passphrase.unified_full_viewing_key,
+            "a passphrase should produce a completely different account"
+        );
+        assert_ne!(
+            no_passphrase.unified_address, with_passphrase.unified_address,
+            "a passphrase should produce a completely different unified address"
+        );
+    }
+
+    #[test]
+    fn test_empty_passphrase_matches_no_passphrase() {
+        let none_passphrase = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+        let empty_passphrase = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            Some(""),
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        assert_eq!(
+            none_passphrase.unified_full_viewing_key,
+            empty_passphrase.unified_full_viewing_key,
+            "an empty passphrase should match the no-passphrase wallet"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        let encrypted = encrypt_wallet(&wallet, "correct horse battery staple")
+            .expect("encryption should succeed");
+        let decrypted = decrypt_wallet(&encrypted, "correct horse battery staple")
+            .expect("decryption should succeed");
+
+        assert_eq!(decrypted.seed_phrase, wallet.seed_phrase);
+        assert_eq!(
+            decrypted.unified_full_viewing_key,
+            wallet.unified_full_viewing_key
+        );
+        assert_eq!(decrypted.unified_address, wallet.unified_address);
+    }
+
+    #[test]
+    fn test_decrypt_wrong_passphrase_fails() {
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        let encrypted = encrypt_wallet(&wallet, "correct horse battery staple")
+            .expect("encryption should succeed");
+        let result = decrypt_wallet(&encrypted, "wrong passphrase");
+
+        assert!(
+            matches!(result, Err(WalletError::Decryption(_))),
+            "decrypting with the wrong passphrase should fail"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_is_randomized() {
+        let wallet = restore_wallet(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet derivation should succeed");
+
+        let first = encrypt_wallet(&wallet, "passphrase").expect("encryption should succeed");
+        let second = encrypt_wallet(&wallet, "passphrase").expect("encryption should succeed");
+
+        assert_ne!(
+            first.salt, second.salt,
+            "each encryption should use a fresh random salt"
+        );
+        assert_ne!(
+            first.nonce, second.nonce,
+            "each encryption should use a fresh random nonce"
+        );
+        assert_ne!(
+            first.ciphertext, second.ciphertext,
+            "a fresh salt and nonce should produce different ciphertext"
+        );
+    }
+
+    #[test]
+    fn test_discover_unified_addresses_preserves_true_indices() {
+        let discovered = discover_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            5,
+            20,
+            ReceiverSelection::default(),
+            None,
+        )
+        .expect("discovery should succeed");
+
+        assert_eq!(discovered.len(), 5);
+        // With the default receiver selection, indices 0 through 4 all yield
+        // a valid address for this seed, so the indices should be contiguous
+        // starting at 0.
+        let indices: Vec<u128> = discovered.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+        let derived = derive_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            5,
+            ReceiverSelection::default(),
+            None,
+        )
+        .expect("derivation should succeed");
+        let discovered_addresses: Vec<String> =
+            discovered.into_iter().map(|(_, addr)| addr).collect();
+        assert_eq!(discovered_addresses, derived);
+    }
+
+    #[test]
+    fn test_discover_unified_addresses_finds_indices_below_transparent_boundary() {
+        // Starting just below the NonHardenedChildIndex boundary (2^31), a
+        // transparent-required selection should still find the two valid
+        // indices left below the boundary.
+        let start = (1u128 << 31) - 2;
+        let discovered = discover_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            start,
+            2,
+            10,
+            ReceiverSelection::default(),
+            None,
+        )
+        .expect("discovery should succeed");
+
+        assert_eq!(discovered.len(), 2);
+        let indices: Vec<u128> = discovered.iter().map(|(i, _)| *i).collect();
+        assert_eq!(
+            indices,
+            vec![start, start + 1],
+            "valid indices below the transparent child-index boundary should still be found"
+        );
+    }
+
+    #[test]
+    fn test_find_diversifier_index_recovers_a_derived_address() {
+        // `discover_unified_addresses`, unlike `derive_unified_addresses`,
+        // pairs each address with the true index that produced it (not
+        // every index yields a valid diversifier).
+        let discovered = discover_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            42,
+            1,
+            10,
+            ReceiverSelection::default(),
+            None,
+        )
+        .expect("discovery should succeed");
+        let (true_index, address) = &discovered[0];
+
+        let recovered = find_diversifier_index(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            address,
+            None,
+        )
+        .expect("the address should be recognized as our own");
+
+        assert_eq!(recovered, true_index.to_string());
+    }
+
+    #[test]
+    fn test_find_diversifier_index_rejects_unrelated_address() {
+        let result = find_diversifier_index(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            "t1HxutHFt2Sejz7fs92wFVAbsFM7NDjsBG6",
+            None,
+        );
+
+        assert!(matches!(result, Err(WalletError::AddressNotOwned(_))));
+    }
+
+    #[test]
+    fn test_discover_unified_addresses_respects_gap_limit() {
+        // Every index beyond the NonHardenedChildIndex boundary is invalid
+        // for a transparent-required selection, so a small gap limit should
+        // give up before reaching `count`.
+        let discovered = discover_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            1u128 << 31,
+            5,
+            3,
+            ReceiverSelection::default(),
+            None,
+        )
+        .expect("discovery should succeed");
+
+        assert!(
+            discovered.is_empty(),
+            "a gap limit smaller than the invalid region should give up before finding anything"
+        );
+    }
 }
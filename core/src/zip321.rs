@@ -0,0 +1,269 @@
+//! ZIP 321 payment request URIs.
+//!
+//! [ZIP 321](https://zips.z.cash/zip-0321) standardizes a `zcash:` URI for
+//! handing payment details (recipient, amount, memo) to another wallet. This
+//! module is a thin wrapper around the reference [`zip321`] crate, which
+//! already implements the URI grammar, base64url memo encoding, and the
+//! zatoshi amount bounds - there's no reason to re-implement any of that
+//! here.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zcash_address::ZcashAddress;
+use zcash_protocol::memo::{Memo, MemoBytes};
+use zcash_protocol::value::Zatoshis;
+use zip321::{Payment as Zip321Payment, TransactionRequest};
+
+/// Errors that can occur building or parsing a ZIP 321 payment request.
+#[derive(Error, Debug)]
+pub enum PaymentRequestError {
+    #[error("Invalid recipient address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(String),
+
+    #[error("Invalid payment request: {0}")]
+    InvalidRequest(String),
+}
+
+/// A recipient to request payment from, used to build a ZIP 321 URI.
+#[derive(Debug, Clone)]
+pub struct PaymentRecipient {
+    /// The recipient's address, in its original encoded form (unified,
+    /// Sapling, or transparent).
+    pub address: String,
+    /// The requested amount, in zatoshis.
+    pub amount_zatoshis: u64,
+    /// An optional memo. Only valid for a shielded recipient address.
+    pub memo: Option<String>,
+    /// A human-readable label for this payment.
+    pub label: Option<String>,
+    /// A human-readable message describing the purpose of this payment.
+    pub message: Option<String>,
+}
+
+/// A single payment recovered from a ZIP 321 `zcash:` URI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    /// The recipient's address, preserved exactly as encoded in the URI - a
+    /// unified address is never downgraded to one of its component
+    /// protocol addresses.
+    pub recipient_address: String,
+    /// The requested amount, in zatoshis, if specified.
+    pub amount: Option<u64>,
+    /// The memo, decoded to UTF-8 text if it is a ZIP 302 text memo,
+    /// otherwise hex-encoded.
+    pub memo: Option<String>,
+    /// A human-readable label for this payment.
+    pub label: Option<String>,
+    /// A human-readable message describing the purpose of this payment.
+    pub message: Option<String>,
+}
+
+/// Build a ZIP 321 `zcash:` payment request URI for one or more recipients.
+///
+/// A single recipient produces a bare `zcash:<addr>?amount=...` URI; more
+/// than one recipient produces the indexed `address.1`/`amount.1`/... form.
+pub fn build_payment_uri(recipients: &[PaymentRecipient]) -> Result<String, PaymentRequestError> {
+    let mut payments = Vec::with_capacity(recipients.len());
+
+    for recipient in recipients {
+        let address = ZcashAddress::try_from_encoded(&recipient.address)
+            .map_err(|e| PaymentRequestError::InvalidAddress(e.to_string()))?;
+
+        let amount = Zatoshis::from_u64(recipient.amount_zatoshis)
+            .map_err(|e| PaymentRequestError::InvalidAmount(e.to_string()))?;
+
+        let memo = recipient
+            .memo
+            .as_ref()
+            .map(|text| MemoBytes::from_bytes(text.as_bytes()))
+            .transpose()
+            .map_err(|e| PaymentRequestError::InvalidMemo(e.to_string()))?;
+
+        let payment = Zip321Payment::new(
+            address,
+            Some(amount),
+            memo,
+            recipient.label.clone(),
+            recipient.message.clone(),
+            vec![],
+        )
+        .map_err(|e| PaymentRequestError::InvalidRequest(e.to_string()))?;
+
+        payments.push(payment);
+    }
+
+    let request = TransactionRequest::new(payments)
+        .map_err(|e| PaymentRequestError::InvalidRequest(e.to_string()))?;
+
+    Ok(request.to_uri())
+}
+
+/// Parse a ZIP 321 `zcash:` payment request URI into its payments.
+pub fn parse_payment_uri(uri: &str) -> Result<Vec<Payment>, PaymentRequestError> {
+    let request = TransactionRequest::from_uri(uri)
+        .map_err(|e| PaymentRequestError::InvalidRequest(e.to_string()))?;
+
+    Ok(request
+        .payments()
+        .values()
+        .map(|payment| {
+            let memo = payment.memo().map(|memo_bytes| {
+                match Memo::try_from(memo_bytes) {
+                    Ok(Memo::Text(text)) => String::from(text),
+                    _ => hex::encode(memo_bytes.as_slice()),
+                }
+            });
+
+            Payment {
+                recipient_address: payment.recipient_address().encode(),
+                amount: payment.amount().map(Zatoshis::into_u64),
+                memo,
+                label: payment.label().cloned(),
+                message: payment.message().cloned(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{ReceiverSelection, generate_wallet};
+    use zcash_protocol::consensus::Network;
+
+    fn test_unified_address() -> String {
+        let wallet = generate_wallet(
+            &[7u8; 32],
+            Network::MainNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet generation should succeed");
+        wallet.unified_address
+    }
+
+    fn test_transparent_address() -> String {
+        let wallet = generate_wallet(
+            &[7u8; 32],
+            Network::MainNetwork,
+            0,
+            0,
+            ReceiverSelection::default(),
+            None,
+            None,
+        )
+        .expect("wallet generation should succeed");
+        wallet
+            .transparent_address
+            .expect("default receiver selection includes a transparent receiver")
+    }
+
+    #[test]
+    fn test_build_and_parse_single_recipient_round_trip() {
+        let unified_address = test_unified_address();
+        let recipients = vec![PaymentRecipient {
+            address: unified_address.clone(),
+            amount_zatoshis: 100_000,
+            memo: Some("thanks!".to_string()),
+            label: Some("coffee".to_string()),
+            message: Some("for the coffee".to_string()),
+        }];
+
+        let uri = build_payment_uri(&recipients).expect("building a URI should succeed");
+        assert!(uri.starts_with("zcash:"));
+
+        let payments = parse_payment_uri(&uri).expect("parsing the URI should succeed");
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].recipient_address, unified_address);
+        assert_eq!(payments[0].amount, Some(100_000));
+        assert_eq!(payments[0].memo.as_deref(), Some("thanks!"));
+        assert_eq!(payments[0].label.as_deref(), Some("coffee"));
+        assert_eq!(payments[0].message.as_deref(), Some("for the coffee"));
+    }
+
+    #[test]
+    fn test_build_multi_recipient_uses_indexed_params() {
+        let unified_address = test_unified_address();
+        let transparent_address = test_transparent_address();
+        let recipients = vec![
+            PaymentRecipient {
+                address: unified_address.clone(),
+                amount_zatoshis: 1_000,
+                memo: None,
+                label: None,
+                message: None,
+            },
+            PaymentRecipient {
+                address: transparent_address.clone(),
+                amount_zatoshis: 2_000,
+                memo: None,
+                label: None,
+                message: None,
+            },
+        ];
+
+        let uri = build_payment_uri(&recipients).expect("building a URI should succeed");
+        assert!(
+            uri.contains("address."),
+            "multi-recipient URIs should use indexed address params"
+        );
+        assert!(
+            uri.contains("amount."),
+            "multi-recipient URIs should use indexed amount params"
+        );
+
+        let payments = parse_payment_uri(&uri).expect("parsing the URI should succeed");
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].recipient_address, unified_address);
+        assert_eq!(payments[1].recipient_address, transparent_address);
+    }
+
+    #[test]
+    fn test_memo_to_transparent_address_is_rejected() {
+        let recipients = vec![PaymentRecipient {
+            address: test_transparent_address(),
+            amount_zatoshis: 1_000,
+            memo: Some("not allowed".to_string()),
+            label: None,
+            message: None,
+        }];
+
+        let result = build_payment_uri(&recipients);
+        assert!(
+            matches!(result, Err(PaymentRequestError::InvalidRequest(_))),
+            "a memo to a transparent-only address should be rejected"
+        );
+    }
+
+    #[test]
+    fn test_invalid_address_is_rejected() {
+        let recipients = vec![PaymentRecipient {
+            address: "not a zcash address".to_string(),
+            amount_zatoshis: 1_000,
+            memo: None,
+            label: None,
+            message: None,
+        }];
+
+        let result = build_payment_uri(&recipients);
+        assert!(matches!(
+            result,
+            Err(PaymentRequestError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid_uri_fails() {
+        let result = parse_payment_uri("not a zcash uri");
+        assert!(result.is_err());
+    }
+}
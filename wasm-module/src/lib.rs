@@ -19,17 +19,21 @@
 use wasm_bindgen::prelude::*;
 
 use rand::RngCore;
-use zcash_address::unified::{self, Container, Encoding};
-use zcash_primitives::transaction::Transaction;
-use zcash_protocol::consensus::{Network, NetworkType};
+use thiserror::Error;
+use zcash_address::unified::{Address as UnifiedAddress, Container, Encoding, Receiver};
+use zcash_keys::encoding::AddressCodec;
+use zcash_protocol::consensus::Network;
 
 // Re-export types from core library
 pub use zcash_wallet_core::{
-    DecryptedOrchardAction, DecryptedSaplingOutput, DecryptedTransaction, DecryptionResult,
-    NetworkKind, NoteCollection, Pool, ScanResult, ScanTransactionResult, ScannedNote,
+    AddressInfo, DataInspection, DataKind, DecryptedOrchardAction, DecryptedSaplingOutput,
+    DecryptedSentOutput, DecryptedTransaction, DecryptionResult, DetailedBalanceResult,
+    EncryptedWalletResult, InspectionResult, Memo, NetworkKind, NoteCollection,
+    NoteConsolidationResult, NoteSelectionResult, Payment, Pool, RollbackResult,
+    ScanCompactBlocksResult, ScanResult, ScanTransactionResult, ScannedNote,
     ScannedTransparentOutput, SpentNullifier, StorageResult, StoredNote, StoredWallet,
-    TransparentInput, TransparentOutput, TransparentSpend, ViewingKeyInfo, WalletCollection,
-    WalletResult,
+    TransferType, TransparentInput, TransparentOutput, TransparentSpend, TxInspection,
+    ViewingKeyInfo, WalletCollection, WalletResult, parse_payment_uri,
 };
 
 /// Log to browser console
@@ -40,7 +44,7 @@ fn console_log(msg: &str) {
 /// Parse and validate a viewing key
 #[wasm_bindgen]
 pub fn parse_viewing_key(key: &str) -> String {
-    let result = parse_viewing_key_inner(key);
+    let result = zcash_wallet_core::parse_viewing_key(key);
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&ViewingKeyInfo {
             valid: false,
@@ -54,85 +58,160 @@ pub fn parse_viewing_key(key: &str) -> String {
     })
 }
 
-fn network_type_to_kind(network: NetworkType) -> NetworkKind {
-    match network {
-        NetworkType::Main => NetworkKind::Mainnet,
-        NetworkType::Test => NetworkKind::Testnet,
-        NetworkType::Regtest => NetworkKind::Regtest,
-    }
+/// Parse and classify a recipient address
+#[wasm_bindgen]
+pub fn parse_address(address: &str, network: &str) -> String {
+    let network_kind = NetworkKind::from(parse_network(network));
+    let result = zcash_wallet_core::parse_address(address, network_kind);
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&AddressInfo {
+            valid: false,
+            kind: String::new(),
+            receivers: Vec::new(),
+            network: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
 }
 
-fn parse_viewing_key_inner(key: &str) -> ViewingKeyInfo {
-    let key = key.trim();
+/// Auto-detect and structurally decode an arbitrary, user-pasted Zcash
+/// datum: a raw transaction, an address, or a viewing key.
+#[wasm_bindgen]
+pub fn inspect(input: &str, network: &str) -> String {
+    let network_kind = NetworkKind::from(parse_network(network));
+    let result = zcash_wallet_core::inspect(input, network_kind);
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&DataInspection {
+            kind: DataKind::Unrecognized,
+            transaction: None,
+            address: None,
+            viewing_key: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
+}
 
-    // Try parsing as Unified Full Viewing Key (UFVK)
-    if let Ok((network, ufvk)) = unified::Ufvk::decode(key) {
-        let items = ufvk.items();
-        let has_sapling = items
-            .iter()
-            .any(|item| matches!(item, unified::Fvk::Sapling(_)));
-        let has_orchard = items
-            .iter()
-            .any(|item| matches!(item, unified::Fvk::Orchard(_)));
+/// Caller-supplied expectations for [`decode_and_inspect`] to check a
+/// decoded datum against. Any field left unset skips that check.
+#[derive(serde::Deserialize, Default)]
+struct InspectContext {
+    /// Expected network ("mainnet" or "testnet"). Checked against a
+    /// decoded address's or viewing key's network.
+    #[serde(default)]
+    expected_network: Option<String>,
+    /// Expected consensus branch name (e.g. "nu6"). Checked against a
+    /// decoded transaction's `branch_id`.
+    #[serde(default)]
+    expected_branch_id: Option<String>,
+}
 
-        return ViewingKeyInfo {
-            valid: true,
-            key_type: "UFVK".to_string(),
-            has_sapling,
-            has_orchard,
-            network: Some(network_type_to_kind(network)),
-            error: None,
-        };
-    }
+/// Result of [`decode_and_inspect`]: the auto-detected structural decode,
+/// plus any contextual mismatches found against `context_json`.
+#[derive(serde::Serialize)]
+struct DecodeAndInspectResult {
+    kind: DataKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction: Option<TxInspection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<AddressInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    viewing_key: Option<ViewingKeyInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
 
-    // Try parsing as Unified Incoming Viewing Key (UIVK)
-    if let Ok((network, _uivk)) = unified::Uivk::decode(key) {
-        return ViewingKeyInfo {
-            valid: true,
-            key_type: "UIVK".to_string(),
-            has_sapling: true,
-            has_orchard: true,
-            network: Some(network_type_to_kind(network)),
-            error: None,
-        };
-    }
+/// Auto-detect and structurally decode an arbitrary, user-pasted Zcash
+/// datum - a txid, any address type, a viewing key, or a raw transaction -
+/// like [`inspect`], but also check the result against an optional JSON
+/// `context`, surfacing any mismatch as a `warnings` entry rather than
+/// failing outright. Mirrors the `zcash-inspect` CLI tool's design: paste
+/// an opaque blob, get back a rich decode plus whatever contextual checks
+/// the caller cares about.
+///
+/// # Arguments
+///
+/// * `data` - The txid, address, viewing key, or raw transaction hex to inspect
+/// * `context_json` - `{}` or `{"expected_network"?: string, "expected_branch_id"?: string}`
+///
+/// # Returns
+///
+/// JSON with `{kind, transaction?, address?, viewing_key?, error?, warnings: string[]}`
+#[wasm_bindgen]
+pub fn decode_and_inspect(data: &str, context_json: &str) -> String {
+    let context: InspectContext = if context_json.trim().is_empty() {
+        InspectContext::default()
+    } else {
+        serde_json::from_str(context_json).unwrap_or_default()
+    };
 
-    // Try parsing as legacy Sapling extended viewing key
-    // These start with "zxviews" (mainnet) or "zxviewtestsapling" (testnet)
-    if key.starts_with("zxviews") || key.starts_with("zxviewtestsapling") {
-        let network = if key.starts_with("zxviews") {
-            NetworkKind::Mainnet
-        } else {
-            NetworkKind::Testnet
-        };
+    // `inspect` needs a network to validate an address/transaction against;
+    // default to mainnet like every other entry point in this module when
+    // the context doesn't name one.
+    let network = context.expected_network.as_deref().unwrap_or("mainnet");
+    let network_kind = NetworkKind::from(parse_network(network));
+    let inspection = zcash_wallet_core::inspect(data, network_kind);
 
-        // Basic validation - proper bech32 decoding
-        if bech32::decode(key).is_ok() {
-            return ViewingKeyInfo {
-                valid: true,
-                key_type: "Sapling ExtFVK".to_string(),
-                has_sapling: true,
-                has_orchard: false,
-                network: Some(network),
-                error: None,
-            };
+    let mut warnings = Vec::new();
+
+    if let (Some(expected), Some(tx)) = (&context.expected_branch_id, &inspection.transaction) {
+        if expected != &tx.branch_id {
+            warnings.push(format!(
+                "Expected consensus branch '{}', transaction deserialized under '{}'",
+                expected, tx.branch_id
+            ));
         }
     }
 
-    ViewingKeyInfo {
-        valid: false,
-        key_type: String::new(),
-        has_sapling: false,
-        has_orchard: false,
-        network: None,
-        error: Some("Unrecognized viewing key format".to_string()),
+    if let Some(expected) = &context.expected_network {
+        let actual_network = inspection
+            .address
+            .as_ref()
+            .and_then(|a| a.network)
+            .or_else(|| inspection.viewing_key.as_ref().and_then(|k| k.network));
+
+        if let Some(actual_network) = actual_network {
+            let expected_kind = NetworkKind::from(parse_network(expected));
+            if actual_network != expected_kind {
+                warnings.push(format!(
+                    "Expected network '{}', datum is valid for '{}'",
+                    expected,
+                    actual_network.as_str()
+                ));
+            }
+        }
     }
+
+    let result = DecodeAndInspectResult {
+        kind: inspection.kind,
+        transaction: inspection.transaction,
+        address: inspection.address,
+        viewing_key: inspection.viewing_key,
+        error: inspection.error,
+        warnings,
+    };
+
+    serde_json::to_string(&result).unwrap_or_else(|_| {
+        r#"{"kind":"unrecognized","error":"Serialization error","warnings":[]}"#.to_string()
+    })
 }
 
-/// Decrypt a transaction using the provided viewing key
+/// Decrypt a transaction using the provided viewing key.
+///
+/// `height` is the block height the transaction was (or will be) mined at,
+/// needed to apply the correct Sapling ZIP-212 rule during trial decryption;
+/// pass `None` if unknown, which assumes ZIP-212 is fully enforced.
 #[wasm_bindgen]
-pub fn decrypt_transaction(raw_tx_hex: &str, viewing_key: &str, network: &str) -> String {
-    let result = decrypt_transaction_inner(raw_tx_hex, viewing_key, network);
+pub fn decrypt_transaction(
+    raw_tx_hex: &str,
+    viewing_key: &str,
+    network: &str,
+    height: Option<u32>,
+) -> String {
+    let result = decrypt_transaction_inner(raw_tx_hex, viewing_key, network, height);
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&DecryptionResult {
             success: false,
@@ -147,52 +226,61 @@ fn decrypt_transaction_inner(
     raw_tx_hex: &str,
     viewing_key: &str,
     network: &str,
+    height: Option<u32>,
 ) -> DecryptionResult {
     console_log(&format!("Decrypting transaction with network: {}", network));
 
-    // Decode the raw transaction hex
-    let tx_bytes = match hex::decode(raw_tx_hex.trim()) {
-        Ok(bytes) => bytes,
+    let parsed_network = parse_network(network);
+
+    let tx = match zcash_wallet_core::parse_transaction(raw_tx_hex, parsed_network) {
+        Ok(tx) => tx,
         Err(e) => {
             return DecryptionResult {
                 success: false,
                 transaction: None,
-                error: Some(format!("Failed to decode transaction hex: {}", e)),
+                error: Some(format!("Failed to parse transaction: {}", e)),
             };
         }
     };
 
-    // Parse the transaction
-    let tx = match Transaction::read(&tx_bytes[..], zcash_primitives::consensus::BranchId::Nu6) {
-        Ok(tx) => tx,
+    let txid = tx.txid().to_string();
+    console_log(&format!("Parsed transaction: {}", txid));
+
+    // No commitment tree position is available here, so Sapling nullifiers
+    // (which need a tree position) stay unset, while Orchard nullifiers are
+    // still derived from the full viewing key; `height` is still
+    // threaded through so Sapling's ZIP-212 grace-window rule is applied
+    // correctly rather than assuming full enforcement.
+    let scan_result = match zcash_wallet_core::scan_transaction(
+        &tx,
+        viewing_key.trim(),
+        parsed_network,
+        height,
+        None,
+        None,
+    ) {
+        Ok(result) => result,
         Err(e) => {
-            // Try with earlier branch IDs
-            match Transaction::read(&tx_bytes[..], zcash_primitives::consensus::BranchId::Nu5) {
-                Ok(tx) => tx,
-                Err(_) => {
-                    return DecryptionResult {
-                        success: false,
-                        transaction: None,
-                        error: Some(format!("Failed to parse transaction: {}", e)),
-                    };
-                }
-            }
+            return DecryptionResult {
+                success: false,
+                transaction: None,
+                error: Some(format!("Failed to scan transaction: {}", e)),
+            };
         }
     };
 
-    let txid = tx.txid().to_string();
-    console_log(&format!("Parsed transaction: {}", txid));
-
     let mut decrypted = DecryptedTransaction {
         txid,
         sapling_outputs: Vec::new(),
         orchard_actions: Vec::new(),
         transparent_inputs: Vec::new(),
         transparent_outputs: Vec::new(),
+        sent_outputs: Vec::new(),
         fee: None,
     };
 
-    // Extract transparent inputs and outputs
+    // Extract transparent inputs and outputs directly from the bundle, since
+    // `ScanResult` doesn't carry the raw scriptPubKey bytes.
     if let Some(transparent_bundle) = tx.transparent_bundle() {
         for (i, input) in transparent_bundle.vin.iter().enumerate() {
             let prevout = input.prevout();
@@ -204,7 +292,6 @@ fn decrypt_transaction_inner(
         }
 
         for (i, output) in transparent_bundle.vout.iter().enumerate() {
-            // Serialize the script to bytes
             let mut script_bytes = Vec::new();
             let _ = output.script_pubkey().write(&mut script_bytes);
 
@@ -212,95 +299,56 @@ fn decrypt_transaction_inner(
                 index: i,
                 value: u64::from(output.value()),
                 script_pubkey: hex::encode(&script_bytes),
-                address: None, // TODO: decode address from script
+                address: output.recipient_address().map(|addr| addr.encode(&parsed_network)),
             });
         }
     }
 
-    // Parse viewing key and attempt decryption
-    let viewing_key = viewing_key.trim();
-
-    // Try as UFVK
-    if let Ok((_network, ufvk)) = unified::Ufvk::decode(viewing_key) {
-        // Extract Sapling FVK if present
-        for item in ufvk.items() {
-            if let unified::Fvk::Sapling(_sapling_bytes) = item
-                && let Some(sapling_bundle) = tx.sapling_bundle()
-            {
-                console_log(&format!(
-                    "Attempting to decrypt {} Sapling outputs",
-                    sapling_bundle.shielded_outputs().len()
-                ));
-
-                // Try to decrypt each Sapling output
-                for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
-                    // Note: Full decryption requires more context (height, etc.)
-                    // For now, we'll extract what we can from the output
-                    let cmu = output.cmu();
-                    decrypted.sapling_outputs.push(DecryptedSaplingOutput {
-                        index: i,
-                        value: 0, // Requires successful decryption
-                        memo: String::new(),
-                        address: None,
-                        note_commitment: hex::encode(cmu.to_bytes()),
-                        nullifier: None,
-                    });
-                }
-            }
-
-            if let unified::Fvk::Orchard(_orchard_bytes) = item
-                && let Some(orchard_bundle) = tx.orchard_bundle()
-            {
-                console_log(&format!(
-                    "Attempting to decrypt {} Orchard actions",
-                    orchard_bundle.actions().len()
-                ));
-
-                for (i, action) in orchard_bundle.actions().iter().enumerate() {
-                    let cmx = action.cmx();
-                    decrypted.orchard_actions.push(DecryptedOrchardAction {
-                        index: i,
-                        value: 0, // Requires successful decryption
-                        memo: String::new(),
-                        address: None,
-                        note_commitment: hex::encode(cmx.to_bytes()),
-                        nullifier: Some(hex::encode(action.nullifier().to_bytes())),
-                    });
-                }
+    // Sapling and Orchard notes come from `scan_transaction`, which already
+    // trial-decrypts against the external key, falls back to the internal
+    // (change) key, and finally the outgoing viewing key, recording which one
+    // succeeded via `transfer_type`. Notes recovered via the outgoing viewing
+    // key are sent outputs rather than received ones, so they're reported
+    // separately in `sent_outputs`.
+    for note in scan_result.notes {
+        if note.transfer_type == Some(TransferType::Outgoing) {
+            if matches!(note.pool, Pool::Sapling | Pool::Orchard) {
+                decrypted.sent_outputs.push(DecryptedSentOutput {
+                    index: note.output_index,
+                    pool: note.pool,
+                    value: note.value,
+                    memo: note.memo.unwrap_or(Memo::Empty),
+                    recipient_address: note.address,
+                    note_commitment: note.commitment,
+                });
             }
+            continue;
         }
-    }
-
-    // If no UFVK decryption happened, still extract basic info from bundles
-    if decrypted.sapling_outputs.is_empty()
-        && let Some(sapling_bundle) = tx.sapling_bundle()
-    {
-        for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
-            let cmu = output.cmu();
-            decrypted.sapling_outputs.push(DecryptedSaplingOutput {
-                index: i,
-                value: 0,
-                memo: "(encrypted)".to_string(),
-                address: None,
-                note_commitment: hex::encode(cmu.to_bytes()),
-                nullifier: None,
-            });
-        }
-    }
 
-    if decrypted.orchard_actions.is_empty()
-        && let Some(orchard_bundle) = tx.orchard_bundle()
-    {
-        for (i, action) in orchard_bundle.actions().iter().enumerate() {
-            let cmx = action.cmx();
-            decrypted.orchard_actions.push(DecryptedOrchardAction {
-                index: i,
-                value: 0,
-                memo: "(encrypted)".to_string(),
-                address: None,
-                note_commitment: hex::encode(cmx.to_bytes()),
-                nullifier: Some(hex::encode(action.nullifier().to_bytes())),
-            });
+        match note.pool {
+            Pool::Sapling => {
+                decrypted.sapling_outputs.push(DecryptedSaplingOutput {
+                    index: note.output_index,
+                    value: note.value,
+                    memo: note.memo.unwrap_or(Memo::Empty),
+                    address: note.address,
+                    note_commitment: note.commitment,
+                    nullifier: note.nullifier,
+                    transfer_type: note.transfer_type,
+                });
+            }
+            Pool::Orchard => {
+                decrypted.orchard_actions.push(DecryptedOrchardAction {
+                    index: note.output_index,
+                    value: note.value,
+                    memo: note.memo.unwrap_or(Memo::Empty),
+                    address: note.address,
+                    note_commitment: note.commitment,
+                    nullifier: note.nullifier,
+                    transfer_type: note.transfer_type,
+                });
+            }
+            Pool::Transparent => {}
         }
     }
 
@@ -380,7 +428,13 @@ fn is_leap_year(year: u64) -> bool {
 
 /// Generate a new wallet with a random seed phrase
 #[wasm_bindgen]
-pub fn generate_wallet(network_str: &str, account_index: u32, address_index: u32) -> String {
+pub fn generate_wallet(
+    network_str: &str,
+    account_index: u32,
+    address_index: u32,
+    passphrase: Option<String>,
+    birthday_height: Option<u32>,
+) -> String {
     let network = parse_network(network_str);
     let network_name = if matches!(network, Network::MainNetwork) {
         "mainnet"
@@ -399,37 +453,44 @@ pub fn generate_wallet(network_str: &str, account_index: u32, address_index: u32
         rand::thread_rng().fill_bytes(&mut entropy);
     });
 
-    let result =
-        match zcash_wallet_core::generate_wallet(&entropy, network, account_index, address_index) {
-            Ok(wallet) => {
-                console_log(&format!(
-                    "Wallet generated: {}",
-                    &wallet.unified_address[..20]
-                ));
-                WalletResult {
-                    success: true,
-                    seed_phrase: Some(wallet.seed_phrase),
-                    network: wallet.network,
-                    account_index: wallet.account_index,
-                    address_index: wallet.address_index,
-                    unified_address: Some(wallet.unified_address),
-                    transparent_address: wallet.transparent_address,
-                    unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
-                    error: None,
-                }
+    let result = match zcash_wallet_core::generate_wallet(
+        &entropy,
+        network,
+        account_index,
+        address_index as u128,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase.as_deref(),
+        birthday_height,
+    ) {
+        Ok(wallet) => {
+            console_log(&format!(
+                "Wallet generated: {}",
+                &wallet.unified_address[..20]
+            ));
+            WalletResult {
+                success: true,
+                seed_phrase: Some(wallet.seed_phrase),
+                network: wallet.network,
+                account_index: wallet.account_index,
+                address_index: wallet.address_index,
+                unified_address: Some(wallet.unified_address),
+                transparent_address: wallet.transparent_address,
+                unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
+                error: None,
             }
-            Err(e) => WalletResult {
-                success: false,
-                seed_phrase: None,
-                network: NetworkKind::Mainnet, // Default for error case
-                account_index: 0,
-                address_index: 0,
-                unified_address: None,
-                transparent_address: None,
-                unified_full_viewing_key: None,
-                error: Some(e.to_string()),
-            },
-        };
+        }
+        Err(e) => WalletResult {
+            success: false,
+            seed_phrase: None,
+            network: NetworkKind::Mainnet, // Default for error case
+            account_index: 0,
+            address_index: 0,
+            unified_address: None,
+            transparent_address: None,
+            unified_full_viewing_key: None,
+            error: Some(e.to_string()),
+        },
+    };
 
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&WalletResult {
@@ -454,6 +515,8 @@ pub fn restore_wallet(
     network_str: &str,
     account_index: u32,
     address_index: u32,
+    passphrase: Option<String>,
+    birthday_height: Option<u32>,
 ) -> String {
     let network = parse_network(network_str);
     let network_name = if matches!(network, Network::MainNetwork) {
@@ -466,39 +529,225 @@ pub fn restore_wallet(
         network_name, account_index, address_index
     ));
 
-    let result =
-        match zcash_wallet_core::restore_wallet(seed_phrase, network, account_index, address_index)
-        {
-            Ok(wallet) => {
-                console_log(&format!(
-                    "Wallet restored: {}",
-                    &wallet.unified_address[..20]
-                ));
-                WalletResult {
-                    success: true,
-                    seed_phrase: Some(wallet.seed_phrase),
-                    network: wallet.network,
-                    account_index: wallet.account_index,
-                    address_index: wallet.address_index,
-                    unified_address: Some(wallet.unified_address),
-                    transparent_address: wallet.transparent_address,
-                    unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
-                    error: None,
-                }
+    let result = match zcash_wallet_core::restore_wallet(
+        seed_phrase,
+        network,
+        account_index,
+        address_index as u128,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase.as_deref(),
+        birthday_height,
+    ) {
+        Ok(wallet) => {
+            console_log(&format!(
+                "Wallet restored: {}",
+                &wallet.unified_address[..20]
+            ));
+            WalletResult {
+                success: true,
+                seed_phrase: Some(wallet.seed_phrase),
+                network: wallet.network,
+                account_index: wallet.account_index,
+                address_index: wallet.address_index,
+                unified_address: Some(wallet.unified_address),
+                transparent_address: wallet.transparent_address,
+                unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
+                error: None,
             }
-            Err(e) => WalletResult {
+        }
+        Err(e) => WalletResult {
+            success: false,
+            seed_phrase: None,
+            network: NetworkKind::Mainnet, // Default for error case
+            account_index: 0,
+            address_index: 0,
+            unified_address: None,
+            transparent_address: None,
+            unified_full_viewing_key: None,
+            error: Some(e.to_string()),
+        },
+    };
+
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&WalletResult {
+            success: false,
+            seed_phrase: None,
+            network: NetworkKind::Mainnet, // Default for error case
+            account_index: 0,
+            address_index: 0,
+            unified_address: None,
+            transparent_address: None,
+            unified_full_viewing_key: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
+}
+
+/// Inspect a seed phrase without deriving any addresses.
+///
+/// This is a cheap, address-free identity check: compare the seed
+/// fingerprint across wallets, or the UFVK fingerprint across accounts,
+/// without paying the cost of full address derivation.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid BIP39 mnemonic
+/// * `network` - The network ("mainnet" or "testnet")
+/// * `account_index` - The account index (BIP32 level 3)
+///
+/// # Returns
+///
+/// JSON string containing the inspection result.
+#[wasm_bindgen]
+pub fn inspect_mnemonic(
+    seed_phrase: &str,
+    network_str: &str,
+    account_index: u32,
+    passphrase: Option<String>,
+) -> String {
+    let network = parse_network(network_str);
+
+    let result = match zcash_wallet_core::inspect_mnemonic(
+        seed_phrase,
+        network,
+        account_index,
+        passphrase.as_deref(),
+    ) {
+        Ok(info) => InspectionResult {
+            success: true,
+            entropy: Some(info.entropy),
+            word_count: Some(info.word_count),
+            language: Some(info.language),
+            seed_fingerprint: Some(info.seed_fingerprint),
+            ufvk_fingerprint: Some(info.ufvk_fingerprint),
+            error: None,
+        },
+        Err(e) => InspectionResult {
+            success: false,
+            entropy: None,
+            word_count: None,
+            language: None,
+            seed_fingerprint: None,
+            ufvk_fingerprint: None,
+            error: Some(e.to_string()),
+        },
+    };
+
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&InspectionResult {
+            success: false,
+            entropy: None,
+            word_count: None,
+            language: None,
+            seed_fingerprint: None,
+            ufvk_fingerprint: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
+}
+
+/// Encrypt a wallet for at-rest storage under a passphrase.
+///
+/// Derives a key from the passphrase with Argon2id under a random salt,
+/// and seals the wallet with XChaCha20-Poly1305 under a random nonce. The
+/// returned blob is self-describing and safe to persist in browser storage.
+///
+/// # Arguments
+///
+/// * `wallet_json` - The `WalletResult` JSON produced by `generate_wallet`
+///   or `restore_wallet`.
+/// * `passphrase` - The passphrase to encrypt the wallet under.
+///
+/// # Returns
+///
+/// JSON string containing the encrypted wallet blob.
+#[wasm_bindgen]
+pub fn encrypt_wallet(wallet_json: &str, passphrase: &str) -> String {
+    let result = encrypt_wallet_inner(wallet_json, passphrase);
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&EncryptedWalletResult {
+            success: false,
+            salt: None,
+            nonce: None,
+            ciphertext: None,
+            kdf_params: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
+}
+
+fn encrypt_wallet_inner(wallet_json: &str, passphrase: &str) -> EncryptedWalletResult {
+    let parsed: serde_json::Value = match serde_json::from_str(wallet_json) {
+        Ok(value) => value,
+        Err(e) => {
+            return EncryptedWalletResult {
                 success: false,
-                seed_phrase: None,
-                network: NetworkKind::Mainnet, // Default for error case
-                account_index: 0,
-                address_index: 0,
-                unified_address: None,
-                transparent_address: None,
-                unified_full_viewing_key: None,
-                error: Some(e.to_string()),
-            },
-        };
+                salt: None,
+                nonce: None,
+                ciphertext: None,
+                kdf_params: None,
+                error: Some(format!("Failed to parse wallet JSON: {}", e)),
+            };
+        }
+    };
+
+    let network = match parsed["network"].as_str().unwrap_or("testnet") {
+        "mainnet" => NetworkKind::Mainnet,
+        _ => NetworkKind::Testnet,
+    };
 
+    let wallet = zcash_wallet_core::WalletInfo {
+        seed_phrase: parsed["seed_phrase"].as_str().unwrap_or_default().to_string(),
+        network,
+        account_index: parsed["account_index"].as_u64().unwrap_or(0) as u32,
+        address_index: parsed["address_index"].as_u64().unwrap_or(0) as u128,
+        unified_address: parsed["unified_address"].as_str().unwrap_or_default().to_string(),
+        receivers: zcash_wallet_core::ReceiverSelection::default(),
+        transparent_address: parsed["transparent_address"].as_str().map(|s| s.to_string()),
+        unified_full_viewing_key: parsed["unified_full_viewing_key"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        birthday: None,
+    };
+
+    match zcash_wallet_core::encrypt_wallet(&wallet, passphrase) {
+        Ok(encrypted) => EncryptedWalletResult {
+            success: true,
+            salt: Some(encrypted.salt),
+            nonce: Some(encrypted.nonce),
+            ciphertext: Some(encrypted.ciphertext),
+            kdf_params: Some(encrypted.kdf_params),
+            error: None,
+        },
+        Err(e) => EncryptedWalletResult {
+            success: false,
+            salt: None,
+            nonce: None,
+            ciphertext: None,
+            kdf_params: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Decrypt a wallet previously sealed with `encrypt_wallet`.
+///
+/// # Arguments
+///
+/// * `encrypted_json` - The `EncryptedWalletResult`-shaped JSON blob
+///   produced by `encrypt_wallet`.
+/// * `passphrase` - The passphrase the wallet was encrypted under.
+///
+/// # Returns
+///
+/// JSON string containing the decrypted `WalletResult`.
+#[wasm_bindgen]
+pub fn decrypt_wallet(encrypted_json: &str, passphrase: &str) -> String {
+    let result = decrypt_wallet_inner(encrypted_json, passphrase);
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&WalletResult {
             success: false,
@@ -515,6 +764,50 @@ pub fn restore_wallet(
     })
 }
 
+fn decrypt_wallet_inner(encrypted_json: &str, passphrase: &str) -> WalletResult {
+    let blob: zcash_wallet_core::EncryptedWallet = match serde_json::from_str(encrypted_json) {
+        Ok(blob) => blob,
+        Err(e) => {
+            return WalletResult {
+                success: false,
+                seed_phrase: None,
+                network: NetworkKind::Mainnet,
+                account_index: 0,
+                address_index: 0,
+                unified_address: None,
+                transparent_address: None,
+                unified_full_viewing_key: None,
+                error: Some(format!("Failed to parse encrypted wallet JSON: {}", e)),
+            };
+        }
+    };
+
+    match zcash_wallet_core::decrypt_wallet(&blob, passphrase) {
+        Ok(wallet) => WalletResult {
+            success: true,
+            seed_phrase: Some(wallet.seed_phrase),
+            network: wallet.network,
+            account_index: wallet.account_index,
+            address_index: wallet.address_index,
+            unified_address: Some(wallet.unified_address),
+            transparent_address: wallet.transparent_address,
+            unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
+            error: None,
+        },
+        Err(e) => WalletResult {
+            success: false,
+            seed_phrase: None,
+            network: NetworkKind::Mainnet,
+            account_index: 0,
+            address_index: 0,
+            unified_address: None,
+            transparent_address: None,
+            unified_full_viewing_key: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
 /// Derive multiple unified addresses from a seed phrase.
 ///
 /// This is useful for scanning transactions and verifying receiving addresses.
@@ -537,6 +830,7 @@ pub fn derive_unified_addresses(
     account_index: u32,
     start_index: u32,
     count: u32,
+    passphrase: Option<String>,
 ) -> String {
     let network = parse_network(network_str);
     console_log(&format!(
@@ -548,8 +842,10 @@ pub fn derive_unified_addresses(
         seed_phrase,
         network,
         account_index,
-        start_index,
+        start_index as u128,
         count,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase.as_deref(),
     ) {
         Ok(addresses) => {
             console_log(&format!("Derived {} unified addresses", addresses.len()));
@@ -562,6 +858,66 @@ pub fn derive_unified_addresses(
     }
 }
 
+/// Discover unified addresses starting at a diversifier index, pairing each
+/// address with the true diversifier index that produced it.
+///
+/// Unlike `derive_unified_addresses`, which silently skips invalid
+/// diversifier indices and so loses the correspondence between its output
+/// and the requested indices, this walks the diversifier space one index at
+/// a time and keeps searching past invalid indices using a BIP44-style gap
+/// limit. This is what transaction scanning needs to map a decrypted output
+/// back to the exact diversifier index that generated it.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid 24-word BIP39 mnemonic
+/// * `network` - The network ("mainnet" or "testnet")
+/// * `account_index` - The account index (BIP32 level 3)
+/// * `start_index` - The starting diversifier index
+/// * `count` - Number of valid addresses to discover
+/// * `gap_limit` - Number of consecutive invalid indices to tolerate before
+///   giving up
+///
+/// # Returns
+///
+/// JSON string containing an array of `[diversifier_index, unified_address]` pairs.
+#[wasm_bindgen]
+pub fn discover_unified_addresses(
+    seed_phrase: &str,
+    network_str: &str,
+    account_index: u32,
+    start_index: u32,
+    count: u32,
+    gap_limit: u32,
+    passphrase: Option<String>,
+) -> String {
+    let network = parse_network(network_str);
+    console_log(&format!(
+        "Discovering {} unified addresses for account {} starting at {} (gap limit {})...",
+        count, account_index, start_index, gap_limit
+    ));
+
+    match zcash_wallet_core::discover_unified_addresses(
+        seed_phrase,
+        network,
+        account_index,
+        start_index as u128,
+        count,
+        gap_limit,
+        zcash_wallet_core::ReceiverSelection::default(),
+        passphrase.as_deref(),
+    ) {
+        Ok(addresses) => {
+            console_log(&format!("Discovered {} unified addresses", addresses.len()));
+            serde_json::to_string(&addresses).unwrap_or_else(|_| "[]".to_string())
+        }
+        Err(e) => {
+            console_log(&format!("Failed to discover unified addresses: {}", e));
+            "[]".to_string()
+        }
+    }
+}
+
 /// Derive multiple transparent addresses from a seed phrase.
 ///
 /// This is useful for scanning transactions - we need to check if transparent
@@ -585,6 +941,7 @@ pub fn derive_transparent_addresses(
     account_index: u32,
     start_index: u32,
     count: u32,
+    passphrase: Option<String>,
 ) -> String {
     let network = parse_network(network_str);
     console_log(&format!(
@@ -598,6 +955,7 @@ pub fn derive_transparent_addresses(
         account_index,
         start_index,
         count,
+        passphrase.as_deref(),
     ) {
         Ok(addresses) => {
             console_log(&format!("Derived {} addresses", addresses.len()));
@@ -622,6 +980,11 @@ pub fn derive_transparent_addresses(
 /// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
 /// * `network` - The network ("mainnet" or "testnet")
 /// * `height` - Optional block height (needed for full Sapling decryption)
+/// * `sapling_start_position` - Size of the Sapling commitment tree before
+///   this transaction's outputs, used to compute each found note's absolute
+///   `position` and its nullifier. `None` if not known.
+/// * `orchard_start_position` - As `sapling_start_position`, but for the
+///   Orchard commitment tree.
 ///
 /// # Returns
 ///
@@ -633,8 +996,17 @@ pub fn scan_transaction(
     viewing_key: &str,
     network: &str,
     height: Option<u32>,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
 ) -> String {
-    let result = scan_transaction_inner(raw_tx_hex, viewing_key, network, height);
+    let result = scan_transaction_inner(
+        raw_tx_hex,
+        viewing_key,
+        network,
+        height,
+        sapling_start_position,
+        orchard_start_position,
+    );
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&ScanTransactionResult {
             success: false,
@@ -650,6 +1022,8 @@ fn scan_transaction_inner(
     viewing_key: &str,
     network_str: &str,
     height: Option<u32>,
+    sapling_start_position: Option<u64>,
+    orchard_start_position: Option<u64>,
 ) -> ScanTransactionResult {
     let network = parse_network(network_str);
     console_log(&format!(
@@ -661,7 +1035,14 @@ fn scan_transaction_inner(
         }
     ));
 
-    match zcash_wallet_core::scan_transaction_hex(raw_tx_hex, viewing_key, network, height) {
+    match zcash_wallet_core::scan_transaction_hex(
+        raw_tx_hex,
+        viewing_key,
+        network,
+        height,
+        sapling_start_position,
+        orchard_start_position,
+    ) {
         Ok(result) => {
             console_log(&format!(
                 "Scan complete: {} notes found, {} nullifiers",
@@ -685,6 +1066,70 @@ fn scan_transaction_inner(
     }
 }
 
+/// Scan a stream of compact blocks for notes belonging to a viewing key,
+/// tracking the incremental witnesses needed to later spend them.
+///
+/// # Arguments
+///
+/// * `blocks_bytes` - Back-to-back, length-delimited `CompactBlock` protobuf
+///   messages, as streamed from a lightwalletd `CompactTxStreamer` endpoint
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network ("mainnet" or "testnet")
+///
+/// # Returns
+///
+/// JSON string containing a `ScanCompactBlocksResult` with one entry per
+/// block, each carrying the wallet's notes found in that block together with
+/// their serialized witnesses, and any nullifiers revealed.
+///
+/// Scans each call from empty trees, so note positions and witnesses are
+/// only correct when `blocks_bytes` starts at genesis (or at a height below
+/// which no note commitments exist); calling this repeatedly with disjoint
+/// ranges does not carry witness state forward between calls.
+#[wasm_bindgen]
+pub fn scan_compact_blocks(blocks_bytes: &[u8], viewing_key: &str, network: &str) -> String {
+    let result = scan_compact_blocks_inner(blocks_bytes, viewing_key, network);
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        serde_json::to_string(&ScanCompactBlocksResult {
+            success: false,
+            result: None,
+            error: Some(format!("Serialization error: {}", e)),
+        })
+        .unwrap()
+    })
+}
+
+fn scan_compact_blocks_inner(
+    blocks_bytes: &[u8],
+    viewing_key: &str,
+    network_str: &str,
+) -> ScanCompactBlocksResult {
+    let network = parse_network(network_str);
+    console_log(&format!(
+        "Scanning compact blocks ({} bytes)",
+        blocks_bytes.len()
+    ));
+
+    match zcash_wallet_core::scan_compact_blocks_bytes(blocks_bytes, viewing_key, network) {
+        Ok(results) => {
+            console_log(&format!("Scanned {} compact blocks", results.len()));
+            ScanCompactBlocksResult {
+                success: true,
+                result: Some(results),
+                error: None,
+            }
+        }
+        Err(e) => {
+            console_log(&format!("Compact block scan failed: {}", e));
+            ScanCompactBlocksResult {
+                success: false,
+                result: None,
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Note Storage Operations
 // ============================================================================
@@ -770,6 +1215,9 @@ pub fn create_stored_note(
         address,
         spent_txid: None,
         created_at: created_at.to_string(),
+        received_height: None,
+        spent_height: None,
+        pending_spent_txid: None,
     };
 
     serde_json::to_string(&StorageResult::ok(note))
@@ -848,12 +1296,18 @@ pub fn add_note_to_list(notes_json: &str, note_json: &str) -> String {
 /// * `notes_json` - JSON array of StoredNotes
 /// * `nullifiers_json` - JSON array of SpentNullifier objects
 /// * `spending_txid` - Transaction ID where the notes were spent
+/// * `spending_height` - Block height the spending transaction was mined at
 ///
 /// # Returns
 ///
 /// JSON containing the updated notes array and count of marked notes.
 #[wasm_bindgen]
-pub fn mark_notes_spent(notes_json: &str, nullifiers_json: &str, spending_txid: &str) -> String {
+pub fn mark_notes_spent(
+    notes_json: &str,
+    nullifiers_json: &str,
+    spending_txid: &str,
+    spending_height: u32,
+) -> String {
     let mut collection: NoteCollection = match serde_json::from_str(notes_json) {
         Ok(c) => c,
         Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
@@ -887,7 +1341,7 @@ pub fn mark_notes_spent(notes_json: &str, nullifiers_json: &str, spending_txid:
         }
     };
 
-    let marked_count = collection.mark_spent_by_nullifiers(&nullifiers, spending_txid);
+    let marked_count = collection.mark_spent_by_nullifiers(&nullifiers, spending_txid, spending_height);
 
     serde_json::to_string(&NoteOperationResult {
         success: true,
@@ -908,12 +1362,18 @@ pub fn mark_notes_spent(notes_json: &str, nullifiers_json: &str, spending_txid:
 /// * `notes_json` - JSON array of StoredNotes
 /// * `spends_json` - JSON array of TransparentSpend objects
 /// * `spending_txid` - Transaction ID where the notes were spent
+/// * `spending_height` - Block height the spending transaction was mined at
 ///
 /// # Returns
 ///
 /// JSON containing the updated notes array and count of marked notes.
 #[wasm_bindgen]
-pub fn mark_transparent_spent(notes_json: &str, spends_json: &str, spending_txid: &str) -> String {
+pub fn mark_transparent_spent(
+    notes_json: &str,
+    spends_json: &str,
+    spending_txid: &str,
+    spending_height: u32,
+) -> String {
     let mut collection: NoteCollection = match serde_json::from_str(notes_json) {
         Ok(c) => c,
         Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
@@ -947,7 +1407,173 @@ pub fn mark_transparent_spent(notes_json: &str, spends_json: &str, spending_txid
         }
     };
 
-    let marked_count = collection.mark_spent_by_transparent(&spends, spending_txid);
+    let marked_count = collection.mark_spent_by_transparent(&spends, spending_txid, spending_height);
+
+    serde_json::to_string(&NoteOperationResult {
+        success: true,
+        notes: collection.notes,
+        added: None,
+        marked_count: Some(marked_count),
+        error: None,
+    })
+    .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
+/// Roll back a note collection to `rollback_height` after a chain reorg.
+///
+/// Drops any note received above `rollback_height`, and un-spends (clears
+/// `spent_txid`/`spent_height` on) any note whose recorded spend happened
+/// above it. Rejects rollbacks deeper than `MAX_REORG` blocks behind the
+/// highest height seen in the collection.
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array (or `NoteCollection`) of StoredNotes
+/// * `rollback_height` - The height to roll back to
+///
+/// # Returns
+///
+/// JSON containing the pruned/reverted notes array, plus counts of removed
+/// and un-spent notes.
+#[wasm_bindgen]
+pub fn rollback_notes_to_height(notes_json: &str, rollback_height: u32) -> String {
+    let collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&RollbackResult {
+                    success: false,
+                    notes: vec![],
+                    removed: 0,
+                    unspent: 0,
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let result = zcash_wallet_core::rollback_notes_to_height(&collection, rollback_height);
+
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
+/// Reserve notes against double-spending while their spending transaction is
+/// still unconfirmed.
+///
+/// Finds notes with matching nullifiers and sets their `pending_spent_txid`,
+/// without touching `spent_txid`/`spent_height`. A note that is already
+/// spent or already pending-reserved is left alone.
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array of StoredNotes
+/// * `nullifiers_json` - JSON array of SpentNullifier objects
+/// * `spending_txid` - Transaction ID of the unconfirmed spending transaction
+///
+/// # Returns
+///
+/// JSON containing the updated notes array and count of marked notes.
+#[wasm_bindgen]
+pub fn mark_notes_pending_spent(notes_json: &str, nullifiers_json: &str, spending_txid: &str) -> String {
+    let mut collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&NoteOperationResult {
+                    success: false,
+                    notes: vec![],
+                    added: None,
+                    marked_count: None,
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let nullifiers: Vec<SpentNullifier> = match serde_json::from_str(nullifiers_json) {
+        Ok(n) => n,
+        Err(e) => {
+            return serde_json::to_string(&NoteOperationResult {
+                success: false,
+                notes: collection.notes,
+                added: None,
+                marked_count: None,
+                error: Some(format!("Failed to parse nullifiers: {}", e)),
+            })
+            .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
+        }
+    };
+
+    let marked_count = collection.mark_pending_spent_by_nullifiers(&nullifiers, spending_txid);
+
+    serde_json::to_string(&NoteOperationResult {
+        success: true,
+        notes: collection.notes,
+        added: None,
+        marked_count: Some(marked_count),
+        error: None,
+    })
+    .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
+/// Clear pending-spend reservations for transactions that never confirmed.
+///
+/// Removes `pending_spent_txid` from any note reserved by one of `txids`,
+/// freeing those notes back up for selection.
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array of StoredNotes
+/// * `txids_json` - JSON array of transaction ID strings to clear
+///
+/// # Returns
+///
+/// JSON containing the updated notes array and count of cleared notes.
+#[wasm_bindgen]
+pub fn clear_pending_spends(notes_json: &str, txids_json: &str) -> String {
+    let mut collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&NoteOperationResult {
+                    success: false,
+                    notes: vec![],
+                    added: None,
+                    marked_count: None,
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let txids: Vec<String> = match serde_json::from_str(txids_json) {
+        Ok(t) => t,
+        Err(e) => {
+            return serde_json::to_string(&NoteOperationResult {
+                success: false,
+                notes: collection.notes,
+                added: None,
+                marked_count: None,
+                error: Some(format!("Failed to parse txids: {}", e)),
+            })
+            .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
+        }
+    };
+
+    let marked_count = collection.clear_pending_spends(&txids);
 
     serde_json::to_string(&NoteOperationResult {
         success: true,
@@ -959,6 +1585,83 @@ pub fn mark_transparent_spent(notes_json: &str, spends_json: &str, spending_txid
     .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
 }
 
+/// Plan a self-send that sweeps small ("dust") notes into one.
+///
+/// Selects up to `max_inputs` confirmed, unspent notes in `pool` whose value
+/// is at most `value_threshold` zatoshis (`value_threshold: 0` means no size
+/// limit), smallest first, for the caller to merge into a single note via a
+/// self-send transaction.
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array (or `NoteCollection`) of StoredNotes
+/// * `pool` - Which pool to consolidate ("orchard", "sapling", or "transparent")
+/// * `max_inputs` - Maximum number of notes to select
+/// * `min_confirmations` - Minimum confirmations required for a note to be eligible
+/// * `current_height` - Current chain tip height
+/// * `value_threshold` - Maximum per-note value to be considered dust (0 = no limit)
+///
+/// # Returns
+///
+/// JSON containing the selected notes, their combined value, and the input count.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn plan_note_consolidation(
+    notes_json: &str,
+    pool: &str,
+    max_inputs: u32,
+    min_confirmations: u32,
+    current_height: u32,
+    value_threshold: u64,
+) -> String {
+    let collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&NoteConsolidationResult {
+                    success: false,
+                    selected: vec![],
+                    total_value: 0,
+                    input_count: 0,
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let pool_enum = match pool.to_lowercase().as_str() {
+        "orchard" => Pool::Orchard,
+        "sapling" => Pool::Sapling,
+        "transparent" => Pool::Transparent,
+        _ => {
+            return serde_json::to_string(&NoteConsolidationResult {
+                success: false,
+                selected: vec![],
+                total_value: 0,
+                input_count: 0,
+                error: Some(format!("Invalid pool: {}", pool)),
+            })
+            .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
+        }
+    };
+
+    let result = zcash_wallet_core::plan_note_consolidation(
+        &collection,
+        pool_enum,
+        max_inputs,
+        min_confirmations,
+        current_height,
+        value_threshold,
+    );
+
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
 /// Calculate the balance from a list of notes.
 ///
 /// Returns the total balance and balance broken down by pool.
@@ -1009,6 +1712,53 @@ pub fn calculate_balance(notes_json: &str) -> String {
     .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
 }
 
+/// Calculate a confirmation-aware balance breakdown from a list of notes.
+///
+/// Unlike [`calculate_balance`], which lumps every unspent note together,
+/// this splits the total (and each pool's total) into `confirmed`,
+/// `unconfirmed`, and `spendable` buckets so the UI can show "X available,
+/// Y pending".
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array (or `NoteCollection`) of StoredNotes
+/// * `current_height` - Current chain tip height
+/// * `min_confirmations` - Minimum confirmations required to count as confirmed
+///
+/// # Returns
+///
+/// JSON containing the confirmed/unconfirmed/spendable totals, in aggregate
+/// and per pool.
+#[wasm_bindgen]
+pub fn calculate_balance_detailed(notes_json: &str, current_height: u32, min_confirmations: u32) -> String {
+    let collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&DetailedBalanceResult {
+                    success: false,
+                    confirmed: 0,
+                    unconfirmed: 0,
+                    spendable: 0,
+                    confirmed_by_pool: std::collections::HashMap::new(),
+                    unconfirmed_by_pool: std::collections::HashMap::new(),
+                    spendable_by_pool: std::collections::HashMap::new(),
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let result = zcash_wallet_core::calculate_balance_detailed(&collection, current_height, min_confirmations);
+
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
 /// Get all unspent notes with positive value.
 ///
 /// Filters the notes list to only include notes that haven't been spent
@@ -1054,30 +1804,94 @@ pub fn get_unspent_notes(notes_json: &str) -> String {
     .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
 }
 
-/// Get notes for a specific wallet.
+/// Get notes for a specific wallet.
+///
+/// Filters the notes list to only include notes belonging to the specified wallet.
+///
+/// # Arguments
+///
+/// * `notes_json` - JSON array of StoredNotes
+/// * `wallet_id` - The wallet ID to filter by
+///
+/// # Returns
+///
+/// JSON array of StoredNotes belonging to the wallet.
+#[wasm_bindgen]
+pub fn get_notes_for_wallet(notes_json: &str, wallet_id: &str) -> String {
+    let collection: NoteCollection = match serde_json::from_str(notes_json) {
+        Ok(c) => c,
+        Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
+            Ok(notes) => NoteCollection { notes },
+            Err(e) => {
+                return serde_json::to_string(&NoteOperationResult {
+                    success: false,
+                    notes: vec![],
+                    added: None,
+                    marked_count: None,
+                    error: Some(format!("Failed to parse notes: {}", e)),
+                })
+                .unwrap_or_else(|_| {
+                    r#"{"success":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        },
+    };
+
+    let wallet_notes: Vec<StoredNote> = collection
+        .notes_for_wallet(wallet_id)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    serde_json::to_string(&NoteOperationResult {
+        success: true,
+        notes: wallet_notes,
+        added: None,
+        marked_count: None,
+        error: None,
+    })
+    .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
+/// Select unspent, confirmed notes to fund a payment of `target_zat`.
 ///
-/// Filters the notes list to only include notes belonging to the specified wallet.
+/// Only shielded (Orchard/Sapling) notes are considered; transparent notes
+/// are never selected. A note counts as confirmed once
+/// `received_height + min_confirmations <= current_height + 1` - a note
+/// with no recorded `received_height` is always treated as unconfirmed.
 ///
 /// # Arguments
 ///
-/// * `notes_json` - JSON array of StoredNotes
-/// * `wallet_id` - The wallet ID to filter by
+/// * `notes_json` - JSON array (or `NoteCollection`) of StoredNotes
+/// * `target_zat` - Payment amount to fund, in zatoshis
+/// * `fee_zat` - Fee to add on top of `target_zat`
+/// * `min_confirmations` - Minimum confirmations required for a note to be spendable
+/// * `current_height` - Current chain tip height, for the confirmation check
 ///
 /// # Returns
 ///
-/// JSON array of StoredNotes belonging to the wallet.
+/// JSON containing the selected notes, total selected, change, and fee - or
+/// `success: false` with the shortfall if eligible funds are insufficient.
 #[wasm_bindgen]
-pub fn get_notes_for_wallet(notes_json: &str, wallet_id: &str) -> String {
+pub fn select_spendable_notes(
+    notes_json: &str,
+    target_zat: u64,
+    fee_zat: u64,
+    min_confirmations: u32,
+    current_height: u32,
+) -> String {
     let collection: NoteCollection = match serde_json::from_str(notes_json) {
         Ok(c) => c,
         Err(_) => match serde_json::from_str::<Vec<StoredNote>>(notes_json) {
             Ok(notes) => NoteCollection { notes },
             Err(e) => {
-                return serde_json::to_string(&NoteOperationResult {
+                return serde_json::to_string(&NoteSelectionResult {
                     success: false,
-                    notes: vec![],
-                    added: None,
-                    marked_count: None,
+                    selected: vec![],
+                    total_selected: 0,
+                    change: 0,
+                    fee: fee_zat,
+                    shortfall: None,
                     error: Some(format!("Failed to parse notes: {}", e)),
                 })
                 .unwrap_or_else(|_| {
@@ -1087,20 +1901,17 @@ pub fn get_notes_for_wallet(notes_json: &str, wallet_id: &str) -> String {
         },
     };
 
-    let wallet_notes: Vec<StoredNote> = collection
-        .notes_for_wallet(wallet_id)
-        .into_iter()
-        .cloned()
-        .collect();
+    let result = zcash_wallet_core::select_spendable_notes(
+        &collection,
+        target_zat,
+        fee_zat,
+        min_confirmations,
+        current_height,
+        false,
+    );
 
-    serde_json::to_string(&NoteOperationResult {
-        success: true,
-        notes: wallet_notes,
-        added: None,
-        marked_count: None,
-        error: None,
-    })
-    .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
 }
 
 // ============================================================================
@@ -1445,17 +2256,106 @@ pub fn get_all_wallets(wallets_json: &str) -> String {
 // Input Validation Functions
 // ============================================================================
 
+/// A stable, machine-readable validation failure, paired with a
+/// human-readable message via `Display`.
+///
+/// Mirrors the typed-error convention used elsewhere in this workspace
+/// (e.g. `SendError`, `PaymentRequestError`) rather than ad hoc strings,
+/// so front-end code can branch on [`ValidationError::code`] instead of
+/// parsing English error text.
+#[derive(Error, Debug)]
+enum ValidationError {
+    #[error("{0} is required")]
+    EmptyInput(&'static str),
+
+    #[error("{field} must be {expected}, got {actual}")]
+    BadLength {
+        field: &'static str,
+        expected: String,
+        actual: usize,
+    },
+
+    #[error("{field} must contain only hexadecimal characters (0-9, a-f, A-F)")]
+    NonHex { field: &'static str },
+
+    #[error("{0}")]
+    WrongNetwork(String),
+
+    #[error("{0}")]
+    ChecksumMismatch(String),
+
+    #[error("{0}")]
+    UnknownAddressFormat(String),
+
+    #[error(
+        "This is a transparent-only unified address (valid under ZIP 316 revision 1), but the \
+         zcash_address version this wallet is built against predates revision 1 and can only \
+         decode unified addresses with at least one shielded receiver: {0}"
+    )]
+    UnsupportedUnifiedAddressRevision(String),
+
+    #[error("Word {word_number} ('{word}') is not in the BIP39 wordlist")]
+    WordNotInList { word_number: usize, word: String },
+
+    #[error("From index must be less than or equal to To index")]
+    InvalidRange,
+
+    #[error("Range too large: {count} addresses requested, maximum is {max}")]
+    RangeTooLarge { count: u32, max: u32 },
+
+    #[error("Account index must be less than {max}, got {actual}")]
+    AccountIndexTooLarge { actual: u32, max: u32 },
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl ValidationError {
+    /// The stable, machine-readable code for this error, e.g. for a
+    /// front-end `switch` instead of parsing [`ValidationError::to_string`].
+    fn code(&self) -> &'static str {
+        match self {
+            ValidationError::EmptyInput(_) => "EMPTY_INPUT",
+            ValidationError::BadLength { .. } => "BAD_LENGTH",
+            ValidationError::NonHex { .. } => "NON_HEX",
+            ValidationError::WrongNetwork(_) => "WRONG_NETWORK",
+            ValidationError::ChecksumMismatch(_) => "CHECKSUM_MISMATCH",
+            ValidationError::UnknownAddressFormat(_) => "UNKNOWN_ADDRESS_FORMAT",
+            ValidationError::UnsupportedUnifiedAddressRevision(_) => {
+                "UNSUPPORTED_UNIFIED_ADDRESS_REVISION"
+            }
+            ValidationError::WordNotInList { .. } => "WORD_NOT_IN_LIST",
+            ValidationError::InvalidRange => "INVALID_RANGE",
+            ValidationError::RangeTooLarge { .. } => "RANGE_TOO_LARGE",
+            ValidationError::AccountIndexTooLarge { .. } => "RANGE_TOO_LARGE",
+            ValidationError::Other(_) => "INVALID_INPUT",
+        }
+    }
+}
+
 /// Result type for validation operations
 #[derive(serde::Serialize, serde::Deserialize)]
 struct ValidationResult {
     valid: bool,
     error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     address_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     word_count: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy_bits: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receivers: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    has_orchard: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    has_sapling: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    has_transparent: Option<bool>,
 }
 
 impl ValidationResult {
@@ -1463,19 +2363,51 @@ impl ValidationResult {
         ValidationResult {
             valid: true,
             error: None,
+            code: None,
             address_type: None,
             word_count: None,
             count: None,
+            entropy_bits: None,
+            receivers: None,
+            has_orchard: None,
+            has_sapling: None,
+            has_transparent: None,
         }
     }
 
+    /// Build a failure result from a raw message with no machine-readable
+    /// `code`. Prefer [`ValidationResult::from_error`] for new call sites.
     fn err(message: impl Into<String>) -> Self {
         ValidationResult {
             valid: false,
             error: Some(message.into()),
+            code: None,
+            address_type: None,
+            word_count: None,
+            count: None,
+            entropy_bits: None,
+            receivers: None,
+            has_orchard: None,
+            has_sapling: None,
+            has_transparent: None,
+        }
+    }
+
+    /// Build a failure result from a typed [`ValidationError`], populating
+    /// both the human-readable `error` message and the stable `code`.
+    fn from_error(error: ValidationError) -> Self {
+        ValidationResult {
+            valid: false,
+            code: Some(error.code().to_string()),
+            error: Some(error.to_string()),
             address_type: None,
             word_count: None,
             count: None,
+            entropy_bits: None,
+            receivers: None,
+            has_orchard: None,
+            has_sapling: None,
+            has_transparent: None,
         }
     }
 }
@@ -1490,29 +2422,32 @@ impl ValidationResult {
 ///
 /// # Returns
 ///
-/// JSON with `{valid: bool, error?: string}`
+/// JSON with `{valid: bool, code?: string, error?: string}`
 #[wasm_bindgen]
 pub fn validate_txid(txid: &str) -> String {
     let txid = txid.trim();
 
     if txid.is_empty() {
-        return serde_json::to_string(&ValidationResult::err("Transaction ID is required"))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::EmptyInput(
+            "Transaction ID",
+        )))
+        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
     if txid.len() != 64 {
-        return serde_json::to_string(&ValidationResult::err(format!(
-            "Transaction ID must be 64 characters, got {}",
-            txid.len()
-        )))
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::BadLength {
+            field: "Transaction ID",
+            expected: "64 characters".to_string(),
+            actual: txid.len(),
+        }))
         .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
     // Check if all characters are valid hex
     if !txid.chars().all(|c| c.is_ascii_hexdigit()) {
-        return serde_json::to_string(&ValidationResult::err(
-            "Transaction ID must contain only hexadecimal characters (0-9, a-f, A-F)",
-        ))
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::NonHex {
+            field: "Transaction ID",
+        }))
         .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
@@ -1520,27 +2455,53 @@ pub fn validate_txid(txid: &str) -> String {
         .unwrap_or_else(|_| r#"{"valid":true}"#.to_string())
 }
 
-/// Validate a Zcash address.
-///
-/// Supports transparent (t-addr), Sapling (zs), and unified addresses (u).
-///
-/// # Arguments
-///
-/// * `address` - The address to validate
-/// * `network` - The network ("mainnet" or "testnet")
-///
-/// # Returns
-///
-/// JSON with `{valid: bool, address_type?: string, error?: string}`
-#[wasm_bindgen]
-pub fn validate_address(address: &str, network: &str) -> String {
-    let address = address.trim();
+/// The two-byte base58check version prefixes for Zcash transparent
+/// addresses, as raw bytes (see the `zcashd` `CBaseChainParams` pubkey/script
+/// address prefixes).
+const TRANSPARENT_P2PKH_MAINNET: [u8; 2] = [0x1C, 0xB8];
+const TRANSPARENT_P2SH_MAINNET: [u8; 2] = [0x1C, 0xBD];
+const TRANSPARENT_P2PKH_TESTNET: [u8; 2] = [0x1D, 0x25];
+const TRANSPARENT_P2SH_TESTNET: [u8; 2] = [0x1C, 0xBA];
+
+/// Decode and verify a base58check string, returning the payload with the
+/// version bytes still attached (i.e. everything but the 4-byte checksum).
+///
+/// Verifies the checksum is the first four bytes of `SHA256(SHA256(payload))`,
+/// per base58check.
+fn decode_base58check(encoded: &str) -> Result<Vec<u8>, ValidationError> {
+    use sha2::Digest;
+
+    let bytes = bs58::decode(encoded).into_vec().map_err(|e| {
+        ValidationError::UnknownAddressFormat(format!("Invalid base58 encoding: {}", e))
+    })?;
+
+    if bytes.len() < 4 {
+        return Err(ValidationError::BadLength {
+            field: "transparent address",
+            expected: "at least 4 bytes (checksum)".to_string(),
+            actual: bytes.len(),
+        });
+    }
 
-    if address.is_empty() {
-        return serde_json::to_string(&ValidationResult::err("Address is required"))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+    let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+
+    let round1 = sha2::Sha256::digest(payload);
+    let round2 = sha2::Sha256::digest(round1);
+    if &round2[..4] != checksum {
+        return Err(ValidationError::ChecksumMismatch(
+            "Transparent address checksum mismatch".to_string(),
+        ));
     }
 
+    Ok(payload.to_vec())
+}
+
+/// Classify and validate a Zcash address by its encoding, verifying real
+/// checksums rather than just a prefix/length.
+///
+/// Shared by [`validate_address`] and [`parse_payment_request`] so both
+/// surfaces agree on what counts as a valid address.
+fn classify_address(address: &str, network: &str) -> Result<&'static str, ValidationError> {
     let is_mainnet = matches!(network.to_lowercase().as_str(), "mainnet" | "main");
 
     // Check for unified address
@@ -1549,89 +2510,330 @@ pub fn validate_address(address: &str, network: &str) -> String {
         if (is_mainnet && !address.starts_with("u1"))
             || (!is_mainnet && !address.starts_with("utest1"))
         {
-            return serde_json::to_string(&ValidationResult::err(format!(
+            return Err(ValidationError::WrongNetwork(format!(
                 "Unified address should start with '{}' for {}",
                 expected_prefix,
                 if is_mainnet { "mainnet" } else { "testnet" }
-            )))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+            )));
         }
 
-        // Try to decode the unified address
-        if zcash_address::unified::Address::decode(address).is_ok() {
-            let mut result = ValidationResult::ok();
-            result.address_type = Some("unified".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"unified"}"#.to_string());
-        }
-        return serde_json::to_string(&ValidationResult::err("Invalid unified address encoding"))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        return match UnifiedAddress::decode(address) {
+            Ok(_) => Ok("unified"),
+            Err(zcash_address::unified::ParseError::OnlyTransparent) => {
+                Err(ValidationError::UnsupportedUnifiedAddressRevision(
+                    address.to_string(),
+                ))
+            }
+            Err(e) => Err(ValidationError::UnknownAddressFormat(format!(
+                "Invalid unified address encoding: {}",
+                e
+            ))),
+        };
     }
 
     // Check for Sapling address
     if address.starts_with("zs") || address.starts_with("ztestsapling") {
-        let expected_prefix = if is_mainnet { "zs" } else { "ztestsapling" };
-        if (is_mainnet && !address.starts_with("zs"))
-            || (!is_mainnet && !address.starts_with("ztestsapling"))
-        {
-            return serde_json::to_string(&ValidationResult::err(format!(
-                "Sapling address should start with '{}' for {}",
-                expected_prefix,
-                if is_mainnet { "mainnet" } else { "testnet" }
-            )))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        let (hrp, payload) = bech32::decode(address).map_err(|e| {
+            ValidationError::UnknownAddressFormat(format!("Invalid bech32 encoding: {}", e))
+        })?;
+
+        let expected_hrp = if is_mainnet { "zs" } else { "ztestsapling" };
+        if hrp.as_str() != expected_hrp {
+            return Err(ValidationError::WrongNetwork(format!(
+                "Sapling address should use HRP '{}' for {}, found '{}'",
+                expected_hrp,
+                if is_mainnet { "mainnet" } else { "testnet" },
+                hrp.as_str()
+            )));
         }
 
-        // Basic bech32 validation
-        if bech32::decode(address).is_ok() {
-            let mut result = ValidationResult::ok();
-            result.address_type = Some("sapling".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"sapling"}"#.to_string());
-        } else {
-            return serde_json::to_string(&ValidationResult::err(
-                "Invalid Sapling address encoding",
-            ))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        // 11-byte diversifier + 32-byte pk_d.
+        if payload.len() != 43 {
+            return Err(ValidationError::BadLength {
+                field: "Sapling address payload",
+                expected: "43 bytes".to_string(),
+                actual: payload.len(),
+            });
         }
+
+        return Ok("sapling");
     }
 
     // Check for transparent address
     if address.starts_with('t') {
-        let expected_prefix = if is_mainnet { "t1" } else { "tm" };
-        if (is_mainnet && !address.starts_with("t1")) || (!is_mainnet && !address.starts_with("tm"))
-        {
-            return serde_json::to_string(&ValidationResult::err(format!(
-                "Transparent address should start with '{}' for {}",
-                expected_prefix,
+        let payload = decode_base58check(address)?;
+
+        // 2-byte version + 20-byte hash160.
+        if payload.len() != 22 {
+            return Err(ValidationError::BadLength {
+                field: "transparent address payload",
+                expected: "22 bytes".to_string(),
+                actual: payload.len(),
+            });
+        }
+
+        let version = [payload[0], payload[1]];
+        return match (version, is_mainnet) {
+            (TRANSPARENT_P2PKH_MAINNET, true) => Ok("transparent_p2pkh"),
+            (TRANSPARENT_P2SH_MAINNET, true) => Ok("transparent_p2sh"),
+            (TRANSPARENT_P2PKH_TESTNET, false) => Ok("transparent_p2pkh"),
+            (TRANSPARENT_P2SH_TESTNET, false) => Ok("transparent_p2sh"),
+            _ => Err(ValidationError::WrongNetwork(format!(
+                "Unrecognized transparent address version for {}",
                 if is_mainnet { "mainnet" } else { "testnet" }
+            ))),
+        };
+    }
+
+    Err(ValidationError::UnknownAddressFormat("Unrecognized address format. Expected unified (u1/utest1), Sapling (zs/ztestsapling), or transparent (t1/t3/tm/t2) address".to_string()))
+}
+
+/// The receiver types contained in a successfully-decoded unified address,
+/// in preference order.
+///
+/// Returns an empty list if `address` doesn't decode as a unified address.
+fn unified_receiver_types(address: &str) -> Vec<&'static str> {
+    let Ok((_, ua)) = UnifiedAddress::decode(address) else {
+        return vec![];
+    };
+
+    ua.items()
+        .iter()
+        .map(|receiver| match receiver {
+            Receiver::Orchard(_) => "orchard",
+            Receiver::Sapling(_) => "sapling",
+            Receiver::P2pkh(_) | Receiver::P2sh(_) => "transparent",
+            Receiver::Unknown { .. } => "unknown",
+        })
+        .collect()
+}
+
+/// Validate a Zcash address.
+///
+/// Supports transparent (t-addr), Sapling (zs), and unified addresses (u).
+/// For a unified address, also reports the set of receivers it contains
+/// (`receivers`, `has_orchard`, `has_sapling`, `has_transparent`), so the UI
+/// can warn before sending to a recipient with no shielded receiver.
+///
+/// Note: the pinned `zcash_address` crate predates ZIP 316 revision 1, so it
+/// still rejects transparent-only unified addresses at decode time and has
+/// no concept of metadata items - a transparent-only UA comes back invalid
+/// with `code: "UNSUPPORTED_UNIFIED_ADDRESS_REVISION"` (distinct from a
+/// genuinely malformed address) rather than silently passing or failing
+/// with a generic error, and expiry height/time cannot be surfaced until
+/// that dependency is upgraded to a revision-1-aware version.
+///
+/// # Arguments
+///
+/// * `address` - The address to validate
+/// * `network` - The network ("mainnet" or "testnet")
+///
+/// # Returns
+///
+/// JSON with `{valid: bool, address_type?: string, receivers?: string[],
+/// has_orchard?: bool, has_sapling?: bool, has_transparent?: bool,
+/// code?: string, error?: string}`
+#[wasm_bindgen]
+pub fn validate_address(address: &str, network: &str) -> String {
+    let address = address.trim();
+
+    if address.is_empty() {
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::EmptyInput(
+            "Address",
+        )))
+        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+    }
+
+    match classify_address(address, network) {
+        Ok(address_type) => {
+            let mut result = ValidationResult::ok();
+            result.address_type = Some(address_type.to_string());
+
+            if address_type == "unified" {
+                let receivers = unified_receiver_types(address);
+                result.has_orchard = Some(receivers.contains(&"orchard"));
+                result.has_sapling = Some(receivers.contains(&"sapling"));
+                result.has_transparent = Some(receivers.contains(&"transparent"));
+                result.receivers = Some(receivers.into_iter().map(String::from).collect());
+            }
+
+            serde_json::to_string(&result).unwrap_or_else(|_| {
+                format!(r#"{{"valid":true,"address_type":"{}"}}"#, address_type)
+            })
+        }
+        Err(error) => serde_json::to_string(&ValidationResult::from_error(error))
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string()),
+    }
+}
+
+/// A single payment recovered from a ZIP 321 request, shaped for a
+/// batch-send confirmation screen.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PaymentRequestPayment {
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address_type: Option<String>,
+    amount_zatoshis: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+/// Result type for [`parse_payment_request`] / [`validate_payment_uri`].
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PaymentRequestResult {
+    valid: bool,
+    payments: Vec<PaymentRequestPayment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl PaymentRequestResult {
+    fn err(message: impl Into<String>) -> Self {
+        PaymentRequestResult {
+            valid: false,
+            payments: vec![],
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Parse and validate every payment in a ZIP 321 `zcash:` payment request URI.
+///
+/// Accepts both the single-recipient form (`zcash:<addr>?amount=...`) and
+/// the indexed multi-recipient form (`address.1`, `amount.1`, ... with the
+/// bare leading address as index 0). Each recovered payment's address is
+/// validated with the same rules as [`validate_address`], and a memo
+/// addressed to a transparent-only recipient is rejected - everything else
+/// (amount bounds, memo length, duplicate/malformed parameters) is enforced
+/// by the ZIP 321 parser itself.
+///
+/// # Arguments
+///
+/// * `uri` - The `zcash:` payment request URI
+/// * `network` - The network ("mainnet" or "testnet")
+///
+/// # Returns
+///
+/// JSON with `{valid, payments: [{address, address_type?, amount_zatoshis,
+/// memo?, label?, message?}], error?}`
+#[wasm_bindgen]
+pub fn parse_payment_request(uri: &str, network: &str) -> String {
+    let parsed: Vec<Payment> = match parse_payment_uri(uri) {
+        Ok(payments) => payments,
+        Err(e) => {
+            return serde_json::to_string(&PaymentRequestResult::err(e.to_string()))
+                .unwrap_or_else(|_| {
+                    r#"{"valid":false,"error":"Serialization error"}"#.to_string()
+                });
+        }
+    };
+
+    if parsed.is_empty() {
+        return serde_json::to_string(&PaymentRequestResult::err(
+            "Payment request has no payments",
+        ))
+        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+    }
+
+    let mut payments = Vec::with_capacity(parsed.len());
+    for payment in parsed {
+        let address_type = match classify_address(&payment.recipient_address, network) {
+            Ok(address_type) => address_type,
+            Err(e) => {
+                return serde_json::to_string(&PaymentRequestResult::err(format!(
+                    "Invalid recipient address '{}': {}",
+                    payment.recipient_address, e
+                )))
+                .unwrap_or_else(|_| {
+                    r#"{"valid":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        };
+
+        let Some(amount_zatoshis) = payment.amount else {
+            return serde_json::to_string(&PaymentRequestResult::err(format!(
+                "Payment to '{}' is missing an amount",
+                payment.recipient_address
             )))
             .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-        }
+        };
 
-        // Basic base58check validation (length check)
-        if address.len() >= 26 && address.len() <= 35 {
-            let mut result = ValidationResult::ok();
-            result.address_type = Some("transparent".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"transparent"}"#.to_string());
-        } else {
-            return serde_json::to_string(&ValidationResult::err(
-                "Invalid transparent address length",
-            ))
+        if address_type.starts_with("transparent") && payment.memo.is_some() {
+            return serde_json::to_string(&PaymentRequestResult::err(format!(
+                "Transparent address '{}' cannot receive a memo",
+                payment.recipient_address
+            )))
             .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
         }
+
+        payments.push(PaymentRequestPayment {
+            address: payment.recipient_address,
+            address_type: Some(address_type.to_string()),
+            amount_zatoshis,
+            memo: payment.memo,
+            label: payment.label,
+            message: payment.message,
+        });
     }
 
-    serde_json::to_string(&ValidationResult::err(
-        "Unrecognized address format. Expected unified (u1/utest1), Sapling (zs/ztestsapling), or transparent (t1/tm) address",
-    ))
+    serde_json::to_string(&PaymentRequestResult {
+        valid: true,
+        payments,
+        error: None,
+    })
     .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string())
 }
 
+/// Validate a ZIP 321 payment request URI without needing the full payment
+/// breakdown.
+///
+/// A thin wrapper around [`parse_payment_request`] for callers that only
+/// need a pass/fail answer before committing to the batch-send flow.
+///
+/// # Returns
+///
+/// JSON with `{valid: bool, error?: string}`
+#[wasm_bindgen]
+pub fn validate_payment_uri(uri: &str, network: &str) -> String {
+    let parsed: PaymentRequestResult =
+        match serde_json::from_str(&parse_payment_request(uri, network)) {
+            Ok(result) => result,
+            Err(e) => {
+                return serde_json::to_string(&ValidationResult::err(format!(
+                    "Serialization error: {}",
+                    e
+                )))
+                .unwrap_or_else(|_| {
+                    r#"{"valid":false,"error":"Serialization error"}"#.to_string()
+                });
+            }
+        };
+
+    let result = if parsed.valid {
+        ValidationResult::ok()
+    } else {
+        ValidationResult::err(
+            parsed
+                .error
+                .unwrap_or_else(|| "Invalid payment request".to_string()),
+        )
+    };
+
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string())
+}
+
 /// Validate a BIP39 seed phrase.
 ///
-/// Checks word count and basic format. Valid phrases have 12, 15, 18, 21, or 24 words.
+/// Performs full BIP39 verification: every word must be in the 2048-word
+/// English wordlist, and the trailing checksum bits (the first `ENT/32`
+/// bits of SHA-256 over the entropy) must match. A bad word and a bad
+/// checksum are reported as distinct errors so a user can tell a typo
+/// from a transcription error.
 ///
 /// # Arguments
 ///
@@ -1639,46 +2841,72 @@ pub fn validate_address(address: &str, network: &str) -> String {
 ///
 /// # Returns
 ///
-/// JSON with `{valid: bool, word_count?: u8, error?: string}`
+/// JSON with `{valid: bool, word_count?: u8, entropy_bits?: u16, code?: string, error?: string}`
 #[wasm_bindgen]
 pub fn validate_seed_phrase(seed_phrase: &str) -> String {
     let seed_phrase = seed_phrase.trim();
 
     if seed_phrase.is_empty() {
-        return serde_json::to_string(&ValidationResult::err("Seed phrase is required"))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-    }
-
-    let words: Vec<&str> = seed_phrase.split_whitespace().collect();
-    let word_count = words.len();
-
-    // Valid BIP39 word counts
-    let valid_counts = [12, 15, 18, 21, 24];
-    if !valid_counts.contains(&word_count) {
-        return serde_json::to_string(&ValidationResult::err(format!(
-            "Seed phrase must have 12, 15, 18, 21, or 24 words, got {}",
-            word_count
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::EmptyInput(
+            "Seed phrase",
         )))
         .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
-    // Check that all words are lowercase alphabetic
-    for word in &words {
-        if !word.chars().all(|c| c.is_ascii_lowercase()) {
-            return serde_json::to_string(&ValidationResult::err(
-                "Seed phrase words must contain only lowercase letters",
+    let words: Vec<&str> = seed_phrase.split_whitespace().collect();
+
+    let mnemonic = match bip39::Mnemonic::parse_in_normalized(bip39::Language::English, seed_phrase)
+    {
+        Ok(mnemonic) => mnemonic,
+        Err(bip39::Error::UnknownWord(index)) => {
+            let word = words.get(index).copied().unwrap_or("?").to_string();
+            return serde_json::to_string(&ValidationResult::from_error(
+                ValidationError::WordNotInList {
+                    word_number: index + 1,
+                    word,
+                },
             ))
             .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
         }
-    }
+        Err(bip39::Error::InvalidChecksum) => {
+            return serde_json::to_string(&ValidationResult::from_error(
+                ValidationError::ChecksumMismatch(
+                    "Checksum mismatch - the words are valid BIP39 words but don't form a valid phrase"
+                        .to_string(),
+                ),
+            ))
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        }
+        Err(bip39::Error::BadWordCount(count)) => {
+            return serde_json::to_string(&ValidationResult::from_error(
+                ValidationError::BadLength {
+                    field: "Seed phrase",
+                    expected: "12, 15, 18, 21, or 24 words".to_string(),
+                    actual: count,
+                },
+            ))
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        }
+        Err(e) => {
+            return serde_json::to_string(&ValidationResult::from_error(ValidationError::Other(
+                e.to_string(),
+            )))
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        }
+    };
+
+    let word_count = mnemonic.word_count();
+    let entropy_bits = (mnemonic.to_entropy().len() * 8) as u16;
 
-    // Basic validation passed (format and word count)
-    // Note: Full BIP39 wordlist validation happens during wallet creation
-    // to minimize dependencies in the WASM module
     let mut result = ValidationResult::ok();
     result.word_count = Some(word_count as u8);
-    serde_json::to_string(&result)
-        .unwrap_or_else(|_| format!(r#"{{"valid":true,"word_count":{}}}"#, word_count))
+    result.entropy_bits = Some(entropy_bits);
+    serde_json::to_string(&result).unwrap_or_else(|_| {
+        format!(
+            r#"{{"valid":true,"word_count":{},"entropy_bits":{}}}"#,
+            word_count, entropy_bits
+        )
+    })
 }
 
 /// Validate an address derivation range.
@@ -1693,23 +2921,23 @@ pub fn validate_seed_phrase(seed_phrase: &str) -> String {
 ///
 /// # Returns
 ///
-/// JSON with `{valid: bool, count?: u32, error?: string}`
+/// JSON with `{valid: bool, count?: u32, code?: string, error?: string}`
 #[wasm_bindgen]
 pub fn validate_address_range(from_index: u32, to_index: u32, max_count: u32) -> String {
     if from_index > to_index {
-        return serde_json::to_string(&ValidationResult::err(
-            "From index must be less than or equal to To index",
-        ))
-        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+        return serde_json::to_string(&ValidationResult::from_error(ValidationError::InvalidRange))
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
     let count = to_index - from_index + 1;
 
     if count > max_count {
-        return serde_json::to_string(&ValidationResult::err(format!(
-            "Range too large: {} addresses requested, maximum is {}",
-            count, max_count
-        )))
+        return serde_json::to_string(&ValidationResult::from_error(
+            ValidationError::RangeTooLarge {
+                count,
+                max: max_count,
+            },
+        ))
         .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
@@ -1729,7 +2957,7 @@ pub fn validate_address_range(from_index: u32, to_index: u32, max_count: u32) ->
 ///
 /// # Returns
 ///
-/// JSON with `{valid: bool, error?: string}`
+/// JSON with `{valid: bool, code?: string, error?: string}`
 #[wasm_bindgen]
 pub fn validate_account_index(index: u32) -> String {
     // BIP32 hardened derivation uses indices >= 2^31
@@ -1737,10 +2965,12 @@ pub fn validate_account_index(index: u32) -> String {
     const MAX_ACCOUNT_INDEX: u32 = 0x7FFFFFFF;
 
     if index > MAX_ACCOUNT_INDEX {
-        return serde_json::to_string(&ValidationResult::err(format!(
-            "Account index must be less than {}, got {}",
-            MAX_ACCOUNT_INDEX, index
-        )))
+        return serde_json::to_string(&ValidationResult::from_error(
+            ValidationError::AccountIndexTooLarge {
+                actual: index,
+                max: MAX_ACCOUNT_INDEX,
+            },
+        ))
         .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 